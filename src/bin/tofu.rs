@@ -2,8 +2,9 @@
 use std::fs::File;
 use std::io::Read;
 use std::io::{stdout, BufWriter};
+use tofu::headless::Headless;
 use tofu::hex_view::view::HexView;
-use tofu::{CurrentBuffer, BuffrCollection};
+use tofu::{config, parse_keys, BuffrCollection, CurrentBuffer, DEFAULT_BYTES_PER_LINE};
 use std::fs::OpenOptions;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
@@ -32,7 +33,6 @@ fn debug_log(message: &str) {
             if log_path.exists() {
                 // If the path exists, attempt to open the file for writing
                 if let Ok(mut file) = OpenOptions::new()
-                    .write(true)
                     .append(true)
                     .open(&log_path) {
 
@@ -47,16 +47,85 @@ fn debug_log(message: &str) {
 }
 
 
+fn usage() -> ! {
+    eprintln!("usage: tofu [--keys <keys> | --script <file>] [filename]");
+    std::process::exit(2);
+}
+
+/// Runs a key sequence against the file without a terminal, returning the exit code.
+fn run_headless(filename: Option<&str>, keys: &str) -> i32 {
+    let keys = match parse_keys(keys) {
+        Ok(keys) => keys,
+        Err(e) => {
+            eprintln!("tofu: {}", e);
+            return 2;
+        }
+    };
+
+    // Scripts edit the whole file, so unlike the view they can't work on a window of it
+    let mut buffr_collection = match filename {
+        Some(filename) => match std::fs::read(filename) {
+            Ok(data) => BuffrCollection::with_current_buffer(CurrentBuffer::from_data_and_path(
+                data,
+                Some(filename),
+            )),
+            Err(e) => {
+                eprintln!("tofu: {}: {}", filename, e);
+                return 1;
+            }
+        },
+        None => BuffrCollection::new(),
+    };
+    for message in config::load(&mut buffr_collection) {
+        eprintln!("tofu: {}", message);
+    }
+
+    let mut headless = Headless::new(&mut buffr_collection);
+    let result = headless.run(&keys);
+    for message in headless.messages() {
+        eprintln!("tofu: {}", message);
+    }
+    let has_quit = headless.has_quit();
+    if let Err(e) = result {
+        eprintln!("tofu: {}", e);
+        return 1;
+    }
+    if !has_quit && buffr_collection.iter().any(|buf| buf.dirty) {
+        eprintln!("tofu: script ended without writing, changes discarded");
+    }
+    0
+}
+
 fn main() {
     debug_log("Starting teehee");
 
+    let mut filename = None;
+    let mut script = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--keys" => script = Some(args.next().unwrap_or_else(|| usage())),
+            "--script" => {
+                let path = args.next().unwrap_or_else(|| usage());
+                script = Some(std::fs::read_to_string(&path).unwrap_or_else(|e| {
+                    eprintln!("tofu: {}: {}", path, e);
+                    std::process::exit(1);
+                }));
+            }
+            _ if arg.starts_with("--") => usage(),
+            _ => filename = Some(arg),
+        }
+    }
+
+    if let Some(keys) = script {
+        std::process::exit(run_headless(filename.as_deref(), &keys));
+    }
 
     let stdout = stdout();
     let mut stdout = BufWriter::with_capacity(STDOUT_BUF, stdout.lock());
-    let filename = std::env::args().nth(1);
     
     // Load only a window_chunk
-    let buffr_collection = filename
+    let mut buffr_collection = filename
         .as_ref()
         .map(|filename| {
             debug_log(&format!("Attempting to load file: {:?}", filename));
@@ -65,10 +134,10 @@ fn main() {
             let mut current_buffer = Vec::new();
             
             // Configurable chunk size (e.g., 368 bytes)
-            // default 23 rows x 16 bytes is 368)
+            // default 23 rows x 16 bytes is 368). The view loads more on its
+            // first draw if the config asks for wider rows.
             let (_, height) = terminal::size().unwrap_or((80, 23));
-            let chunk_size = (height as usize - 1) * 16;  // Subtract status line
-            
+            let chunk_size = (height as usize - 1) * DEFAULT_BYTES_PER_LINE; // Subtract status line
 
             debug_log(&format!("Loading file with chunk size: {}", chunk_size));
             // let chunk_size = 368;
@@ -82,7 +151,7 @@ fn main() {
                 Some(filename),
             ))
         })
-        .unwrap_or_else(BuffrCollection::new);
+        .unwrap_or_default();
 
     /*
     Original, loads whole file
@@ -98,7 +167,12 @@ fn main() {
     //     .unwrap_or_else(BuffrCollection::new);
         
         
-    let view = HexView::with_buffr_collection(buffr_collection);
+    let config_messages = config::load(&mut buffr_collection);
+
+    let mut view = HexView::with_buffr_collection(buffr_collection);
+    if let Some(message) = config_messages.into_iter().next() {
+        view.set_info(message);
+    }
 
     view.run_event_loop(&mut stdout).unwrap();
 }
@@ -105,7 +105,7 @@ impl Rope {
         self.0.len() == 0
     }
 
-    pub fn iter_chunks<T: IntervalBounds>(&self, range: T) -> ChunkIter {
+    pub fn iter_chunks<T: IntervalBounds>(&self, range: T) -> ChunkIter<'_> {
         let Interval { start, end } = range.into_interval(self.0.len());
         ChunkIter {
             cursor: Cursor::new(&self.0, start),
@@ -113,7 +113,7 @@ impl Rope {
         }
     }
 
-    pub fn slice_to_cow<T: IntervalBounds>(&self, range: T) -> Cow<[u8]> {
+    pub fn slice_to_cow<T: IntervalBounds>(&self, range: T) -> Cow<'_, [u8]> {
         let mut iter = self.iter_chunks(range);
         let first = iter.next();
         let second = iter.next();
@@ -172,7 +172,7 @@ impl From<Rope> for Vec<u8> {
     }
 }
 
-impl<'a> From<&'a Rope> for Vec<u8> {
+impl From<&Rope> for Vec<u8> {
     fn from(rope: &Rope) -> Self {
         rope.iter_chunks(..).fold(vec![], |mut acc, x| {
             acc.extend_from_slice(x);
@@ -0,0 +1,197 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::options::{ClipboardEncoding, Options};
+use crate::registers::RegisterContents;
+
+/// The register that mirrors the system clipboard.
+pub const CLIPBOARD_REGISTER: char = '+';
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn base64_decode(text: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for c in text
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace() && *c != b'=')
+    {
+        let value = BASE64_ALPHABET
+            .iter()
+            .position(|&x| x == c)
+            .ok_or_else(|| format!("invalid base64 character {:?}", c as char))?;
+        acc = acc << 6 | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+fn hex_decode(text: &str) -> Result<Vec<u8>, String> {
+    let digits = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| {
+            c.to_digit(16)
+                .ok_or_else(|| format!("invalid hex digit {:?}", c))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if digits.len() % 2 != 0 {
+        return Err("odd number of hex digits".to_string());
+    }
+    Ok(digits
+        .chunks(2)
+        .map(|pair| (pair[0] << 4 | pair[1]) as u8)
+        .collect())
+}
+
+/// Turns register contents into clipboard text. Multiple selections go on separate lines,
+/// except in raw mode where they are simply concatenated.
+pub fn encode(contents: &RegisterContents, encoding: ClipboardEncoding) -> Vec<u8> {
+    match encoding {
+        ClipboardEncoding::Raw => contents.concat(),
+        ClipboardEncoding::Hex => contents
+            .iter()
+            .map(|sel| {
+                sel.iter()
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+            .into_bytes(),
+        ClipboardEncoding::Base64 => contents
+            .iter()
+            .map(|sel| base64_encode(sel))
+            .collect::<Vec<_>>()
+            .join("\n")
+            .into_bytes(),
+    }
+}
+
+pub fn decode(text: &[u8], encoding: ClipboardEncoding) -> Result<RegisterContents, String> {
+    let lines = || {
+        String::from_utf8_lossy(text)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::to_owned)
+            .collect::<Vec<_>>()
+    };
+    match encoding {
+        ClipboardEncoding::Raw => Ok(vec![text.to_vec()]),
+        ClipboardEncoding::Hex => lines().iter().map(|line| hex_decode(line)).collect(),
+        ClipboardEncoding::Base64 => lines().iter().map(|line| base64_decode(line)).collect(),
+    }
+}
+
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    }
+}
+
+/// Sends the register contents to the configured copy helper. Without one, returns the
+/// OSC 52 sequence that has the terminal set its clipboard instead, unless it's turned off.
+pub fn copy(options: &Options, contents: &RegisterContents) -> Result<Option<String>, String> {
+    let text = encode(contents, options.clipboard_encoding);
+
+    if let Some(command) = &options.clipboard_copy {
+        let mut child = shell(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("clipboard helper failed: {}", e))?;
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(&text)
+            .map_err(|e| format!("clipboard helper failed: {}", e))?;
+        let status = child
+            .wait()
+            .map_err(|e| format!("clipboard helper failed: {}", e))?;
+        return if status.success() {
+            Ok(None)
+        } else {
+            Err(format!("clipboard helper exited with {}", status))
+        };
+    }
+
+    // OSC 52 travels through the terminal, so it also works over SSH
+    Ok(Some(format!("\x1b]52;c;{}\x07", base64_encode(&text))).filter(|_| options.osc52))
+}
+
+/// Reads the clipboard through the configured paste helper, if there is one.
+pub fn paste(options: &Options) -> Option<Result<RegisterContents, String>> {
+    let command = options.clipboard_paste.as_ref()?;
+    Some(
+        shell(command)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .map_err(|e| format!("clipboard helper failed: {}", e))
+            .and_then(|output| {
+                if output.status.success() {
+                    decode(&output.stdout, options.clipboard_encoding)
+                } else {
+                    Err(format!("clipboard helper exited with {}", output.status))
+                }
+            }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_roundtrip() {
+        assert_eq!(base64_encode(b"tofu"), "dG9mdQ==");
+        assert_eq!(base64_encode(b"teehee"), "dGVlaGVl");
+        assert_eq!(base64_decode("dG9mdQ==").unwrap(), b"tofu");
+        assert_eq!(base64_decode("dGVlaGVl").unwrap(), b"teehee");
+    }
+
+    #[test]
+    fn test_encode_decode_selections() {
+        let contents = vec![vec![0xde, 0xad], vec![0xbe, 0xef]];
+        for &encoding in &[ClipboardEncoding::Hex, ClipboardEncoding::Base64] {
+            let text = encode(&contents, encoding);
+            assert_eq!(decode(&text, encoding).unwrap(), contents);
+        }
+        assert_eq!(encode(&contents, ClipboardEncoding::Hex), b"de ad\nbe ef");
+    }
+}
@@ -0,0 +1,54 @@
+use std::env;
+use std::path::PathBuf;
+
+use crate::modes::command;
+use crate::modes::mode::ModeTransition;
+use crate::BuffrCollection;
+
+/// `tofu` under `$XDG_CONFIG_HOME` (falling back to `~/.config`).
+pub fn config_dir() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_dir.join("tofu"))
+}
+
+/// `$TOFU_CONFIG`, or `tofurc` in the config directory.
+pub fn config_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("TOFU_CONFIG") {
+        return Some(path.into());
+    }
+    Some(config_dir()?.join("tofurc"))
+}
+
+/// Runs every line of the config file as a command, e.g. `set osc52=off` or
+/// `macro a 10l<a-s>b`. Blank lines and lines starting with `#` are skipped.
+///
+/// Returns the messages produced by the commands, prefixed with their line number.
+pub fn load(buffr_collection: &mut BuffrCollection) -> Vec<String> {
+    let path = match config_path() {
+        Some(path) if path.exists() => path,
+        _ => return vec![],
+    };
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) => return vec![format!("{}: {}", path.display(), e)],
+    };
+
+    let mut messages = vec![];
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim_start();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix(':').unwrap_or(line);
+        match command::execute(buffr_collection, line) {
+            ModeTransition::ModeAndInfo(_, info)
+            | ModeTransition::ModeAndDirtyBytesAndInfo(_, _, info) => {
+                messages.push(format!("{}:{}: {}", path.display(), i + 1, info))
+            }
+            _ => {}
+        }
+    }
+    messages
+}
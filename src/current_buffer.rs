@@ -1,11 +1,22 @@
 use xi_rope::Interval;
 
+use std::cmp;
 use std::collections::HashMap;
+use std::io::{self, Error, ErrorKind};
 use std::path::{Path, PathBuf};
 
 use super::byte_rope::*;
 use super::history::History;
+use crate::clipboard::{self, CLIPBOARD_REGISTER};
+use crate::jumps::{Anchors, Jump, JumpList};
+use crate::macros::Macros;
+use crate::marks::Marks;
+use crate::modes::find::{SEARCH_HISTORY_SIZE, SEARCH_REGISTER};
 use crate::modes::mode::DirtyBytes;
+use crate::modes::search::Pattern;
+use crate::options::Options;
+use crate::panes::{Panes, SplitDirection};
+use crate::registers::Registers;
 use crate::selection::{SelRegion, Selection};
 
 use std::fs::File;
@@ -26,6 +37,9 @@ use std::time::{
 
 const DEBUG_LOG: bool = false;
 
+/// Bytes read around the target of a jump outside the loaded part of the file.
+const JUMP_WINDOW: usize = 0x2000;
+
 fn debug_log(message: &str) {
     /*
     use std::fs::OpenOptions;
@@ -44,7 +58,6 @@ fn debug_log(message: &str) {
             if log_path.exists() {
                 // If the path exists, attempt to open the file for writing
                 if let Ok(mut file) = OpenOptions::new()
-                    .write(true)
                     .append(true)
                     .open(&log_path) {
 
@@ -72,23 +85,48 @@ pub struct CurrentBuffer {
     pub path: Option<PathBuf>,
     pub data: Rope,
     pub selection: Selection,
-    pub registers: HashMap<char, Vec<Vec<u8>>>,
     pub dirty: bool,
     pub history: History,
+    /// Where in the file the loaded window starts.
     pub data_start_offset: usize,
+    /// Where in the file the loaded window ended before any edits, which is where
+    /// the next chunk is read from.
+    disk_end: usize,
+    pub marks: Marks,
+    pub jump_anchors: Anchors,
+    /// Selections of the inactive panes showing this buffer, by pane id.
+    pub pane_selections: HashMap<usize, Selection>,
 }
 
 // in current_buffers.rs
 impl CurrentBuffer {
     pub fn from_data_and_path(data: Vec<u8>, path: Option<impl Into<PathBuf>>) -> CurrentBuffer {
+        let path = path.map(Into::into);
+        // An unreadable sidecar shouldn't keep the file from opening
+        let marks = path
+            .as_deref()
+            .and_then(|path| Marks::load(path).ok())
+            .unwrap_or_default();
         CurrentBuffer {
+            disk_end: data.len(),
             data: data.into(),
             selection: Selection::new(),
-            registers: HashMap::new(),
             dirty: false,
-            path: path.map(Into::into),
+            path,
             history: History::new(),
             data_start_offset: 0,  // Initialize at 0
+            marks,
+            jump_anchors: Anchors::default(),
+            pane_selections: HashMap::new(),
+        }
+    }
+
+    /// Saves the marks next to the file right away, unless they point into unsaved
+    /// edits; then they're saved with the next write, so they match what's on disk.
+    pub fn save_marks_if_clean(&self) -> std::io::Result<()> {
+        match &self.path {
+            Some(path) if !self.dirty => self.marks.save(path),
+            _ => Ok(()),
         }
     }
     
@@ -108,9 +146,9 @@ impl CurrentBuffer {
             debug_log(&format!("File size: {}, Current buffer size: {}", 
                 file_size, self.data.len()));
             
-            // Seek to the end of current data
-            file.seek(SeekFrom::Start(self.data.len() as u64))?;
-            debug_log(&format!("Seeking to position: {}", self.data.len()));
+            // Seek to the end of the loaded window
+            file.seek(SeekFrom::Start(self.disk_end as u64))?;
+            debug_log(&format!("Seeking to position: {}", self.disk_end));
             
             // Read next chunk
             let mut next_chunk = vec![0; chunk_size];
@@ -134,6 +172,7 @@ impl CurrentBuffer {
                 // Apply the delta to append the new chunk
                 let old_size = self.data.len();
                 self.data = self.data.apply_delta(&delta);
+                self.disk_end += bytes_read;
                 debug_log(&format!("Buffer size changed: {} -> {}", 
                     old_size, self.data.len()));
                 
@@ -148,6 +187,146 @@ impl CurrentBuffer {
         }
     }
 
+    /// The length of the buffer, counting the parts of the file outside the
+    /// loaded window.
+    pub fn total_len(&self) -> usize {
+        let on_disk = self
+            .path
+            .as_ref()
+            .and_then(|path| std::fs::metadata(path).ok())
+            .map_or(0, |metadata| metadata.len() as usize);
+        on_disk.saturating_sub(self.disk_end) + self.data_start_offset + self.data.len()
+    }
+
+    /// Where the main caret is in the file.
+    pub fn caret_file_offset(&self) -> usize {
+        self.data_start_offset + self.selection.main_cursor_offset()
+    }
+
+    /// Makes sure `offset` in the file is in the loaded window, moving the window
+    /// there if it isn't, and returns where in the window it is. A moved window
+    /// starts on a row of `bytes_per_line` bytes.
+    pub fn load_window_around(
+        &mut self,
+        offset: usize,
+        bytes_per_line: usize,
+    ) -> io::Result<usize> {
+        // The file may have shrunk since the offset was saved
+        let total_len = self.total_len();
+        let offset = cmp::min(offset, total_len.saturating_sub(1));
+        let start = self.data_start_offset;
+        let outside = offset < start || offset >= start + self.data.len();
+        if outside && offset < total_len {
+            self.move_window(offset, bytes_per_line)?;
+        }
+        let offset = offset.saturating_sub(self.data_start_offset);
+        Ok(cmp::min(offset, self.data.len().saturating_sub(1)))
+    }
+
+    // Reads a new window around `offset`. The old one goes away with its history,
+    // so unsaved edits in it would be lost.
+    fn move_window(&mut self, offset: usize, bytes_per_line: usize) -> io::Result<()> {
+        if self.dirty {
+            return Err(Error::new(
+                ErrorKind::Other,
+                "write the changes first to leave the loaded part of the file",
+            ));
+        }
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let start = offset.saturating_sub(JUMP_WINDOW / 2);
+        // Keep rows starting at multiples of the row width in the file
+        let start = start - start % bytes_per_line;
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(start as u64))?;
+        let mut data = Vec::with_capacity(JUMP_WINDOW);
+        file.take(JUMP_WINDOW as u64).read_to_end(&mut data)?;
+
+        self.data_start_offset = start;
+        self.disk_end = start + data.len();
+        self.data = data.into();
+        self.history = History::new();
+        self.selection = Selection::new();
+        for selection in self.pane_selections.values_mut() {
+            *selection = Selection::new();
+        }
+        Ok(())
+    }
+
+    /// Drops the first `len` bytes of the loaded window, moving the selections
+    /// back with the bytes they're on. Edits in those bytes would be lost, so
+    /// nothing is dropped while there are any.
+    pub fn trim_front(&mut self, len: usize) -> bool {
+        if self.dirty || len > self.data.len() {
+            return false;
+        }
+        let max_len = self.data.len();
+        let delta = Delta::simple_edit(
+            Interval::new(0, len),
+            Rope::from(vec![]).into_node(),
+            max_len,
+        );
+        self.selection.apply_delta(&delta, max_len);
+        self.transform_pane_selections(&delta);
+        self.data = self.data.apply_delta(&delta);
+        self.data_start_offset += len;
+        // Undoing would replay edits against bytes that aren't loaded anymore
+        self.history = History::new();
+        true
+    }
+
+    /// Writes the buffer to `path`: the loaded window, with the parts of the file
+    /// around it that were never loaded copied from disk.
+    pub fn write_to(&mut self, path: &Path) -> io::Result<()> {
+        let source = match &self.path {
+            Some(source) => source.clone(),
+            None => return std::fs::write(path, self.data.slice_to_cow(..)),
+        };
+        let on_disk = std::fs::metadata(&source).map_or(0, |metadata| metadata.len() as usize);
+        if self.data_start_offset == 0 && self.disk_end >= on_disk {
+            std::fs::write(path, self.data.slice_to_cow(..))?;
+        } else {
+            self.splice_into(&source, path)?;
+        }
+        if source.as_path() == path {
+            self.disk_end = self.data_start_offset + self.data.len();
+        }
+        Ok(())
+    }
+
+    // Builds the file next to `path` and moves it in place, as `path` may be the
+    // file the unloaded parts are read from.
+    fn splice_into(&self, source: &Path, path: &Path) -> io::Result<()> {
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| Error::new(ErrorKind::Other, "not a file name"))?;
+        let temp = path.with_file_name(format!(".{}.tofu-tmp", file_name.to_string_lossy()));
+        let result = self
+            .write_spliced(source, &temp)
+            .and_then(|()| std::fs::rename(&temp, path));
+        if result.is_err() {
+            let _ = std::fs::remove_file(&temp);
+        }
+        result
+    }
+
+    fn write_spliced(&self, source: &Path, path: &Path) -> io::Result<()> {
+        let mut input = File::open(source)?;
+        let mut output = File::create(path)?;
+        io::copy(
+            &mut (&mut input).take(self.data_start_offset as u64),
+            &mut output,
+        )?;
+        for chunk in self.data.iter_chunks(..) {
+            output.write_all(chunk)?;
+        }
+        input.seek(SeekFrom::Start(self.disk_end as u64))?;
+        io::copy(&mut input, &mut output)?;
+        output.set_permissions(input.metadata()?.permissions())
+    }
+
     pub fn name(&self) -> String {
         if let Some(path) = &self.path {
             format!("{}", path.display())
@@ -166,7 +345,7 @@ impl CurrentBuffer {
             }
             new
         });
-        invalidated_ranges.sort_by(|a, b| a.start.cmp(&b.start));
+        invalidated_ranges.sort_by_key(|r| r.start);
 
         let mut disjoint_invalidated_ranges = Vec::new();
         for r in invalidated_ranges {
@@ -186,6 +365,11 @@ impl CurrentBuffer {
 
     fn apply_delta_to_current_buffer(&mut self, delta: RopeDelta, is_final: bool) {
         let next_data = self.data.apply_delta(&delta);
+        self.marks
+            .apply_delta(&delta, self.data_start_offset, self.data.len());
+        self.jump_anchors
+            .apply_delta(&delta, self.data_start_offset, self.data.len());
+        self.transform_pane_selections(&delta);
         if is_final {
             self.history
                 .perform_final(&self.data, delta, self.selection.clone());
@@ -197,6 +381,14 @@ impl CurrentBuffer {
         self.dirty = true;
     }
 
+    // Other panes' selections follow edits the same way the main one does
+    fn transform_pane_selections(&mut self, delta: &RopeDelta) {
+        let max_len = self.data.len();
+        for selection in self.pane_selections.values_mut() {
+            selection.apply_delta(delta, max_len);
+        }
+    }
+
     pub fn apply_delta(&mut self, delta: RopeDelta) -> DirtyBytes {
         let max_len = self.data.len();
         self.apply_delta_to_current_buffer(delta.clone(), true);
@@ -205,6 +397,15 @@ impl CurrentBuffer {
         DirtyBytes::ChangeLength
     }
 
+    /// Applies an edit that replaces bytes without changing the length, like an
+    /// overwrite, leaving the selection where it is.
+    pub fn apply_delta_in_place(&mut self, delta: RopeDelta) -> DirtyBytes {
+        debug_assert_eq!(delta.new_document_len(), self.data.len());
+        self.apply_delta_to_current_buffer(delta, true);
+
+        DirtyBytes::ChangeLength
+    }
+
     pub fn apply_delta_offset_carets(
         &mut self,
         delta: RopeDelta,
@@ -250,6 +451,11 @@ impl CurrentBuffer {
             self.history.undo(&self.data, self.selection.clone())
         {
             self.selection = old_selection;
+            self.marks
+                .apply_delta(&undo_delta, self.data_start_offset, self.data.len());
+            self.jump_anchors
+                .apply_delta(&undo_delta, self.data_start_offset, self.data.len());
+            self.transform_pane_selections(&undo_delta);
             self.data = self.data.apply_delta(&undo_delta);
             self.dirty = true;
             Some(DirtyBytes::ChangeLength)
@@ -263,6 +469,11 @@ impl CurrentBuffer {
             self.history.redo(&self.data, self.selection.clone())
         {
             self.selection = old_selection;
+            self.marks
+                .apply_delta(&redo_delta, self.data_start_offset, self.data.len());
+            self.jump_anchors
+                .apply_delta(&redo_delta, self.data_start_offset, self.data.len());
+            self.transform_pane_selections(&redo_delta);
             self.data = self.data.apply_delta(&redo_delta);
             self.dirty = true;
             Some(DirtyBytes::ChangeLength)
@@ -299,23 +510,21 @@ impl CurrentBuffer {
         self.switch_main_sel(|sel| sel.select_prev(count))
     }
 
-    pub fn yank_selections(&mut self, reg: char) {
+    pub fn selected_bytes(&self) -> Vec<Vec<u8>> {
         if self.data.is_empty() {
-            self.registers
-                .insert(reg, vec![vec![]; self.selection.len()]);
-            return;
+            return vec![vec![]; self.selection.len()];
         }
 
-        let selections = self
-            .selection
+        self.selection
             .iter()
             .map(|region| self.data.slice_to_cow(region.min()..=region.max()).to_vec())
-            .collect();
-        self.registers.insert(reg, selections);
+            .collect()
     }
 
-    pub fn overflow_sel_style(&self) -> Option<OverflowSelectionStyle> {
-        let last_sel = self.selection.iter().last().unwrap();
+    /// How the last region of `selection`, this buffer's or a pane's, shows
+    /// past the end.
+    pub fn overflow_sel_style(&self, selection: &Selection) -> Option<OverflowSelectionStyle> {
+        let last_sel = selection.iter().last().unwrap();
         let len = self.data.len();
         if last_sel.caret == len && last_sel.tail == len {
             Some(OverflowSelectionStyle::CursorTail)
@@ -338,6 +547,19 @@ impl CurrentBuffer {
 pub struct BuffrCollection {
     list: Vec<CurrentBuffer>,
     cur_buf_index: usize,
+    alt_buf_index: Option<usize>,
+    registers: Registers,
+    options: Options,
+    macros: Macros,
+    jumps: JumpList,
+    panes: Panes,
+    /// Patterns searched for with `/` and `?`, most recent first, and whether the
+    /// latest went forward.
+    search_history: Vec<Pattern>,
+    search_forward: bool,
+    /// An OSC 52 sequence from the last clipboard yank, for the view to send to
+    /// the terminal. Without a terminal it's never sent.
+    pending_osc52: Option<String>,
 }
 
 impl Default for BuffrCollection {
@@ -360,17 +582,141 @@ impl BuffrCollection {
     pub fn with_current_buffer(buf: CurrentBuffer) -> BuffrCollection {
         BuffrCollection {
             cur_buf_index: 0,
+            alt_buf_index: None,
+            registers: Registers::new(),
+            options: Options::default(),
+            macros: Macros::default(),
+            jumps: JumpList::default(),
+            panes: Panes::default(),
+            search_history: vec![],
+            search_forward: true,
+            pending_osc52: None,
             list: vec![buf],
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.cur_buf_index
+    }
+
+    pub fn alternate_index(&self) -> Option<usize> {
+        self.alt_buf_index
+    }
+
     pub fn current(&self) -> &CurrentBuffer {
         &self.list[self.cur_buf_index]
     }
+    pub fn get(&self, index: usize) -> Option<&CurrentBuffer> {
+        self.list.get(index)
+    }
+
     pub fn current_mut(&mut self) -> &mut CurrentBuffer {
         &mut self.list[self.cur_buf_index]
     }
 
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    pub fn macros(&self) -> &Macros {
+        &self.macros
+    }
+
+    pub fn macros_mut(&mut self) -> &mut Macros {
+        &mut self.macros
+    }
+
+    /// Identifies the current buffer and how many changes it has seen, so callers
+    /// can tell whether a key edited it.
+    pub fn change_stamp(&self) -> (usize, usize) {
+        (self.cur_buf_index, self.current().history.change_count())
+    }
+
+    pub fn registers_mut(&mut self) -> &mut Registers {
+        &mut self.registers
+    }
+
+    pub fn search_history(&self) -> &[Pattern] {
+        &self.search_history
+    }
+
+    /// The pattern and direction `n` repeats.
+    pub fn last_search(&self) -> Option<(&Pattern, bool)> {
+        self.search_history
+            .first()
+            .map(|pattern| (pattern, self.search_forward))
+    }
+
+    /// Puts `pattern` at the front of the search history, and mirrors the history
+    /// in the search register as the patterns' text forms.
+    pub fn remember_search(&mut self, pattern: Pattern, forward: bool) {
+        self.search_history.retain(|old| *old != pattern);
+        self.search_history.insert(0, pattern);
+        self.search_history.truncate(SEARCH_HISTORY_SIZE);
+        self.search_forward = forward;
+        let contents = self
+            .search_history
+            .iter()
+            .map(|pattern| pattern.to_text().into_bytes())
+            .collect();
+        self.registers
+            .set(SEARCH_REGISTER, contents)
+            .expect("the search register isn't a digit");
+    }
+
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    pub fn options_mut(&mut self) -> &mut Options {
+        &mut self.options
+    }
+
+    /// Yanks the current buffer's selections. Yanking into the clipboard register
+    /// also hands the data to the system clipboard.
+    pub fn yank_selections(&mut self, reg: char) -> Result<(), String> {
+        let contents = self.current().selected_bytes();
+        if reg == CLIPBOARD_REGISTER {
+            let copied = clipboard::copy(&self.options, &contents);
+            self.registers.yank(reg, contents);
+            self.pending_osc52 = copied?;
+            return Ok(());
+        }
+        self.registers.yank(reg, contents);
+        Ok(())
+    }
+
+    /// The OSC 52 sequence waiting to be written to the terminal, if any.
+    pub fn take_osc52(&mut self) -> Option<String> {
+        self.pending_osc52.take()
+    }
+
+    /// Refreshes the clipboard register from the paste helper before it is pasted.
+    pub fn sync_register(&mut self, reg: char) -> Result<(), String> {
+        if reg != CLIPBOARD_REGISTER {
+            return Ok(());
+        }
+        match clipboard::paste(&self.options) {
+            Some(contents) => {
+                self.registers.set(reg, contents?)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Borrows the current buffer together with the shared registers.
+    pub fn current_and_registers_mut(&mut self) -> (&mut CurrentBuffer, &mut Registers) {
+        (&mut self.list[self.cur_buf_index], &mut self.registers)
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &CurrentBuffer> {
         self.list.iter()
     }
@@ -378,12 +724,146 @@ impl BuffrCollection {
         self.list.iter_mut()
     }
 
+    // Every switch goes through here so the alternate buffer and jump list stay in sync
+    fn set_current_index(&mut self, index: usize) {
+        if index != self.cur_buf_index {
+            self.record_jump();
+            self.alt_buf_index = Some(self.cur_buf_index);
+            self.cur_buf_index = index;
+        }
+    }
+
+    /// Moves every caret to `offset` in the file, moving the loaded window there
+    /// if it's outside, and records the jump.
+    pub fn jump_to_offset(
+        &mut self,
+        offset: usize,
+        bytes_per_line: usize,
+    ) -> io::Result<DirtyBytes> {
+        // Moving the window resets the carets, so note where the main one was first,
+        // and only record the jump once the move worked
+        let from = self.current().caret_file_offset();
+        let current_buffer = self.current_mut();
+        let offset = current_buffer.load_window_around(offset, bytes_per_line)?;
+        current_buffer.map_selections(|region| vec![region.jump_to(offset)]);
+        self.record_jump_from(from);
+        Ok(DirtyBytes::ChangeLength)
+    }
+
+    /// Remembers where the main caret is before a big move, so `<c-o>` can return.
+    pub fn record_jump(&mut self) {
+        self.record_jump_from(self.current().caret_file_offset());
+    }
+
+    fn record_jump_from(&mut self, offset: usize) {
+        for jump in self.jumps.truncate() {
+            self.list[jump.buffer].jump_anchors.remove(jump.anchor);
+        }
+        self.push_location(offset);
+    }
+
+    fn push_current_location(&mut self) {
+        self.push_location(self.current().caret_file_offset());
+    }
+
+    // Adds `offset` in the current buffer to the jump list, unless it's already last
+    fn push_location(&mut self, offset: usize) {
+        let buffer = self.cur_buf_index;
+        let anchors = &mut self.list[buffer].jump_anchors;
+        if let Some(last) = self.jumps.last() {
+            if last.buffer == buffer && anchors.get(last.anchor) == Some(offset) {
+                return;
+            }
+        }
+        let anchor = anchors.insert(offset);
+        if let Some(evicted) = self.jumps.push(Jump { buffer, anchor }) {
+            self.list[evicted.buffer]
+                .jump_anchors
+                .remove(evicted.anchor);
+        }
+    }
+
+    /// Goes `count` entries back in the jump list. The first step back also
+    /// records the current location, so `<c-i>` can return to it.
+    pub fn jump_back(&mut self, count: usize, bytes_per_line: usize) -> io::Result<bool> {
+        if !self.jumps.is_walking() {
+            self.push_current_location();
+            self.jumps.back(1);
+        }
+        match self.jumps.back(count) {
+            Some(jump) => self.go_to_jump(jump, bytes_per_line).map(|()| true),
+            None => Ok(false),
+        }
+    }
+
+    pub fn jump_forward(&mut self, count: usize, bytes_per_line: usize) -> io::Result<bool> {
+        match self.jumps.forward(count) {
+            Some(jump) => self.go_to_jump(jump, bytes_per_line).map(|()| true),
+            None => Ok(false),
+        }
+    }
+
+    fn go_to_jump(&mut self, jump: Jump, bytes_per_line: usize) -> io::Result<()> {
+        if jump.buffer != self.cur_buf_index {
+            self.alt_buf_index = Some(self.cur_buf_index);
+            self.cur_buf_index = jump.buffer;
+        }
+        let current_buffer = self.current_mut();
+        if let Some(offset) = current_buffer.jump_anchors.get(jump.anchor) {
+            let offset = current_buffer.load_window_around(offset, bytes_per_line)?;
+            current_buffer.map_selections(|region| vec![region.jump_to(offset)]);
+        }
+        Ok(())
+    }
+
+    pub fn switch_to_index(&mut self, index: usize) -> bool {
+        if index < self.list.len() {
+            self.set_current_index(index);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn switch_to_next(&mut self, count: usize) {
+        self.set_current_index((self.cur_buf_index + count) % self.list.len());
+    }
+
+    pub fn switch_to_prev(&mut self, count: usize) {
+        let len = self.list.len();
+        self.set_current_index((self.cur_buf_index + len - count % len) % len);
+    }
+
+    pub fn switch_to_alternate(&mut self) -> bool {
+        match self.alt_buf_index {
+            Some(alt) => self.switch_to_index(alt),
+            None => false,
+        }
+    }
+
+    /// Finds a buffer by exact name, falling back to a unique substring match.
+    pub fn find_by_name(&self, name: &str) -> Option<usize> {
+        if let Some(i) = self.list.iter().position(|buf| buf.name() == name) {
+            return Some(i);
+        }
+        let mut candidates = self
+            .list
+            .iter()
+            .enumerate()
+            .filter(|(_, buf)| buf.name().contains(name))
+            .map(|(i, _)| i);
+        match (candidates.next(), candidates.next()) {
+            (Some(i), None) => Some(i),
+            _ => None,
+        }
+    }
+
     pub fn switch_current_buffer(&mut self, filename: impl AsRef<Path>) -> Result<(), std::io::Error> {
         let canon = filename.as_ref().canonicalize()?;
         for (i, buf) in self.list.iter().enumerate() {
             if let Some(path) = &buf.path {
                 if path.canonicalize()? == canon {
-                    self.cur_buf_index = i;
+                    self.set_current_index(i);
                     return Ok(());
                 }
             }
@@ -393,15 +873,286 @@ impl BuffrCollection {
             std::fs::read(&filename)?,
             Some(filename.as_ref().to_owned()),
         ));
-        self.cur_buf_index = self.list.len() - 1;
+        self.set_current_index(self.list.len() - 1);
         Ok(())
     }
 
+    pub fn panes(&self) -> &Panes {
+        &self.panes
+    }
+
+    /// Splits the active pane in two. The new pane shows the same bytes with a
+    /// copy of the selection, and becomes the active one.
+    pub fn split_pane(&mut self, direction: SplitDirection) {
+        self.panes.direction = direction;
+        let id = self.panes.insert(self.cur_buf_index);
+        let selection = self.current().selection.clone();
+        self.current_mut().pane_selections.insert(id, selection);
+        self.focus_pane(self.panes.active_index() + 1);
+    }
+
+    /// Makes another pane the active one, putting its buffer and selection in
+    /// place of the current ones.
+    pub fn focus_pane(&mut self, index: usize) -> bool {
+        let target = match self.panes.get(index) {
+            Some(target) => target,
+            None => return false,
+        };
+        let active = self.panes.active_index();
+        if index == active {
+            return true;
+        }
+
+        let selection = self.current().selection.clone();
+        let id = self.panes.active().id;
+        self.current_mut().pane_selections.insert(id, selection);
+        self.panes.set_buffer(active, self.cur_buf_index);
+
+        self.cur_buf_index = target.buffer;
+        self.panes.set_active(index, target.buffer);
+        let current_buffer = self.current_mut();
+        // Panes moved off a closed buffer have no selection of their own yet
+        if let Some(selection) = current_buffer.pane_selections.remove(&target.id) {
+            current_buffer.selection = selection;
+        }
+        true
+    }
+
+    /// Closes the active pane, focusing the next one. The last pane can't be closed.
+    pub fn close_pane(&mut self) -> bool {
+        let closing = self.panes.active_index();
+        let next = if closing + 1 < self.panes.len() {
+            closing + 1
+        } else if closing > 0 {
+            closing - 1
+        } else {
+            return false;
+        };
+        self.focus_pane(next);
+        self.forget_pane(closing);
+        true
+    }
+
+    /// Closes every pane but the active one.
+    pub fn only_pane(&mut self) {
+        for index in (0..self.panes.len()).rev() {
+            if index != self.panes.active_index() {
+                self.forget_pane(index);
+            }
+        }
+    }
+
+    fn forget_pane(&mut self, index: usize) {
+        let pane = self.panes.remove(index);
+        self.list[pane.buffer].pane_selections.remove(&pane.id);
+    }
+
     pub fn delete_current(&mut self) {
-        self.list.remove(self.cur_buf_index);
-        self.cur_buf_index = self.cur_buf_index.saturating_sub(1);
+        let removed = self.cur_buf_index;
+        self.list.remove(removed);
+        self.jumps.remove_buffer(removed);
+        self.cur_buf_index = match self.alt_buf_index {
+            Some(alt) if alt > removed => alt - 1,
+            Some(alt) if alt < removed => alt,
+            _ => removed.saturating_sub(1),
+        };
+        self.alt_buf_index = None;
+        self.panes.remove_buffer(removed, self.cur_buf_index);
         if self.list.is_empty() {
             self.list.push(CurrentBuffer::default());
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collection_of(names: &[&str]) -> BuffrCollection {
+        let mut collection = BuffrCollection::with_current_buffer(
+            CurrentBuffer::from_data_and_path(vec![], Some(names[0])),
+        );
+        for name in &names[1..] {
+            collection
+                .list
+                .push(CurrentBuffer::from_data_and_path(vec![], Some(*name)));
+        }
+        collection
+    }
+
+    #[test]
+    fn test_cycle_and_alternate() {
+        let mut collection = collection_of(&["a.bin", "b.bin", "c.bin"]);
+        collection.switch_to_prev(1);
+        assert_eq!(collection.current_index(), 2);
+        collection.switch_to_next(2);
+        assert_eq!(collection.current_index(), 1);
+        assert_eq!(collection.alternate_index(), Some(2));
+        assert!(collection.switch_to_alternate());
+        assert_eq!(collection.current_index(), 2);
+        assert_eq!(collection.alternate_index(), Some(1));
+    }
+
+    #[test]
+    fn test_delete_returns_to_alternate() {
+        let mut collection = collection_of(&["a.bin", "b.bin", "c.bin"]);
+        collection.switch_to_index(2);
+        collection.delete_current();
+        assert_eq!(collection.current().name(), "a.bin");
+        assert_eq!(collection.alternate_index(), None);
+    }
+
+    #[test]
+    fn test_loaded_window() {
+        let path = std::env::temp_dir().join(format!("tofu-window-{}", std::process::id()));
+        let file: Vec<u8> = (0..0x6000).map(|i| (i / 0x100) as u8).collect();
+        std::fs::write(&path, &file).unwrap();
+        let mut collection = BuffrCollection::with_current_buffer(
+            CurrentBuffer::from_data_and_path(file[..0x100].to_vec(), Some(&path)),
+        );
+
+        collection.jump_to_offset(0x4010, 0x100).unwrap();
+        let buf = collection.current_mut();
+        assert_eq!(buf.data_start_offset, 0x3000);
+        assert_eq!(buf.data.len(), JUMP_WINDOW);
+        assert_eq!(buf.selection.main_cursor_offset(), 0x1010);
+        assert_eq!(buf.total_len(), file.len());
+
+        // Jumps are remembered by file offset, and move the window back
+        assert!(collection.jump_back(1, 0x100).unwrap());
+        assert_eq!(collection.current().data_start_offset, 0);
+        assert!(collection.jump_forward(1, 0x100).unwrap());
+        let buf = collection.current_mut();
+        assert_eq!(buf.data_start_offset, 0x3000);
+        assert_eq!(buf.selection.main_cursor_offset(), 0x1010);
+
+        let mut pane_selection = Selection::new();
+        pane_selection.move_main(SelRegion::new(0x1800, 0x1800));
+        buf.pane_selections.insert(1, pane_selection);
+        assert!(buf.trim_front(0x1000));
+        assert_eq!(buf.data_start_offset, 0x4000);
+        assert_eq!(buf.selection.main_cursor_offset(), 0x10);
+        assert_eq!(buf.pane_selections[&1].main_cursor_offset(), 0x800);
+        assert!(buf.load_next_chunk(0x100).unwrap());
+        assert_eq!(buf.data.slice_to_cow(..), &file[0x4000..0x5100]);
+
+        // Edits are written in place, with the unloaded parts around them kept
+        let delta = Delta::simple_edit(
+            Interval::new(0, 0x100),
+            Rope::from(vec![]).into_node(),
+            buf.data.len(),
+        );
+        buf.apply_delta(delta);
+        assert!(!buf.trim_front(0x100));
+        assert!(buf.load_window_around(0, 0x100).is_err());
+        // A jump that can't move the window isn't recorded
+        buf.map_selections(|region| vec![region.jump_to(0x20)]);
+        let last_jump = collection.jumps.last();
+        assert!(collection.jump_to_offset(0, 0x100).is_err());
+        assert_eq!(collection.jumps.last(), last_jump);
+        let buf = collection.current_mut();
+        buf.write_to(&path).unwrap();
+        let mut expected = file.clone();
+        expected.drain(0x4000..0x4100);
+        assert_eq!(std::fs::read(&path).unwrap(), expected);
+        assert_eq!(buf.total_len(), expected.len());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_moved_window_starts_on_a_row() {
+        let path = std::env::temp_dir().join(format!("tofu-rows-{}", std::process::id()));
+        std::fs::write(&path, vec![0; 0x6000]).unwrap();
+        let mut collection = BuffrCollection::with_current_buffer(
+            CurrentBuffer::from_data_and_path(vec![0; 0x100], Some(&path)),
+        );
+        collection.jump_to_offset(0x2000, 24).unwrap();
+        let buf = collection.current();
+        assert_eq!(buf.data_start_offset, 0xff0);
+        assert_eq!(buf.caret_file_offset(), 0x2000);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_clipboard_yank_waits_for_the_terminal() {
+        let mut collection = BuffrCollection::with_current_buffer(
+            CurrentBuffer::from_data_and_path(b"ab".to_vec(), None::<&str>),
+        );
+        collection.current_mut().selection.select_all(2);
+        collection.yank_selections(CLIPBOARD_REGISTER).unwrap();
+        assert_eq!(
+            collection.take_osc52().as_deref(),
+            Some("\x1b]52;c;NjEgNjI=\x07")
+        );
+        assert_eq!(collection.take_osc52(), None);
+
+        collection.options_mut().set("osc52", "off").unwrap();
+        collection.yank_selections(CLIPBOARD_REGISTER).unwrap();
+        assert_eq!(collection.take_osc52(), None);
+        assert_eq!(
+            collection.registers().get(CLIPBOARD_REGISTER),
+            Some(&vec![b"ab".to_vec()])
+        );
+    }
+
+    #[test]
+    fn test_jump_list_follows_edits() {
+        let mut collection = collection_of(&["a.bin", "b.bin"]);
+        collection.current_mut().data = vec![0; 0x10].into();
+        collection.record_jump();
+        collection
+            .current_mut()
+            .map_selections(|region| vec![region.jump_to(0xa)]);
+        collection.switch_to_index(1);
+
+        let mut builder = xi_rope::DeltaBuilder::new(0x10);
+        builder.replace(0..0, Into::<Rope>::into(vec![1, 2]).into_node());
+        collection.list[0].apply_delta(builder.build());
+
+        assert!(collection.jump_back(1, 0x10).unwrap());
+        assert_eq!(collection.current_index(), 0);
+        assert_eq!(collection.current().selection.main_cursor_offset(), 0xc);
+        assert!(collection.jump_back(1, 0x10).unwrap());
+        assert_eq!(collection.current().selection.main_cursor_offset(), 2);
+        assert!(!collection.jump_back(1, 0x10).unwrap());
+        assert!(collection.jump_forward(2, 0x10).unwrap());
+        assert_eq!(collection.current_index(), 1);
+        assert!(!collection.jump_forward(1, 0x10).unwrap());
+    }
+
+    #[test]
+    fn test_pane_selections() {
+        let mut collection = collection_of(&["a.bin", "b.bin"]);
+        collection.current_mut().data = vec![0; 0x10].into();
+        collection.split_pane(SplitDirection::Vertical);
+        collection
+            .current_mut()
+            .map_selections(|region| vec![region.jump_to(8)]);
+
+        let mut builder = xi_rope::DeltaBuilder::new(0x10);
+        builder.delete(0..4);
+        collection.current_mut().apply_delta(builder.build());
+        assert_eq!(collection.current().selection.main_cursor_offset(), 4);
+
+        assert!(collection.focus_pane(0));
+        assert_eq!(collection.current().selection.main_cursor_offset(), 0);
+        collection.switch_to_index(1);
+        assert!(collection.focus_pane(1));
+        assert_eq!(collection.current_index(), 0);
+        assert_eq!(collection.current().selection.main_cursor_offset(), 4);
+
+        assert!(collection.close_pane());
+        assert_eq!(collection.panes().len(), 1);
+        assert_eq!(collection.current_index(), 1);
+        assert!(!collection.close_pane());
+    }
+
+    #[test]
+    fn test_find_by_name() {
+        let collection = collection_of(&["dump/a.bin", "dump/b.bin"]);
+        assert_eq!(collection.find_by_name("b.bin"), Some(1));
+        assert_eq!(collection.find_by_name("dump/a.bin"), Some(0));
+        assert_eq!(collection.find_by_name("dump"), None);
+    }
+}
@@ -0,0 +1,264 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::options::Endian;
+
+/// Extra inspector rows that read the bytes at the main caret as a structured
+/// value, such as a timestamp or an address. They are listed in `DECODERS` and
+/// picked with the `decoders` option; none are shown by default.
+#[derive(Debug)]
+pub struct Decoder {
+    pub name: &'static str,
+    /// How many bytes the value takes.
+    pub size: usize,
+    /// Whether the inspector endian changes how the bytes are read.
+    pub uses_endian: bool,
+    /// Reads the value from exactly `size` bytes.
+    pub decode: fn(&[u8], Endian) -> Result<String, String>,
+}
+
+pub const DECODERS: &[Decoder] = &[
+    Decoder {
+        name: "unix32",
+        size: 4,
+        uses_endian: true,
+        decode: unix32,
+    },
+    Decoder {
+        name: "unix64",
+        size: 8,
+        uses_endian: true,
+        decode: unix64,
+    },
+    Decoder {
+        name: "filetime",
+        size: 8,
+        uses_endian: false,
+        decode: filetime,
+    },
+    Decoder {
+        name: "dostime",
+        size: 4,
+        uses_endian: false,
+        decode: dostime,
+    },
+    Decoder {
+        name: "guid",
+        size: 16,
+        uses_endian: true,
+        decode: guid,
+    },
+    Decoder {
+        name: "ipv4",
+        size: 4,
+        uses_endian: false,
+        decode: ipv4,
+    },
+    Decoder {
+        name: "ipv6",
+        size: 16,
+        uses_endian: false,
+        decode: ipv6,
+    },
+];
+
+// Seconds between the FILETIME epoch, 1601-01-01, and the Unix one
+const FILETIME_UNIX_OFFSET: i64 = 11_644_473_600;
+const FILETIME_TICKS_PER_SECOND: u64 = 10_000_000;
+
+impl Decoder {
+    pub fn label(&self, endian: Endian) -> String {
+        match (self.uses_endian, endian) {
+            (false, _) => self.name.to_owned(),
+            (true, Endian::Big) => format!("{} be", self.name),
+            (true, Endian::Little) => format!("{} le", self.name),
+        }
+    }
+}
+
+fn read_uint(data: &[u8], endian: Endian) -> u64 {
+    let bytes = data.iter().copied();
+    match endian {
+        Endian::Big => bytes.fold(0, |value, byte| value << 8 | byte as u64),
+        Endian::Little => bytes.rev().fold(0, |value, byte| value << 8 | byte as u64),
+    }
+}
+
+// Days since 1970-01-01 to a proleptic Gregorian (year, month, day)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// Formats seconds since the Unix epoch as a UTC date, for years 0 to 9999.
+fn format_unix_time(seconds: i64) -> Result<String, String> {
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    if !(0..=9999).contains(&year) {
+        return Err("out of range".to_owned());
+    }
+    let time = seconds.rem_euclid(86_400);
+    Ok(format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    ))
+}
+
+fn unix32(data: &[u8], endian: Endian) -> Result<String, String> {
+    format_unix_time(read_uint(data, endian) as u32 as i32 as i64)
+}
+
+fn unix64(data: &[u8], endian: Endian) -> Result<String, String> {
+    format_unix_time(read_uint(data, endian) as i64)
+}
+
+// Ticks of 100ns since 1601, always little endian
+fn filetime(data: &[u8], _: Endian) -> Result<String, String> {
+    let ticks = read_uint(data, Endian::Little);
+    let seconds = (ticks / FILETIME_TICKS_PER_SECOND) as i64 - FILETIME_UNIX_OFFSET;
+    let date = format_unix_time(seconds)?;
+    Ok(format!("{}.{:07}", date, ticks % FILETIME_TICKS_PER_SECOND))
+}
+
+// A little endian time word followed by a date word, as in FAT and zip headers
+fn dostime(data: &[u8], _: Endian) -> Result<String, String> {
+    let time = read_uint(&data[..2], Endian::Little);
+    let date = read_uint(&data[2..], Endian::Little);
+    let (year, month, day) = (1980 + (date >> 9), (date >> 5) & 0xf, date & 0x1f);
+    let (hours, minutes, seconds) = (time >> 11, (time >> 5) & 0x3f, (time & 0x1f) * 2);
+    if !(1..=12).contains(&month) || day == 0 || hours > 23 || minutes > 59 || seconds > 59 {
+        return Err("invalid".to_owned());
+    }
+    Ok(format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year, month, day, hours, minutes, seconds
+    ))
+}
+
+// Little endian reads the first three fields the Microsoft way; big endian reads
+// the RFC 4122 byte order
+fn guid(data: &[u8], endian: Endian) -> Result<String, String> {
+    let tail: String = data[8..]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    Ok(format!(
+        "{:08x}-{:04x}-{:04x}-{}-{}",
+        read_uint(&data[..4], endian),
+        read_uint(&data[4..6], endian),
+        read_uint(&data[6..8], endian),
+        &tail[..4],
+        &tail[4..]
+    ))
+}
+
+fn ipv4(data: &[u8], _: Endian) -> Result<String, String> {
+    Ok(Ipv4Addr::new(data[0], data[1], data[2], data[3]).to_string())
+}
+
+fn ipv6(data: &[u8], _: Endian) -> Result<String, String> {
+    let mut octets = [0; 16];
+    octets.copy_from_slice(data);
+    Ok(Ipv6Addr::from(octets).to_string())
+}
+
+/// Parses a comma separated list of decoder names, such as `unix32,guid`.
+pub fn parse_decoders(value: &str) -> Result<Vec<&'static Decoder>, String> {
+    if value.is_empty() {
+        return Ok(vec![]);
+    }
+    value
+        .split(',')
+        .map(|name| {
+            let name = name.trim();
+            DECODERS
+                .iter()
+                .find(|decoder| decoder.name == name)
+                .ok_or_else(|| format!("unknown decoder {}", name))
+        })
+        .collect()
+}
+
+pub fn format_decoders(decoders: &[&Decoder]) -> String {
+    decoders
+        .iter()
+        .map(|decoder| decoder.name)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(name: &str, data: &[u8], endian: Endian) -> Result<String, String> {
+        let decoder = parse_decoders(name).unwrap()[0];
+        (decoder.decode)(data, endian)
+    }
+
+    #[test]
+    fn test_decoders() {
+        assert_eq!(
+            decode("unix32", &[0, 0, 0, 0], Endian::Big).unwrap(),
+            "1970-01-01 00:00:00"
+        );
+        assert_eq!(
+            decode("unix32", &[0x65, 0x92, 0x00, 0x80], Endian::Big).unwrap(),
+            "2024-01-01 00:00:00"
+        );
+        assert_eq!(
+            decode("unix32", &[0xff, 0xff, 0xff, 0xff], Endian::Big).unwrap(),
+            "1969-12-31 23:59:59"
+        );
+        assert!(decode("unix64", &[0x7f, 0, 0, 0, 0, 0, 0, 0], Endian::Big).is_err());
+        assert_eq!(
+            decode(
+                "filetime",
+                &[0x00, 0x80, 0x3e, 0xd5, 0xde, 0xb1, 0x9d, 0x01],
+                Endian::Big
+            )
+            .unwrap(),
+            "1970-01-01 00:00:00.0000000"
+        );
+        assert_eq!(
+            decode("dostime", &[0x20, 0x6c, 0x21, 0x58], Endian::Big).unwrap(),
+            "2024-01-01 13:33:00"
+        );
+        assert_eq!(
+            decode("dostime", &[0, 0, 0, 0], Endian::Big),
+            Err("invalid".to_owned())
+        );
+        let bytes = [
+            0x33, 0x22, 0x11, 0x00, 0x55, 0x44, 0x77, 0x66, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+            0xee, 0xff,
+        ];
+        assert_eq!(
+            decode("guid", &bytes, Endian::Little).unwrap(),
+            "00112233-4455-6677-8899-aabbccddeeff"
+        );
+        assert_eq!(
+            decode("ipv4", &[192, 168, 0, 1], Endian::Big).unwrap(),
+            "192.168.0.1"
+        );
+        let mut loopback = [0; 16];
+        loopback[15] = 1;
+        assert_eq!(decode("ipv6", &loopback, Endian::Big).unwrap(), "::1");
+        assert!(parse_decoders("guid,nope").is_err());
+    }
+}
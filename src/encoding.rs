@@ -0,0 +1,320 @@
+use std::fmt;
+use std::str::FromStr;
+
+use lazy_static::lazy_static;
+
+// Glyph tables start at the byte named in their comment. Undefined and control
+// bytes are `\0`.
+
+// CP437 from 0x00, as it has glyphs for control codes too
+const CP437_LOW: &str = "\0☺☻♥♦♣♠•◘○◙♂♀♪♫☼►◄↕‼¶§▬↨↑↓→←∟↔▲▼";
+// CP437 from 0x80
+const CP437_HIGH: &str = "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜ¢£¥₧ƒáíóúñÑªº¿⌐¬½¼¡«»░▒▓│┤╡╢╖╕╣║╗╝╜╛┐└┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀αßΓπΣσµτΦΘΩδ∞φε∩≡±≥≤⌠⌡÷≈°∙·√ⁿ²■\u{a0}";
+// EBCDIC code page 037, from 0x40; everything below is a control code
+const CP037_HIGH: &str = " \u{a0}âäàáãåçñ¢.<(+|&éêëèíîïìß!$*);¬-/ÂÄÀÁÃÅÇÑ¦,%_>?øÉÊËÈÍÎÏÌ`:#@'=\"Øabcdefghi«»ðýþ±°jklmnopqrªºæ¸Æ¤µ~stuvwxyz¡¿ÐÝÞ®^£¥·©§¶¼½¾[]¯¨´×{ABCDEFGHI\0ôöòóõ}JKLMNOPQR¹ûüùúÿ\\÷STUVWXYZ²ÔÖÒÓÕ0123456789³ÛÜÙÚ\0";
+// Windows-1252 from 0x80, the rest is Latin-1
+const WINDOWS_1252_HIGH: &str = "€\0‚ƒ„…†‡ˆ‰Š‹Œ\0Ž\0\0‘’“”•–—˜™š›œ\0žŸ";
+
+lazy_static! {
+    static ref CP437_LOW_TABLE: Vec<char> = CP437_LOW.chars().collect();
+    static ref CP437_HIGH_TABLE: Vec<char> = CP437_HIGH.chars().collect();
+    static ref CP037_HIGH_TABLE: Vec<char> = CP037_HIGH.chars().collect();
+    static ref WINDOWS_1252_HIGH_TABLE: Vec<char> = WINDOWS_1252_HIGH.chars().collect();
+}
+
+/// How the character column of the view decodes bytes, and how typed characters
+/// are encoded in ascii insert mode.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Encoding {
+    Ascii,
+    Latin1,
+    Cp437,
+    Ebcdic,
+    Windows1252,
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+/// What the character column shows for one byte.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CharCell {
+    /// The first byte of a character.
+    Char(char),
+    /// A later byte of the character started before it.
+    Continuation,
+    /// A byte that doesn't decode, or decodes to something that can't be shown.
+    Invalid,
+}
+
+impl CharCell {
+    pub fn glyph(self) -> char {
+        match self {
+            CharCell::Char(c) => c,
+            CharCell::Continuation => '·',
+            CharCell::Invalid => '.',
+        }
+    }
+}
+
+/// Characters taking two terminal columns. Close enough for the CJK ranges and
+/// emoji; anything it misses just shifts the rest of the row.
+pub fn is_wide(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115f
+        | 0x2e80..=0x303e
+        | 0x3041..=0x33ff
+        | 0x3400..=0x4dbf
+        | 0x4e00..=0x9fff
+        | 0xa000..=0xa4cf
+        | 0xac00..=0xd7a3
+        | 0xf900..=0xfaff
+        | 0xfe30..=0xfe4f
+        | 0xff00..=0xff60
+        | 0xffe0..=0xffe6
+        | 0x1f300..=0x1f64f
+        | 0x1f900..=0x1f9ff
+        | 0x20000..=0x3fffd)
+}
+
+// Combining marks and zero width characters would merge into the cell before them
+fn is_zero_width(c: char) -> bool {
+    matches!(c as u32,
+        0x0300..=0x036f
+        | 0x1ab0..=0x1aff
+        | 0x1dc0..=0x1dff
+        | 0x200b..=0x200f
+        | 0x2028..=0x202e
+        | 0x2060..=0x206f
+        | 0x20d0..=0x20ff
+        | 0xfe00..=0xfe0f
+        | 0xfe20..=0xfe2f
+        | 0xfeff)
+}
+
+fn printable(c: char) -> Option<char> {
+    if c == '\0' || c.is_control() || is_zero_width(c) || c == '\u{ad}' {
+        None
+    } else {
+        Some(c)
+    }
+}
+
+fn char_cells(cells: &mut Vec<CharCell>, c: char, len: usize) {
+    cells.push(printable(c).map_or(CharCell::Invalid, CharCell::Char));
+    for _ in 1..len {
+        cells.push(CharCell::Continuation);
+    }
+}
+
+impl Encoding {
+    pub const NAMES: &'static [&'static str] = &[
+        "ascii",
+        "latin1",
+        "cp437",
+        "ebcdic",
+        "windows-1252",
+        "utf-8",
+        "utf-16le",
+        "utf-16be",
+    ];
+
+    /// How many bytes before and after a range have to be decoded along with it to
+    /// find where its characters start and end.
+    pub fn context_len(self) -> usize {
+        match self {
+            Encoding::Utf8 | Encoding::Utf16Le | Encoding::Utf16Be => 3,
+            _ => 0,
+        }
+    }
+
+    fn decode_byte(self, byte: u8) -> Option<char> {
+        let c = match (self, byte) {
+            (Encoding::Ascii, _) if byte.is_ascii_graphic() || byte == 0x20 => byte as char,
+            (Encoding::Ascii, _) => return None,
+            (Encoding::Cp437, 0x00..=0x1f) => CP437_LOW_TABLE[byte as usize],
+            (Encoding::Cp437, 0x7f) => '⌂',
+            (Encoding::Cp437, 0x80..=0xff) => CP437_HIGH_TABLE[byte as usize - 0x80],
+            (Encoding::Ebcdic, 0x00..=0x3f) => return None,
+            (Encoding::Ebcdic, _) => CP037_HIGH_TABLE[byte as usize - 0x40],
+            (Encoding::Windows1252, 0x80..=0x9f) => WINDOWS_1252_HIGH_TABLE[byte as usize - 0x80],
+            _ => byte as char,
+        };
+        printable(c)
+    }
+
+    /// Decodes a cell for every byte. `offset` is where `bytes` starts in the
+    /// buffer, as UTF-16 code units are taken to start at even offsets.
+    pub fn decode(self, bytes: &[u8], offset: usize) -> Vec<CharCell> {
+        let mut cells = Vec::with_capacity(bytes.len());
+        match self {
+            Encoding::Utf8 => {
+                let mut i = 0;
+                while i < bytes.len() {
+                    let len = match bytes[i] {
+                        0x00..=0x7f => 1,
+                        0xc2..=0xdf => 2,
+                        0xe0..=0xef => 3,
+                        0xf0..=0xf4 => 4,
+                        _ => 0,
+                    };
+                    let decoded = bytes
+                        .get(i..i + len)
+                        .and_then(|sequence| std::str::from_utf8(sequence).ok())
+                        .and_then(|s| s.chars().next());
+                    match decoded {
+                        Some(c) => {
+                            char_cells(&mut cells, c, len);
+                            i += len;
+                        }
+                        None => {
+                            cells.push(CharCell::Invalid);
+                            i += 1;
+                        }
+                    }
+                }
+            }
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                let unit = |i: usize| {
+                    let pair = [*bytes.get(i)?, *bytes.get(i + 1)?];
+                    Some(if self == Encoding::Utf16Le {
+                        u16::from_le_bytes(pair)
+                    } else {
+                        u16::from_be_bytes(pair)
+                    })
+                };
+                let mut i = offset % 2;
+                cells.resize(i, CharCell::Invalid);
+                while i < bytes.len() {
+                    let units = match unit(i) {
+                        Some(first @ 0xd800..=0xdbff) => match unit(i + 2) {
+                            Some(second @ 0xdc00..=0xdfff) => vec![first, second],
+                            _ => vec![first],
+                        },
+                        Some(first) => vec![first],
+                        None => vec![],
+                    };
+                    match char::decode_utf16(units.iter().copied()).next() {
+                        Some(Ok(c)) => char_cells(&mut cells, c, units.len() * 2),
+                        _ => cells.extend([CharCell::Invalid, CharCell::Invalid]),
+                    }
+                    i += std::cmp::max(units.len(), 1) * 2;
+                }
+                cells.truncate(bytes.len());
+            }
+            _ => cells.extend(bytes.iter().map(|&byte| {
+                self.decode_byte(byte)
+                    .map_or(CharCell::Invalid, CharCell::Char)
+            })),
+        }
+        cells
+    }
+
+    /// The bytes typing `c` inserts, if the encoding has it. Ascii keeps inserting
+    /// other characters as UTF-8.
+    pub fn encode(self, c: char) -> Option<Vec<u8>> {
+        match self {
+            Encoding::Ascii | Encoding::Utf8 => Some(c.to_string().into_bytes()),
+            Encoding::Utf16Le => Some(
+                c.encode_utf16(&mut [0; 2])
+                    .iter()
+                    .flat_map(|unit| unit.to_le_bytes())
+                    .collect(),
+            ),
+            Encoding::Utf16Be => Some(
+                c.encode_utf16(&mut [0; 2])
+                    .iter()
+                    .flat_map(|unit| unit.to_be_bytes())
+                    .collect(),
+            ),
+            _ => (0..=0xff)
+                .find(|&byte| self.decode_byte(byte) == Some(c))
+                .map(|byte| vec![byte]),
+        }
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ascii" => Ok(Encoding::Ascii),
+            "latin1" | "latin-1" | "iso-8859-1" => Ok(Encoding::Latin1),
+            "cp437" => Ok(Encoding::Cp437),
+            "ebcdic" | "cp037" => Ok(Encoding::Ebcdic),
+            "windows-1252" | "cp1252" => Ok(Encoding::Windows1252),
+            "utf-8" | "utf8" => Ok(Encoding::Utf8),
+            "utf-16le" | "utf16le" => Ok(Encoding::Utf16Le),
+            "utf-16be" | "utf16be" => Ok(Encoding::Utf16Be),
+            _ => Err(format!(
+                "expected one of {}, got {}",
+                Encoding::NAMES.join(", "),
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Encoding::Ascii => "ascii",
+            Encoding::Latin1 => "latin1",
+            Encoding::Cp437 => "cp437",
+            Encoding::Ebcdic => "ebcdic",
+            Encoding::Windows1252 => "windows-1252",
+            Encoding::Utf8 => "utf-8",
+            Encoding::Utf16Le => "utf-16le",
+            Encoding::Utf16Be => "utf-16be",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_multibyte() {
+        assert_eq!(
+            Encoding::Utf8.decode("é!".as_bytes(), 0),
+            vec![
+                CharCell::Char('é'),
+                CharCell::Continuation,
+                CharCell::Char('!')
+            ]
+        );
+        assert_eq!(
+            Encoding::Utf8.decode(&[0xa9, 0x41], 0),
+            vec![CharCell::Invalid, CharCell::Char('A')]
+        );
+        // Starting on an odd offset, the first byte ends a unit outside the slice
+        assert_eq!(
+            Encoding::Utf16Le.decode(&[0x00, 0x41, 0x00, 0x3d, 0xd8, 0x00, 0xde], 1),
+            vec![
+                CharCell::Invalid,
+                CharCell::Char('A'),
+                CharCell::Continuation,
+                CharCell::Char('😀'),
+                CharCell::Continuation,
+                CharCell::Continuation,
+                CharCell::Continuation,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_single_byte_roundtrip() {
+        assert_eq!(
+            Encoding::Ebcdic.decode(&[0xc1, 0x81, 0x05], 0),
+            vec![CharCell::Char('A'), CharCell::Char('a'), CharCell::Invalid,]
+        );
+        assert_eq!(Encoding::Ebcdic.encode('A'), Some(vec![0xc1]));
+        assert_eq!(Encoding::Cp437.encode('░'), Some(vec![0xb0]));
+        assert_eq!(Encoding::Windows1252.encode('€'), Some(vec![0x80]));
+        assert_eq!(Encoding::Latin1.encode('€'), None);
+        assert_eq!(Encoding::Utf16Be.encode('A'), Some(vec![0x00, 0x41]));
+    }
+}
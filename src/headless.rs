@@ -0,0 +1,267 @@
+use crossterm::event::{Event, KeyEvent};
+
+use crate::keymap::format_key;
+use crate::macros::MAX_REPLAY_DEPTH;
+use crate::modes::mode::{Mode, ModeTransition};
+use crate::modes::normal::Normal;
+use crate::options::{BytesPerLine, DEFAULT_BYTES_PER_LINE};
+use crate::BuffrCollection;
+
+/// Drives the modes without a terminal, for scripted edits.
+pub struct Headless<'a> {
+    buffr_collection: &'a mut BuffrCollection,
+    mode: Box<dyn Mode>,
+    messages: Vec<String>,
+}
+
+impl<'a> Headless<'a> {
+    pub fn new(buffr_collection: &'a mut BuffrCollection) -> Self {
+        Headless {
+            buffr_collection,
+            mode: Box::new(Normal::new()),
+            messages: vec![],
+        }
+    }
+
+    /// Whether a `:q`-style command has ended the session.
+    pub fn has_quit(&self) -> bool {
+        !self.mode.takes_input()
+    }
+
+    /// Messages the modes would have shown in the status line.
+    pub fn messages(&self) -> &[String] {
+        &self.messages
+    }
+
+    /// Feeds the keys to the current mode. Stops early on quit, and fails on the
+    /// first key that no mode handles.
+    pub fn run(&mut self, keys: &[KeyEvent]) -> Result<(), String> {
+        self.run_nested(keys, 1, 0)
+    }
+
+    fn run_nested(&mut self, keys: &[KeyEvent], count: usize, depth: usize) -> Result<(), String> {
+        if depth >= MAX_REPLAY_DEPTH {
+            return Err("macro replay nested too deeply".to_owned());
+        }
+        for _ in 0..count {
+            for key in keys {
+                if self.has_quit() {
+                    return Ok(());
+                }
+                // Replayed keys are already covered by the key that started the replay
+                if depth == 0 {
+                    self.buffr_collection.macros_mut().record(*key);
+                }
+                let (idle, stamp) = (self.mode.is_idle(), self.buffr_collection.change_stamp());
+                let transition = self
+                    .mode
+                    .transition(
+                        &Event::Key(*key),
+                        self.buffr_collection,
+                        self.bytes_per_line(),
+                    )
+                    .ok_or_else(|| format!("key {} was not handled", format_key(key)))?;
+                let has_info = transition.has_info();
+                self.apply(transition);
+
+                let after = self.buffr_collection.change_stamp();
+                let macros = self.buffr_collection.macros_mut();
+                macros.track_key(*key, idle, stamp);
+                macros.track_result(self.mode.is_idle(), after);
+                // Like in the view, a replayed step that reports something ends the replay
+                if depth > 0 && has_info {
+                    return Ok(());
+                }
+
+                let replay = self.buffr_collection.macros_mut().take_pending_replay();
+                if let Some((macro_keys, macro_count)) = replay {
+                    self.run_nested(&macro_keys, macro_count, depth + 1)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Without a terminal there's nothing for `auto` to fit
+    fn bytes_per_line(&self) -> usize {
+        match self.buffr_collection.options().bytes_per_line {
+            BytesPerLine::Fixed(bytes_per_line) => bytes_per_line,
+            BytesPerLine::Auto => DEFAULT_BYTES_PER_LINE,
+        }
+    }
+
+    fn apply(&mut self, transition: ModeTransition) {
+        match transition {
+            ModeTransition::None | ModeTransition::DirtyBytes(_) => {}
+            ModeTransition::NewMode(mode) | ModeTransition::ModeAndDirtyBytes(mode, _) => {
+                self.mode = mode;
+            }
+            ModeTransition::ModeAndInfo(mode, info)
+            | ModeTransition::ModeAndDirtyBytesAndInfo(mode, _, info) => {
+                self.mode = mode;
+                self.messages.push(info);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keymap::parse_keys;
+    use crate::CurrentBuffer;
+
+    fn run(data: &[u8], keys: &str) -> Vec<u8> {
+        run_with_messages(data, keys).0
+    }
+
+    fn run_with_messages(data: &[u8], keys: &str) -> (Vec<u8>, Vec<String>) {
+        let mut buffr_collection = BuffrCollection::with_current_buffer(
+            CurrentBuffer::from_data_and_path(data.to_vec(), None::<&str>),
+        );
+        let mut headless = Headless::new(&mut buffr_collection);
+        headless.run(&parse_keys(keys).unwrap()).unwrap();
+        let messages = headless.messages().to_vec();
+        let data = buffr_collection
+            .current()
+            .data
+            .slice_to_cow(..)
+            .into_owned();
+        (data, messages)
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        assert_eq!(run(b"hello", "QlypQ2q"), b"heeeello");
+    }
+
+    #[test]
+    fn test_repeat_change() {
+        assert_eq!(run(b"abcdef", "d2."), b"def");
+        assert_eq!(run(b"abcd", "i<c-n><esc>l."), b"\0a\0bcd");
+        assert_eq!(run(b"abcd", "rzl.u"), b"zbcd");
+    }
+
+    #[test]
+    fn test_search_motions() {
+        assert_eq!(run(b"xabyabzab", "/ab<ret>d"), b"xyabzab");
+        assert_eq!(run(b"xabyabzab", "/ab<ret>nd"), b"xabyzab");
+        assert_eq!(run(b"xabyabzab", "/ab<ret>3nd"), b"xyabzab");
+        assert_eq!(run(b"xabyabzab", "?ab<ret>d"), b"xabyabz");
+        assert_eq!(run(b"xabyabzab", "/ab<ret>Nd"), b"xabyabz");
+        assert_eq!(run(b"xa\x01yab", "<a-/>61<c-w><ret>d"), b"xyab");
+        assert_eq!(run(b"xabyabzab", "/ab<ret>/<up><ret>d"), b"xabyzab");
+    }
+
+    #[test]
+    fn test_search_wrap_messages() {
+        let (_, messages) = run_with_messages(b"xabyab", "/ab<ret>nn");
+        assert_eq!(messages, ["search wrapped to the top"]);
+
+        // Only the loaded window is searched, and the message says so
+        let mut buffer = CurrentBuffer::from_data_and_path(b"xabyab".to_vec(), None::<&str>);
+        buffer.data_start_offset = 0x100;
+        let mut buffr_collection = BuffrCollection::with_current_buffer(buffer);
+        let mut headless = Headless::new(&mut buffr_collection);
+        headless
+            .run(&parse_keys("?ab<ret>/cd<ret>").unwrap())
+            .unwrap();
+        assert_eq!(
+            headless.messages(),
+            [
+                "search wrapped to the bottom of the loaded bytes",
+                "no matches in the loaded bytes"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_masked_patterns() {
+        assert_eq!(run(b"\x14\x41\x4f\x51", "%S4?<ret>d"), b"\x14\x51");
+        assert_eq!(run(b"\x14\x41\x4f\x51", "%S?1<ret>d"), b"\x14\x4f");
+        assert_eq!(run(b"\x7f\x80\xbf\xc0", "%S80&c0<ret>d"), b"\x7f\xc0");
+        assert_eq!(run(b"\x7f\x80\xbf\xc0", "%S??<ret>d"), b"");
+        assert_eq!(run(b"ab\x01cd\x11cd\x21cd", "%S?16364<ret>d"), b"ab");
+        assert_eq!(run(b"xab\x01ab\x31", "<a-/>6162?1<ret>d"), b"xab\x31");
+    }
+
+    #[test]
+    fn test_regex_patterns() {
+        assert_eq!(run(b"abcxaxc", "%s<c-r>a.c<ret>d"), b"x");
+        assert_eq!(run(b"k=12,k=3", "%s<c-r>k=([0-9]+)<ret>d"), b"k=,k=");
+        assert_eq!(run(b"a,,b,c", "%<a-s>/<c-r>,+<ret>d"), b",,,");
+        assert_eq!(run(b"ab12cd345", "/<c-r>[0-9]+<ret>d"), b"abcd345");
+        assert_eq!(run(b"a\xffb\n", "%s<c-r>\\xff|\\n<ret>d"), b"ab");
+        assert_eq!(run(b"abc", "%s<c-r>(b<ret>"), b"abc");
+    }
+
+    #[test]
+    fn test_read_and_write_files() {
+        let base = std::env::temp_dir().join(format!("tofu-files-{}", std::process::id()));
+        let base = base.display();
+        std::fs::write(format!("{}.in", base), b"XY").unwrap();
+
+        assert_eq!(run(b"abc", &format!("l:r {}.in<ret>", base)), b"aXYbc");
+        assert_eq!(run(b"abcd", &format!("l:ro {}.in<ret>", base)), b"aXYd");
+        assert_eq!(
+            run(b"abcd", &format!("%<a-s>b:ro {}.in<ret>", base)),
+            b"XXXX"
+        );
+        assert_eq!(run(b"abc", &format!("ll:ro {}.in<ret>", base)), b"abX");
+
+        run(b"abcd", &format!("%<a-s>w:ws {}.out<ret>", base));
+        assert_eq!(std::fs::read(format!("{}.out", base)).unwrap(), b"abcd");
+        run(b"abcd", &format!("%<a-s>w:wss {}.out<ret>", base));
+        assert_eq!(std::fs::read(format!("{}.out.1", base)).unwrap(), b"ab");
+        assert_eq!(std::fs::read(format!("{}.out.2", base)).unwrap(), b"cd");
+
+        for suffix in ["in", "out", "out.1", "out.2"] {
+            std::fs::remove_file(format!("{}.{}", base, suffix)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_inspector_edit_with_several_carets() {
+        assert_eq!(
+            run(&[0; 8], "%<a-s>w<a-;><a-i>jjjc258<ret>q"),
+            [1, 2, 1, 2, 1, 2, 1, 2]
+        );
+        // The carets stay where the values were written
+        assert_eq!(
+            run(&[0; 8], "%<a-s>w<a-;><a-i>jjjc258<ret>q;d"),
+            [2, 2, 2, 2]
+        );
+        // Values that don't fit at every caret aren't written at all
+        let too_long = |data: &[u8], keys| {
+            let (written, messages) = run_with_messages(data, keys);
+            assert_eq!(written, data);
+            assert!(messages.iter().any(|message| message.contains("don't fit")));
+        };
+        too_long(&[0; 8], "%<a-s>w<a-i>jjjc258<ret>q");
+        too_long(&[0; 4], "%<a-s>b<a-i>jjjjc1<ret>q");
+        too_long(&[0; 4], "%<a-s>b<a-i>jjjc-1<ret>q");
+    }
+
+    #[test]
+    fn test_replay_stored_macro() {
+        assert_eq!(run(b"abc", ":macro a d<ret>\"a2q"), b"c");
+    }
+
+    #[test]
+    fn test_macro_register_names() {
+        // Uppercase registers append to the lowercase one
+        assert_eq!(run(b"abcd", ":macro a d<ret>\"AQdQu\"aq"), b"cd");
+        // The digits hold the yank ring, so macros can't be stored there
+        let (data, messages) = run_with_messages(b"abc", "\"1Qd:macro 2 d<ret>");
+        assert_eq!(data, b"bc");
+        assert!(messages
+            .iter()
+            .all(|message| message.contains("only holds")));
+        assert_eq!(messages.len(), 2);
+    }
+
+    #[test]
+    fn test_replay_stops_at_info() {
+        assert_eq!(run(b"abcdef", ":macro a d'z<ret>\"a3q"), b"bcdef");
+    }
+}
@@ -1,139 +1,131 @@
-use crate::hex_view::{
-    colorize_byte, make_padding, OutputColorizer, PrioritizedStyle, Priority, StylingCommand,
-};
-use crossterm::style::{Attributes, Color};
-use crossterm::{style, ErrorKind};
-use lazy_static::lazy_static;
-use std::convert::TryInto;
+use crate::decoders::Decoder;
+use crate::hex_view::{basic_style, make_padding, OutputColorizer, StylingCommand};
+use crate::inspector::Field;
+use crate::options::{Endian, Options};
+use crossterm::ErrorKind;
 use std::io::Write;
 
-lazy_static! {
-    static ref BIN_ZERO_STYLE: StylingCommand =
-        StylingCommand::default().with_start_style(PrioritizedStyle {
-            style: style::ContentStyle {
-                foreground_color: Some(Color::AnsiValue(150)),
-                background_color: Some(Color::Reset),
-                attributes: Attributes::default(),
-            },
-            priority: Priority::Basic,
-        });
-    static ref BIN_ONE_STYLE: StylingCommand =
-        StylingCommand::default().with_start_style(PrioritizedStyle {
-            style: style::ContentStyle {
-                foreground_color: Some(Color::Blue),
-                background_color: Some(Color::Reset),
-                attributes: Attributes::default(),
-            },
-            priority: Priority::Basic,
-        });
-    static ref INVALID_DATA_STYLE: StylingCommand =
-        StylingCommand::default().with_start_style(PrioritizedStyle {
-            style: style::ContentStyle {
-                foreground_color: Some(Color::Red),
-                background_color: Some(Color::Reset),
-                attributes: Attributes::default(),
-            },
-            priority: Priority::Basic,
-        });
-    static ref DEFAULT_STYLE: StylingCommand =
-        StylingCommand::default().with_start_style(PrioritizedStyle {
-            style: style::ContentStyle {
-                foreground_color: Some(Color::DarkMagenta),
-                background_color: Some(Color::Reset),
-                attributes: Attributes::default(),
-            },
-            priority: Priority::Basic,
-        });
-    static ref DEFAULT_VALUE_STYLE: StylingCommand =
-        StylingCommand::default().with_start_style(PrioritizedStyle {
-            style: style::ContentStyle {
-                foreground_color: Some(Color::AnsiValue(150)),
-                background_color: Some(Color::Reset),
-                attributes: Attributes::default(),
-            },
-            priority: Priority::Basic,
-        });
+/// The theme's inspector styles.
+struct Styles {
+    label: StylingCommand,
+    value: StylingCommand,
+    one: StylingCommand,
+    error: StylingCommand,
+    unavailable: StylingCommand,
+    selected: StylingCommand,
 }
 
 fn format_binary_byte(
     stdout: &mut impl Write,
     colorizer: &OutputColorizer,
+    styles: &Styles,
     byte: u8,
 ) -> Result<(), ErrorKind> {
     for c in format!("{:08b}", byte).chars() {
         match c {
-            '0' => colorizer.draw(stdout, '0', &BIN_ZERO_STYLE)?,
-            '1' => colorizer.draw(stdout, '1', &BIN_ONE_STYLE)?,
+            '0' => colorizer.draw(stdout, '0', &styles.value)?,
+            '1' => colorizer.draw(stdout, '1', &styles.one)?,
             _ => {}
         }
     }
     Ok(())
 }
 
-fn format_char(c: char) -> String {
-    if c.is_ascii_graphic() {
-        c.to_string()
-    } else {
-        format!("{:x?}", c)
-    }
-}
-
-fn utf8_into_char(data: &[u8]) -> Result<char, char> {
-    let max_char_len = if data.len() < 4 { data.len() } else { 4 };
-
-    for i in 1..=max_char_len {
-        if let Ok(s) = String::from_utf8(data[0..i].to_vec()) {
-            return Ok(s.chars().next().unwrap());
-        }
-    }
+// Room for the longest label, `utf-16 be`, and the longest integer
+const LABEL_WIDTH: usize = 9;
+const VALUE_WIDTH: usize = 20;
 
-    Err('�')
+pub struct BytePropertiesFormatter<'a> {
+    data: &'a [u8],
+    fields: &'a [Field],
+    decoders: &'a [&'static Decoder],
+    endian: Endian,
+    selected: Option<usize>,
+    line: usize,
+    styles: Styles,
 }
 
-fn bytes_to_4_byte_vec(data: &[u8]) -> Vec<u8> {
-    if data.len() >= 4 {
-        data[0..4].to_vec()
-    } else {
-        let mut res = data.to_vec();
-        while res.len() < 4 {
-            res.insert(0, 0);
+impl<'a> BytePropertiesFormatter<'a> {
+    /// `selected` is the row being edited, if any, whose label is highlighted.
+    pub fn new(data: &'a [u8], options: &'a Options, selected: Option<usize>) -> Self {
+        Self {
+            data,
+            fields: &options.inspector,
+            decoders: &options.decoders,
+            endian: options.inspector_endian,
+            selected,
+            line: 0,
+            styles: Styles {
+                label: basic_style(options.theme.inspector_label),
+                value: basic_style(options.theme.inspector_value),
+                one: basic_style(options.theme.inspector_one),
+                error: basic_style(options.theme.inspector_error),
+                unavailable: basic_style(options.theme.inspector_unavailable),
+                selected: basic_style(options.theme.inspector_selected),
+            },
         }
-        res
     }
-}
 
-fn utf16_into_char(data: &[u8]) -> Result<char, char> {
-    if data.len() >= 2 {
-        if let Ok(s) = String::from_utf16(&[u16::from_be_bytes([data[0], data[1]])]) {
-            return Ok(s.chars().next().unwrap());
-        }
+    /// Rows taken by the inspector: the `inspector` rows, then the `decoders` ones.
+    pub fn height(options: &Options) -> usize {
+        options.inspector.len() + options.decoders.len()
     }
 
-    if data.len() >= 4 {
-        if let Ok(s) = String::from_utf16(&[
-            u16::from_be_bytes([data[0], data[1]]),
-            u16::from_be_bytes([data[2], data[3]]),
-        ]) {
-            return Ok(s.chars().next().unwrap());
-        }
+    pub fn are_all_printed(&self) -> bool {
+        self.line >= self.fields.len() + self.decoders.len()
     }
 
-    Err('�')
-}
-
-pub struct BytePropertiesFormatter<'a> {
-    data: &'a [u8],
-    line: usize,
-}
-
-impl<'a> BytePropertiesFormatter<'a> {
-    pub fn new(data: &'a [u8]) -> Self {
-        assert!(data.len() <= 4);
-        Self { data, line: 0 }
+    fn draw_value(
+        &self,
+        stdout: &mut impl Write,
+        colorizer: &OutputColorizer,
+        field: Field,
+        value: Result<String, String>,
+    ) -> Result<usize, ErrorKind> {
+        match (field, value) {
+            (Field::Hex, Ok(text)) => {
+                for byte in self.data.iter().take(8) {
+                    colorizer.draw_hex_byte(stdout, *byte, &self.styles.value)?;
+                }
+                Ok(text.len() + 1)
+            }
+            (Field::Bin, Ok(text)) => {
+                for byte in self.data.iter().take(4) {
+                    format_binary_byte(stdout, colorizer, &self.styles, *byte)?;
+                    colorizer.draw(stdout, ' ', &self.styles.label)?;
+                }
+                Ok(text.len() + 1)
+            }
+            (_, Ok(text)) => {
+                colorizer.draw(stdout, &text, &self.styles.value)?;
+                Ok(text.chars().count())
+            }
+            (_, Err(text)) => {
+                colorizer.draw(stdout, &text, &self.styles.error)?;
+                Ok(text.chars().count())
+            }
+        }
     }
 
-    pub fn are_all_printed(&self) -> bool {
-        self.line > (BytePropertiesFormatter::height() - 1)
+    fn draw_decoder_line(
+        &self,
+        stdout: &mut impl Write,
+        colorizer: &OutputColorizer,
+        decoder: &Decoder,
+    ) -> Result<(), ErrorKind> {
+        let label = format!(
+            "{:>width$}: ",
+            decoder.label(self.endian),
+            width = LABEL_WIDTH
+        );
+        if self.data.len() < decoder.size {
+            return colorizer.draw(stdout, format!("{}-", label), &self.styles.unavailable);
+        }
+        colorizer.draw(stdout, label, &self.styles.label)?;
+        match (decoder.decode)(&self.data[..decoder.size], self.endian) {
+            Ok(text) => colorizer.draw(stdout, text, &self.styles.value),
+            Err(text) => colorizer.draw(stdout, text, &self.styles.error),
+        }
     }
 
     pub fn draw_line(
@@ -141,112 +133,58 @@ impl<'a> BytePropertiesFormatter<'a> {
         stdout: &mut impl Write,
         colorizer: &OutputColorizer,
     ) -> Result<(), ErrorKind> {
-        let first_byte = if !self.data.is_empty() {
-            self.data[0]
-        } else {
-            0
-        };
-
-        match self.line {
-            0 => {
-                colorizer.draw(stdout, "hex u8: ", &DEFAULT_STYLE)?;
-                colorizer.draw_hex_byte(
-                    stdout,
-                    first_byte,
-                    &colorize_byte(first_byte, &DEFAULT_VALUE_STYLE),
-                )?;
-
-                colorizer.draw(stdout, "          hex u32: ", &DEFAULT_STYLE)?;
-                for byte in self.data.iter() {
-                    colorizer.draw_hex_byte(
-                        stdout,
-                        *byte,
-                        &colorize_byte(*byte, &DEFAULT_VALUE_STYLE),
-                    )?;
+        let field = match self.fields.get(self.line) {
+            Some(field) => *field,
+            None => {
+                if let Some(decoder) = self.decoders.get(self.line - self.fields.len()) {
+                    self.line += 1;
+                    return self.draw_decoder_line(stdout, colorizer, decoder);
                 }
+                return Ok(());
             }
-            1 => {
-                colorizer.draw(stdout, "bin u8: ", &DEFAULT_STYLE)?;
-                format_binary_byte(stdout, colorizer, first_byte)?;
+        };
+        let is_selected = self.selected == Some(self.line);
+        self.line += 1;
 
-                colorizer.draw(stdout, "     bin u32: ", &DEFAULT_STYLE)?;
-                for byte in self.data.iter() {
-                    format_binary_byte(stdout, colorizer, *byte)?;
-                    colorizer.draw(stdout, ' ', &DEFAULT_STYLE)?;
-                }
+        let values = match field.decode(self.data, self.endian) {
+            Some(values) => values,
+            None => {
+                let label = format!("{:>width$}", field.to_string(), width = LABEL_WIDTH);
+                let label_style: &StylingCommand = if is_selected {
+                    &self.styles.selected
+                } else {
+                    &self.styles.unavailable
+                };
+                colorizer.draw(stdout, label, label_style)?;
+                return colorizer.draw(stdout, ": -", &self.styles.unavailable);
             }
-            2 => {
-                let byte_literal = format!("{}", first_byte);
-                let len = byte_literal.len();
-
-                colorizer.draw(stdout, "dec u8: ", &DEFAULT_STYLE)?;
-                colorizer.draw(stdout, byte_literal, &DEFAULT_VALUE_STYLE)?;
-
-                colorizer.draw(stdout, make_padding(12 - len), &DEFAULT_STYLE)?;
-                colorizer.draw(stdout, " dec u32: ", &DEFAULT_STYLE)?;
-                colorizer.draw(
-                    stdout,
-                    u32::from_be_bytes(bytes_to_4_byte_vec(self.data).try_into().unwrap()),
-                    &DEFAULT_VALUE_STYLE,
-                )?;
+        };
+        for (i, (label, value)) in values.into_iter().enumerate() {
+            if i > 0 {
+                colorizer.draw(stdout, ' ', &self.styles.label)?;
             }
-            3 => {
-                let byte_literal = format!("{}", first_byte as i8);
-                let len = byte_literal.len();
-
-                colorizer.draw(stdout, "dec i8: ", &DEFAULT_STYLE)?;
-                colorizer.draw(stdout, byte_literal, &DEFAULT_VALUE_STYLE)?;
-
-                colorizer.draw(stdout, make_padding(12 - len), &DEFAULT_STYLE)?;
-                colorizer.draw(stdout, " dec i32: ", &DEFAULT_STYLE)?;
+            let label = format!("{:>width$}", label, width = LABEL_WIDTH);
+            let label_style: &StylingCommand = if is_selected && i == 0 {
+                &self.styles.selected
+            } else {
+                &self.styles.label
+            };
+            colorizer.draw(stdout, label, label_style)?;
+            colorizer.draw(stdout, ": ", &self.styles.label)?;
+            let len = self.draw_value(stdout, colorizer, field, value)?;
+            if i == 0 {
                 colorizer.draw(
                     stdout,
-                    i32::from_be_bytes(bytes_to_4_byte_vec(self.data).try_into().unwrap()),
-                    &DEFAULT_VALUE_STYLE,
+                    make_padding(VALUE_WIDTH.saturating_sub(len)),
+                    &self.styles.label,
                 )?;
             }
-            4 => {
-                colorizer.draw(stdout, " utf-8: ", &DEFAULT_STYLE)?;
-                let len = match utf8_into_char(self.data) {
-                    Ok(c) => {
-                        let c = format_char(c);
-                        let len = c.len();
-                        colorizer.draw(stdout, c, &DEFAULT_VALUE_STYLE)?;
-                        len
-                    }
-                    Err(c) => {
-                        colorizer.draw(stdout, c, &INVALID_DATA_STYLE)?;
-                        1
-                    }
-                };
-
-                colorizer.draw(stdout, make_padding(12 - len), &DEFAULT_STYLE)?;
-                colorizer.draw(stdout, "  utf-16: ", &DEFAULT_STYLE)?;
-                match utf16_into_char(self.data) {
-                    Ok(c) => colorizer.draw(stdout, format_char(c), &DEFAULT_VALUE_STYLE),
-                    Err(c) => colorizer.draw(stdout, c, &INVALID_DATA_STYLE),
-                }?;
-            }
-            _ => (),
         }
-
-        self.line += 1;
-
         Ok(())
     }
 
-    pub fn height() -> usize {
-        5
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::hex_view::byte_properties::utf16_into_char;
-
-    #[test]
-    fn test_utf16_into_char() {
-        let data = &[0xd8, 0x01, 0xdc, 0x37];
-        assert_eq!(utf16_into_char(data), Ok('𐐷'));
+    /// Columns taken by the widest line, one with two integers.
+    pub fn width() -> usize {
+        66
     }
 }
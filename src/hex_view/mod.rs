@@ -1,29 +1,16 @@
-use crossterm::{
-    queue,
-    style::{self, Color},
-    ErrorKind,
-};
-use std::fmt;
+use crossterm::{queue, style, ErrorKind};
+use std::borrow::Cow;
 use std::fmt::Display;
 use std::io::Write;
 
+use crate::theme::ByteStyles;
+
 mod byte_properties;
 pub mod view;
 
-const COLOR_NULL: Color = Color::AnsiValue(150);
-const COLOR_ASCII_PRINTABLE: Color = Color::Cyan;
-const COLOR_ASCII_WHITESPACE: Color = Color::Green;
-const COLOR_ASCII_OTHER: Color = Color::Rgb {
-    r: 232,
-    g: 52,
-    b: 210,
-};
-const COLOR_NONASCII: Color = Color::Yellow;
-
 #[derive(Debug, Clone, Copy)]
 pub enum Priority {
     Basic,
-    #[allow(dead_code)]
     Mark,
     Selection,
     Cursor,
@@ -117,61 +104,49 @@ fn queue_style(stdout: &mut impl Write, style: &style::ContentStyle) -> Result<(
     Ok(())
 }
 
-fn get_byte_color(byte: u8) -> Color {
-    if byte == 0x00 {
-        COLOR_NULL
-    } else if byte.is_ascii_graphic() {
-        COLOR_ASCII_PRINTABLE
-    } else if byte.is_ascii_whitespace() {
-        COLOR_ASCII_WHITESPACE
-    } else if byte.is_ascii() {
-        COLOR_ASCII_OTHER
-    } else {
-        COLOR_NONASCII
-    }
-}
-
-fn colorize_byte(byte: u8, style_cmd: &StylingCommand) -> StylingCommand {
-    let default_content_style = style::ContentStyle {
-        foreground_color: None,
-        background_color: None,
-        attributes: Default::default(),
-    };
-
-    let start_style = *style_cmd.start_style().unwrap_or(&default_content_style);
-
-    style_cmd.clone().with_start_style(PrioritizedStyle {
-        style: style::ContentStyle {
-            foreground_color: Some(get_byte_color(byte)),
-            background_color: start_style.background_color,
-            attributes: start_style.attributes,
-        },
+/// A style for text drawn on its own, outside of the byte columns.
+fn basic_style(style: style::ContentStyle) -> StylingCommand {
+    StylingCommand::default().with_start_style(PrioritizedStyle {
+        style,
         priority: Priority::Basic,
     })
 }
 
-pub fn make_padding(len: usize) -> &'static str {
-    debug_assert!(len < 0x40, "can't make padding of len {}", len);
-    &"                                                                "[..len]
+pub fn make_padding(len: usize) -> Cow<'static, str> {
+    const SPACES: &str = "                                                                ";
+    if len <= SPACES.len() {
+        Cow::Borrowed(&SPACES[..len])
+    } else {
+        Cow::Owned(" ".repeat(len))
+    }
 }
 
-struct ByteAsciiRepr(u8);
+pub struct OutputColorizer<'a> {
+    byte_styles: &'a ByteStyles,
+}
 
-impl fmt::Display for ByteAsciiRepr {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.0.is_ascii_graphic() || self.0 == 0x20 {
-            write!(f, "{}", char::from(self.0))
-        } else {
-            write!(f, ".")
-        }
+impl<'a> OutputColorizer<'a> {
+    pub fn new(byte_styles: &'a ByteStyles) -> Self {
+        Self { byte_styles }
     }
-}
 
-pub struct OutputColorizer;
+    /// Gives `style_cmd` the colour of the byte, keeping its background. When the
+    /// byte has a background or attributes of its own, they end with the byte.
+    fn colorize_byte(&self, byte: u8, style_cmd: &StylingCommand) -> StylingCommand {
+        let start_style = style_cmd.start_style().copied().unwrap_or_default();
 
-impl OutputColorizer {
-    pub fn new() -> Self {
-        Self {}
+        let colorized = style_cmd.clone().with_start_style(PrioritizedStyle {
+            style: self.byte_styles.apply(byte, &start_style),
+            priority: Priority::Basic,
+        });
+        if !self.byte_styles.is_highlighted(byte) {
+            return colorized;
+        }
+        let end_style = style_cmd.end_style().copied().unwrap_or(start_style);
+        colorized.with_end_style(PrioritizedStyle {
+            style: self.byte_styles.restore(&end_style),
+            priority: Priority::Basic,
+        })
     }
 
     pub fn draw_hex_byte(
@@ -180,7 +155,18 @@ impl OutputColorizer {
         byte: u8,
         style: &StylingCommand,
     ) -> Result<(), ErrorKind> {
-        let style_cmd = colorize_byte(byte, style);
+        self.draw_hex_digits(stdout, byte, style)?;
+        queue!(stdout, style::Print(" ".to_string()))
+    }
+
+    /// Draws the two hex digits of `byte` without the space that follows a cell.
+    pub fn draw_hex_digits(
+        &self,
+        stdout: &mut impl Write,
+        byte: u8,
+        style: &StylingCommand,
+    ) -> Result<(), ErrorKind> {
+        let style_cmd = self.colorize_byte(byte, style);
 
         if let Some(start_cmd) = style_cmd.start_style() {
             queue_style(stdout, start_cmd)?;
@@ -198,22 +184,25 @@ impl OutputColorizer {
             queue_style(stdout, end_cmd)?;
         }
 
-        queue!(stdout, style::Print(" ".to_string()))
+        Ok(())
     }
 
-    pub fn draw_ascii_byte(
+    /// Draws the character column cell of `byte`, coloured by the byte. `glyph` may be
+    /// empty when a wide character before it already covers the cell.
+    pub fn draw_char_cell(
         &self,
         stdout: &mut impl Write,
         byte: u8,
+        glyph: &str,
         style: &StylingCommand,
     ) -> Result<(), ErrorKind> {
-        let style_cmd = colorize_byte(byte, style);
+        let style_cmd = self.colorize_byte(byte, style);
 
         if let Some(start_cmd) = style_cmd.start_style() {
             queue_style(stdout, start_cmd)?;
         }
 
-        queue!(stdout, style::Print(format!("{}", ByteAsciiRepr(byte))))?;
+        queue!(stdout, style::Print(glyph))?;
 
         if let Some(end_cmd) = style_cmd.end_style() {
             queue_style(stdout, end_cmd)?;
@@ -241,9 +230,3 @@ impl OutputColorizer {
         Ok(())
     }
 }
-
-impl Default for OutputColorizer {
-    fn default() -> Self {
-        OutputColorizer::new()
-    }
-}
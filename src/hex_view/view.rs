@@ -1,6 +1,7 @@
+use std::borrow::Cow;
 use std::cell::Cell;
 use std::cmp;
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt;
 use std::io::{
     SeekFrom,
@@ -16,9 +17,10 @@ use std::fs::File;
 use std::fs::OpenOptions;
 use crossterm::{
     cursor,
-    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    event::{
+        self, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
     execute, queue, style,
-    style::{Color, Stylize},
     terminal, 
     QueueableCommand, 
     Result,
@@ -26,29 +28,46 @@ use crossterm::{
 use xi_rope::{
     Interval,
     Delta,
-    Rope
 };
 use xi_rope::tree::TreeBuilder;
-use xi_rope::multiset::SubsetBuilder;
 use crate::byte_rope::Bytes;  // TODO Horrible name that will collide this must be changed
 use std::time::{
     SystemTime, 
     UNIX_EPOCH
 };
 use super::byte_properties::BytePropertiesFormatter;
-use super::{make_padding, PrioritizedStyle, Priority, StylingCommand};
+use super::{make_padding, queue_style, PrioritizedStyle, Priority, StylingCommand};
 use crate::current_buffer::*;
+use crate::encoding::{is_wide, CharCell};
 use crate::hex_view::OutputColorizer;
+use crate::inspector::MAX_INSPECTED_BYTES;
+use crate::keymap::format_key;
+use crate::macros::MAX_REPLAY_DEPTH;
+use crate::minimap::Minimap;
 use crate::modes;
 use crate::modes::mode::{DirtyBytes, Mode, ModeTransition};
-use crate::selection::Direction;
+use crate::options::{BytesPerLine, Endian, MinimapStyle, StatuslineStyle, DEFAULT_BYTES_PER_LINE};
+use crate::panes::{Pane, SplitDirection};
+use crate::selection::{Direction, Selection};
+use crate::theme::{downgrade, gradient_color, ColorDepth, Theme};
 // use std::path::Path;
 use std::env;
 
+
 use crate::byte_rope::Rope as CustomByteRope;
 
 const VERTICAL: &str = "│";
+const HORIZONTAL: &str = "─";
 const LEFTARROW: &str = "";
+// Offsets get at least as many digits as a 32-bit address
+const MIN_OFFSET_WIDTH: usize = 8;
+// The minimap sidebar: a divider, the strip and the window marker
+const MINIMAP_WIDTH: u16 = 3;
+// How often the minimap picks up blocks while its file is being scanned
+const MINIMAP_REFRESH: time::Duration = time::Duration::from_millis(100);
+// Minimap cells by entropy, two bits per byte apart
+const ENTROPY_SHADES: [&str; 4] = ["░", "▒", "▓", "█"];
+const MINIMAP_MARKER: &str = "◀";
 
 // Oh my Uma, it's a Debug-Log... 
 // Why is this returning a result???
@@ -73,7 +92,6 @@ fn debug_log(message: &str) {
             if log_path.exists() {
                 // If the path exists, attempt to open the file for writing
                 if let Ok(mut file) = OpenOptions::new()
-                    .write(true)
                     .append(true)
                     .open(&log_path) {
 
@@ -88,6 +106,7 @@ fn debug_log(message: &str) {
 }
 
 
+#[allow(dead_code)]
 struct MixedRepr(u8);
 
 impl fmt::Display for MixedRepr {
@@ -104,6 +123,7 @@ trait StatusLinePrompter: Mode {
     fn render_with_size(
         &self,
         stdout: &mut dyn Write,
+        theme: &Theme,
         max_width: usize,
         last_start_col: usize,
     ) -> Result<usize>;
@@ -124,19 +144,18 @@ impl StatusLinePrompter for modes::search::Search {
     fn render_with_size(
         &self,
         stdout: &mut dyn Write,
+        theme: &Theme,
         mut max_width: usize,
         last_start_col: usize,
     ) -> Result<usize> {
         let mut start_column = last_start_col;
-        d_queue!(
-            stdout,
-            style::PrintStyledContent(
-                style::style("search:")
-                    .with(style::Color::White)
-                    .on(style::Color::Blue),
-            )
-        )?;
-        max_width -= "search:".len();
+        let label = if self.pattern.regex {
+            "regex:"
+        } else {
+            "search:"
+        };
+        d_queue!(stdout, style::PrintStyledContent(theme.info.apply(label)))?;
+        max_width -= label.len();
 
         // Make sure start_column is between self.cursor and the length of the pattern
         if self.pattern.pieces.len() <= start_column {
@@ -146,66 +165,77 @@ impl StatusLinePrompter for modes::search::Search {
         }
 
         if self.hex {
-            if self.cursor >= start_column + max_width / 3 {
-                start_column = self.cursor - max_width / 3 + 1;
+            use modes::search::HexHalf;
+            // While a mask is typed, it's shown after the byte before the cursor
+            let mask_entry = match self.hex_half {
+                Some(HexHalf::Mask(high)) => Some(high),
+                _ => None,
+            };
+            let mut lengths = self.pattern.pieces[start_column..]
+                .iter()
+                .enumerate()
+                .map(|(i, piece)| match mask_entry {
+                    Some(_) if start_column + i + 1 == self.cursor => "80&c0 ".len(),
+                    _ => piece.to_hex().len() + 1,
+                })
+                .collect::<Vec<_>>();
+            lengths.push("   ".len()); // The cursor past the end
+            let mut required_length: usize = lengths[..=self.cursor - start_column].iter().sum();
+            while required_length > max_width && start_column < self.cursor {
+                required_length -= lengths.remove(0);
+                start_column += 1;
             }
-            let last_byte = std::cmp::min(self.pattern.pieces.len(), start_column + max_width / 3);
 
             let normalized_cursor = self.cursor - start_column;
-            for (i, piece) in self.pattern.pieces[start_column..last_byte]
+            for ((i, piece), length) in self.pattern.pieces[start_column..]
                 .iter()
                 .enumerate()
+                .zip(lengths)
             {
-                match piece {
-                    PatternPiece::Literal(byte) if normalized_cursor != i => {
-                        d_queue!(stdout, style::Print(format!("{:02x} ", byte)))?
-                    }
-                    PatternPiece::Literal(byte)
-                        if normalized_cursor == i && self.hex_half.is_some() =>
-                    {
-                        d_queue!(
-                            stdout,
-                            style::Print(format!("{:x}", byte >> 4)),
-                            style::PrintStyledContent(
-                                style::style(format!("{:x}", byte & 0xf))
-                                    .with(style::Color::Black)
-                                    .on(style::Color::White)
-                            ),
-                            style::Print(" "),
-                        )?
-                    }
-                    PatternPiece::Literal(byte) => d_queue!(
+                if max_width < length {
+                    break;
+                }
+                max_width -= length;
+                let text = piece.to_hex();
+                let style = match piece {
+                    PatternPiece::Literal(_) => style::ContentStyle::new(),
+                    _ => theme.prompt_wildcard,
+                };
+                match mask_entry {
+                    Some(high) if i + 1 == normalized_cursor => d_queue!(
                         stdout,
-                        style::PrintStyledContent(
-                            style::style(format!("{:02x}", byte))
-                                .with(style::Color::Black)
-                                .on(style::Color::White)
-                        ),
+                        style::PrintStyledContent(style.apply(format!(
+                            "{:02x}&{}",
+                            piece.value(),
+                            high.map(|high| format!("{:x}", high >> 4)).unwrap_or_default()
+                        ))),
+                        style::PrintStyledContent(theme.prompt_cursor.apply(if high.is_some() {
+                            " "
+                        } else {
+                            "  "
+                        })),
                         style::Print(" "),
                     )?,
-                    PatternPiece::Wildcard if normalized_cursor != i => d_queue!(
+                    _ if normalized_cursor != i || mask_entry.is_some() => {
+                        d_queue!(stdout, style::PrintStyledContent(style.apply(text + " ")))?
+                    }
+                    _ if self.hex_half.is_some() => d_queue!(
                         stdout,
-                        style::PrintStyledContent(style::style("** ").with(style::Color::DarkRed))
+                        style::PrintStyledContent(style.apply(&text[..1])),
+                        style::PrintStyledContent(theme.prompt_cursor.apply(&text[1..])),
+                        style::Print(" "),
                     )?,
-                    PatternPiece::Wildcard => d_queue!(
+                    _ => d_queue!(
                         stdout,
-                        style::PrintStyledContent(
-                            style::style("**")
-                                .with(style::Color::DarkRed)
-                                .on(style::Color::White)
-                        ),
+                        style::PrintStyledContent(theme.prompt_cursor.apply(text)),
                         style::Print(" "),
                     )?,
                 }
             }
-            if self.cursor == self.pattern.pieces.len() {
+            if self.cursor == self.pattern.pieces.len() && mask_entry.is_none() {
                 d_queue!(
                     stdout,
-                    style::PrintStyledContent(
-                        style::style("  ")
-                            .with(style::Color::Black)
-                            .on(style::Color::White)
-                    ),
+                    style::PrintStyledContent(theme.prompt_cursor.apply("  ")),
                     style::Print(" "),
                 )?
             }
@@ -223,6 +253,7 @@ impl StatusLinePrompter for modes::search::Search {
                 PatternPiece::Literal(0x20) => 1,
                 PatternPiece::Literal(byte) if byte.is_ascii_graphic() => 1,
                 PatternPiece::Literal(_) => 4,
+                PatternPiece::Masked { .. } => x.to_hex().len() + 2,
             })
             .collect::<Vec<_>>();
         let required_length: usize = lengths[..self.cursor - start_column].iter().sum();
@@ -258,11 +289,7 @@ impl StatusLinePrompter for modes::search::Search {
                 }
                 PatternPiece::Literal(byte) if normalized_cursor != i => d_queue!(
                     stdout,
-                    style::PrintStyledContent(
-                        style::style(format!("<{:02x}>", byte))
-                            .with(style::Color::Black)
-                            .on(style::Color::DarkGrey)
-                    ),
+                    style::PrintStyledContent(theme.prompt_escape.apply(format!("<{:02x}>", byte))),
                 )?,
                 PatternPiece::Literal(byte)
                     if normalized_cursor == i && (byte.is_ascii_graphic() || *byte == 0x20) =>
@@ -270,30 +297,32 @@ impl StatusLinePrompter for modes::search::Search {
                     d_queue!(
                         stdout,
                         style::PrintStyledContent(
-                            style::style(format!("{}", *byte as char))
-                                .with(style::Color::Black)
-                                .on(style::Color::White)
+                            theme.prompt_cursor.apply(format!("{}", *byte as char))
                         ),
                     )?
                 }
                 PatternPiece::Literal(byte) => d_queue!(
                     stdout,
-                    style::PrintStyledContent(
-                        style::style(format!("<{:02x}>", byte))
-                            .with(style::Color::Black)
-                            .on(style::Color::White)
-                    ),
+                    style::PrintStyledContent(theme.prompt_cursor.apply(format!("<{:02x}>", byte))),
                 )?,
                 PatternPiece::Wildcard if normalized_cursor != i => d_queue!(
                     stdout,
-                    style::PrintStyledContent(style::style("*").with(style::Color::DarkRed))
+                    style::PrintStyledContent(theme.prompt_wildcard.apply("*"))
                 )?,
                 PatternPiece::Wildcard => d_queue!(
+                    stdout,
+                    style::PrintStyledContent(theme.prompt_cursor.apply("*")),
+                )?,
+                PatternPiece::Masked { .. } if normalized_cursor != i => d_queue!(
+                    stdout,
+                    style::PrintStyledContent(
+                        theme.prompt_wildcard.apply(format!("<{}>", piece.to_hex()))
+                    )
+                )?,
+                PatternPiece::Masked { .. } => d_queue!(
                     stdout,
                     style::PrintStyledContent(
-                        style::style("*")
-                            .with(style::Color::DarkRed)
-                            .on(style::Color::White)
+                        theme.prompt_cursor.apply(format!("<{}>", piece.to_hex()))
                     ),
                 )?,
             }
@@ -302,11 +331,7 @@ impl StatusLinePrompter for modes::search::Search {
         if self.cursor == self.pattern.pieces.len() {
             d_queue!(
                 stdout,
-                style::PrintStyledContent(
-                    style::style(" ")
-                        .with(style::Color::Black)
-                        .on(style::Color::White)
-                ),
+                style::PrintStyledContent(theme.prompt_cursor.apply(" ")),
             )?;
         }
 
@@ -318,18 +343,12 @@ impl StatusLinePrompter for modes::command::Command {
     fn render_with_size(
         &self,
         stdout: &mut dyn Write,
+        theme: &Theme,
         mut max_width: usize,
         last_start_col: usize,
     ) -> Result<usize> {
         let mut start_column = last_start_col;
-        d_queue!(
-            stdout,
-            style::PrintStyledContent(
-                style::style(":")
-                    .with(style::Color::White)
-                    .on(style::Color::Blue),
-            )
-        )?;
+        d_queue!(stdout, style::PrintStyledContent(theme.info.apply(":")))?;
         max_width -= 1;
 
         // Make sure start_column is between self.cursor and the length of the pattern
@@ -357,11 +376,7 @@ impl StatusLinePrompter for modes::command::Command {
         if self.cursor == self.command.len() {
             d_queue!(
                 stdout,
-                style::PrintStyledContent(
-                    style::style(" ")
-                        .with(style::Color::Black)
-                        .on(style::Color::White)
-                ),
+                style::PrintStyledContent(theme.prompt_cursor.apply(" ")),
             )?;
         }
 
@@ -369,15 +384,65 @@ impl StatusLinePrompter for modes::command::Command {
     }
 }
 
+impl StatusLinePrompter for modes::inspect::InspectInput {
+    fn render_with_size(
+        &self,
+        stdout: &mut dyn Write,
+        theme: &Theme,
+        max_width: usize,
+        _: usize,
+    ) -> Result<usize> {
+        let prompt = format!("{}:", self.label);
+        d_queue!(stdout, style::PrintStyledContent(theme.info.apply(&prompt)))?;
+
+        // Values are short; when one isn't, show its end where the typing happens
+        let room = max_width.saturating_sub(prompt.chars().count() + 1);
+        let skipped = self.text.chars().count().saturating_sub(room);
+        let visible: String = self.text.chars().skip(skipped).collect();
+        d_queue!(
+            stdout,
+            style::Print(visible),
+            style::PrintStyledContent(theme.prompt_cursor.apply(" ")),
+        )?;
+        Ok(0)
+    }
+}
+
+/// The part of the screen a pane's rows are drawn in. Panes right of another
+/// start with a divider column.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+struct Area {
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+    active: bool,
+}
+
 pub struct HexView {
     buffr_collection: BuffrCollection,
     size: (u16, u16),
+    /// The pane being drawn, the active one unless inside `with_pane`.
+    area: Area,
     bytes_per_line: usize,
     start_offset: usize,
+    /// Scroll positions of the inactive panes as file offsets, by pane id, so
+    /// they stay on their bytes while the loaded window moves.
+    pane_offsets: HashMap<usize, usize>,
+    /// The inactive pane being drawn inside `with_pane`.
+    drawn_pane: Option<Pane>,
+    active_pane_id: usize,
+    pane_layout: (usize, SplitDirection),
     last_visible_rows: Cell<usize>,
     last_visible_prompt_col: Cell<usize>,
     last_draw_time: time::Duration,
-    colorizer: OutputColorizer,
+    minimap: Option<Minimap>,
+    minimap_shown: bool,
+    /// Whether the buffer had unsaved changes when the minimap was last synced,
+    /// to scan the file again once they're written.
+    minimap_dirty: bool,
+    /// Where the view was scrolled to when an incremental search started.
+    search_origin: Option<usize>,
 
     mode: Box<dyn Mode>,
     info: Option<String>,
@@ -387,8 +452,7 @@ impl HexView {
     /// Checks if the buffer size exceeds the threshold for trimming
     /// Uses dynamic chunk size based on terminal height
     fn should_trim_buffer(&self) -> bool {
-        let (_, height) = terminal::size().unwrap_or((80, 23));
-        let chunk_size = (height as usize - 1) * 16;
+        let chunk_size = self.chunk_size();
         let buffer_threshold = chunk_size * 3;  // 3x chunk size threshold
         
         let current_size = self.buffr_collection.current().data.len();
@@ -398,6 +462,7 @@ impl HexView {
         current_size > buffer_threshold
     }
     
+    #[allow(dead_code)]
     fn is_near_bottom(&self) -> bool {
         debug_log("is_near_bottom()");
         
@@ -412,12 +477,14 @@ impl HexView {
         total_buffer_bytes - current_view_end < (total_buffer_bytes / 10)
     }
     
+    #[allow(dead_code)]
     fn is_near_top(&self) -> bool {
         // Within first 10% of buffer
         self.start_offset < (self.buffr_collection.current().data.len() / 10)
     }
 
     // Similar for add_chunk_to_bottom:
+    #[allow(dead_code)]
     fn add_chunk_to_bottom(&mut self, chunk_size: usize) -> std::result::Result<(), std::io::Error> {
         debug_log(&format!("Attempting to add chunk to bottom, size={}", chunk_size));
         
@@ -461,6 +528,7 @@ impl HexView {
         Ok(())
     }    
 
+    #[allow(dead_code)]
     fn add_chunk_to_top(&mut self, chunk_size: usize) -> std::result::Result<(), std::io::Error> {
         debug_log(&format!("add_chunk_to_top, size={:?}", chunk_size));
         
@@ -547,6 +615,7 @@ impl HexView {
     /// chunk_size: 3
     /// After Trim:   [A B C D E]         (size 5)
     /// ```
+    #[allow(dead_code)]
     fn trim_buffer_bottom(&mut self, chunk_size: usize) {
         debug_log(&format!("trim_buffer_bottom, size={:?}", chunk_size));
         
@@ -587,106 +656,21 @@ impl HexView {
         }
     }
 
-    /// # Trim Buffer Top Function
-    /// 
-    /// This function trims the top portion of a buffer using a custom Rope implementation.
-    /// It's essential for maintaining a sliding window view of large files in memory.
-    /// 
-    /// ## Technical Details
-    /// - Implements top-down trimming for a custom binary Rope structure
-    /// - Part of a larger pagination/windowing system
-    /// - Uses xi-rope's underlying tree structure
-    /// 
-    /// ## Data Structure Relationships
-    /// ```text
-    /// CustomByteRope
-    ///   └─ Node<RopeInfo>
-    ///       └─ Bytes(Vec<u8>)
-    /// ```
-    /// 
-    /// ## Parameters
-    /// * `chunk_size`: The number of bytes to remove from the top of the buffer
-    /// 
-    /// ## Implementation Notes
-    /// 1. Buffer Management:
-    ///    - Maintains buffer size constraints
-    ///    - Updates start_offset for proper position tracking
-    ///    - Handles boundary conditions
-    /// 
-    /// 2. Data Processing Flow:
-    ///    a. Validates input and buffer size
-    ///    b. Extracts required portion using iter_chunks
-    ///    c. Rebuilds rope structure with TreeBuilder
-    ///    d. Updates buffer state
-    /// 
-    /// 3. Safety Considerations:
-    ///    - Prevents buffer underflow
-    ///    - Maintains minimum size requirements
-    ///    - Handles edge cases for small files
-    /// 
-    /// ## Error Prevention
-    /// - Checks for minimum buffer size (2 * chunk_size)
-    /// - Validates chunk_size > 0
-    /// - Ensures proper rope structure maintenance
-    /// 
-    /// ## Memory Management
-    /// - Creates new rope structure efficiently
-    /// - Properly handles data transfer between old and new ropes
-    /// - Maintains leaf size constraints
-    /// 
-    /// ## Example Operation:
-    /// ```text
-    /// Before: [A B C D E F G H]  (offset 0)
-    /// Trim 3: [D E F G H]        (offset 3)
-    /// ```
-    /// 
-    /// ## Related Components
-    /// - Buffer Interface
-    /// - File Loading System
-    /// - Pagination Controller
-    /// 
-    /// ## Common Debugging Points
-    /// 1. Offset Calculations
-    /// 2. Rope Structure Integrity
-    /// 3. Memory Usage Patterns
-    /// 4. Edge Case Handling
+    /// Drops `chunk_size` bytes from the top of the loaded window, keeping the
+    /// view on the same bytes.
     fn trim_buffer_top(&mut self, chunk_size: usize) {
-        debug_log("\n=== Trim Buffer Top ===");
         debug_log(&format!("trim_buffer_top, size={:?}", chunk_size));
-        
         let current_buffer = self.buffr_collection.current_mut();
-        let total_len = current_buffer.data.len();
-        
-        // Safety checks
-        if chunk_size == 0 || total_len <= chunk_size * 2 {
-            debug_log(&format!("Cannot trim: chunk_size={}, total_len={}", chunk_size, total_len));
+        // Keep at least two chunks around the view
+        if current_buffer.data.len() <= chunk_size * 2 {
             return;
         }
-
-        if chunk_size < total_len {
-            debug_log(&format!("Trimming first {} bytes from buffer of size {}", chunk_size, total_len));
-            
-            // Create new rope using your custom implementation
-            let mut builder = TreeBuilder::new();
-            let chunks: Vec<u8> = current_buffer.data
-                .iter_chunks(chunk_size..total_len)
-                .flat_map(|chunk| chunk.to_vec())
-                .collect();
-                
-            if !chunks.is_empty() {
-                builder.push_leaf(Bytes(chunks));
-            }
-            
-            let old_len = current_buffer.data.len();
-            current_buffer.data = CustomByteRope(builder.build());
-            let new_len = current_buffer.data.len();
-            
-            debug_log(&format!("Buffer trimmed: {} -> {}", old_len, new_len));
-            
+        if current_buffer.trim_front(chunk_size) {
             self.start_offset = self.start_offset.saturating_sub(chunk_size);
         }
     }
 
+    #[allow(dead_code)]
     fn manage_buffer(&mut self) -> std::result::Result<(), std::io::Error> {
 
         let chunk_size = 368;  // Your previous chunk size
@@ -706,24 +690,450 @@ impl HexView {
         Ok(())
     }
         
+    /// Bytes shown by a full screen of rows.
+    fn chunk_size(&self) -> usize {
+        self.area.height as usize * self.bytes_per_line
+    }
+
     pub fn with_buffr_collection(buffr_collection: BuffrCollection) -> HexView {
-        HexView {
+        let mut view = HexView {
             buffr_collection,
-            bytes_per_line: 0x10,
+            bytes_per_line: DEFAULT_BYTES_PER_LINE,
             start_offset: 0,
-            size: terminal::size().unwrap(),
+            size: terminal::size().unwrap_or((80, 24)),
+            area: Area::default(),
+            pane_offsets: HashMap::new(),
+            drawn_pane: None,
+            active_pane_id: 0,
+            pane_layout: (1, SplitDirection::Horizontal),
             last_visible_rows: Cell::new(0),
             last_visible_prompt_col: Cell::new(0),
             last_draw_time: Default::default(),
-            colorizer: OutputColorizer::new(),
+            minimap: None,
+            minimap_shown: false,
+            minimap_dirty: false,
+            search_origin: None,
 
             mode: Box::new(modes::normal::Normal::new()),
             info: None,
-        }
+        };
+        view.area = view.pane_areas()[0];
+        view.update_bytes_per_line();
+        view
     }
 
     pub fn set_bytes_per_line(&mut self, bpl: usize) {
-        self.bytes_per_line = bpl;
+        self.buffr_collection.options_mut().bytes_per_line = BytesPerLine::Fixed(bpl);
+        self.update_bytes_per_line();
+    }
+
+    /// Applies the `bytes-per-line` option, returning whether the row width
+    /// changed. The first visible row is realigned so it still starts a line.
+    fn update_bytes_per_line(&mut self) -> bool {
+        let bytes_per_line = match self.buffr_collection.options().bytes_per_line {
+            BytesPerLine::Fixed(bytes_per_line) => bytes_per_line,
+            BytesPerLine::Auto => self.fit_bytes_per_line(),
+        };
+        if bytes_per_line == self.bytes_per_line {
+            return false;
+        }
+        self.bytes_per_line = bytes_per_line;
+        self.start_offset = self.row_start(self.start_offset);
+        true
+    }
+
+    /// Where the row holding `offset` in the loaded window starts. Rows start at
+    /// multiples of the row width in the file, wherever the window starts.
+    fn row_start(&self, offset: usize) -> usize {
+        let file_offset = self.buffer().data_start_offset + offset;
+        offset.saturating_sub(file_offset % self.bytes_per_line)
+    }
+
+    pub fn set_info(&mut self, info: String) {
+        self.info = Some(info);
+    }
+
+    /// Columns the hex column takes for `bytes` bytes: two digits each, and a
+    /// separator after every group, even a partial one.
+    fn hex_width(&self, bytes: usize) -> usize {
+        let options = self.buffr_collection.options();
+        bytes * 2
+            + (bytes + options.group - 1) / options.group * options.group_separator.chars().count()
+    }
+
+    /// Columns a row takes before the byte inspector: padding, the hex column, a
+    /// separator, the ascii column with its padding and another separator.
+    fn row_width(&self, bytes_per_line: usize) -> usize {
+        1 + self.offset_column_width() + self.hex_width(bytes_per_line) + 2 + bytes_per_line + 1 + 2
+    }
+
+    /// The index of the buffer shown by the pane being drawn.
+    fn buffer_index(&self) -> usize {
+        match self.drawn_pane {
+            Some(pane) => pane.buffer,
+            None => self.buffr_collection.current_index(),
+        }
+    }
+
+    /// The buffer shown by the pane being drawn.
+    fn buffer(&self) -> &CurrentBuffer {
+        self.buffr_collection.get(self.buffer_index()).unwrap()
+    }
+
+    /// The selection of the pane being drawn. Inactive panes keep theirs with
+    /// their buffer.
+    fn selection(&self) -> &Selection {
+        let buffer = self.buffer();
+        self.drawn_pane
+            .and_then(|pane| buffer.pane_selections.get(&pane.id))
+            .unwrap_or(&buffer.selection)
+    }
+
+    /// The mark offsets are shown relative to, when `offset-mark` names a set mark.
+    fn relative_mark(&self) -> Option<(char, usize)> {
+        let name = self.buffr_collection.options().offset_mark?;
+        let offset = self.buffer().marks.get(name)?;
+        Some((name, offset))
+    }
+
+    /// Digits of the offset labels: enough for the end of the buffer, so that the
+    /// layout doesn't shift while scrolling.
+    fn offset_label_width(&self) -> usize {
+        let options = self.buffr_collection.options();
+        let len = self.buffer().data.len();
+        let widest = match self.relative_mark() {
+            Some(_) => options.offset_radix.format_signed(len as isize).len(),
+            None => options
+                .offset_radix
+                .format(options.address(self.file_offset(len)))
+                .len(),
+        };
+        cmp::max(MIN_OFFSET_WIDTH, widest)
+    }
+
+    /// Columns the offset column takes with its separator, if it's shown.
+    fn offset_column_width(&self) -> usize {
+        if self.buffr_collection.options().offset_column {
+            self.offset_label_width() + 3
+        } else {
+            0
+        }
+    }
+
+    /// Where in the file an offset into the loaded window is.
+    fn file_offset(&self, offset: usize) -> usize {
+        self.buffer().data_start_offset + offset
+    }
+
+    /// How the offset column and the status line show a file offset.
+    fn offset_label(&self, offset: usize, width: usize) -> String {
+        let radix = self.buffr_collection.options().offset_radix;
+        match self.relative_mark() {
+            Some((_, mark)) => format!(
+                "{:>width$}",
+                radix.format_signed(offset as isize - mark as isize),
+                width = width
+            ),
+            None => radix.format_padded(self.buffr_collection.options().address(offset), width),
+        }
+    }
+
+    /// The main caret's position for the status line.
+    fn position_label(&self) -> String {
+        let buf = self.buffr_collection.current();
+        let caret = self.file_offset(buf.selection.main_cursor_offset());
+        match self.relative_mark() {
+            Some((name, _)) => format!(" {} from '{} ", self.offset_label(caret, 0), name),
+            None => format!(
+                " {}/{} ",
+                self.offset_label(caret, 0),
+                self.offset_label(buf.total_len() - 1, 0)
+            ),
+        }
+    }
+
+    /// Columns the pane being drawn has for its rows, not counting a divider.
+    fn content_width(&self) -> usize {
+        (self.area.width - cmp::min(self.area.x, 1)) as usize
+    }
+
+    /// Where each pane goes: stacked panes are separated by a divider row that
+    /// names the buffer above it, side by side ones by a divider column.
+    fn pane_areas(&self) -> Vec<Area> {
+        let panes = self.buffr_collection.panes();
+        let count = panes.len() as u16;
+        let rows = self.size.1.saturating_sub(1); // -1 for statusline
+        let width = self.size.0.saturating_sub(self.minimap_width());
+        (0..count)
+            .map(|i| {
+                let last = i + 1 == count;
+                let (x, y, width, height) = match panes.direction {
+                    SplitDirection::Horizontal => {
+                        let step = rows / count;
+                        let y = i * step;
+                        let height = if last {
+                            rows - y
+                        } else {
+                            step.saturating_sub(1)
+                        };
+                        (0, y, width, height)
+                    }
+                    SplitDirection::Vertical => {
+                        let step = width / count;
+                        let x = i * step;
+                        let width = if last { width - x } else { step };
+                        (x, 0, width, rows)
+                    }
+                };
+                Area {
+                    x,
+                    y,
+                    width,
+                    height,
+                    active: i as usize == panes.active_index(),
+                }
+            })
+            .collect()
+    }
+
+    /// Runs `f` with the pane at `index` set up to be drawn as if it were the
+    /// active one: its area, buffer, selection and scroll position.
+    fn with_pane<T>(&mut self, index: usize, f: impl FnOnce(&mut HexView) -> T) -> T {
+        let areas = self.pane_areas();
+        let active = self.buffr_collection.panes().active_index();
+        if index == active {
+            self.area = areas[active];
+            return f(self);
+        }
+
+        let pane = self.buffr_collection.panes().get(index).unwrap();
+        self.drawn_pane = Some(pane);
+        let (window_start, len) = (self.buffer().data_start_offset, self.buffer().data.len());
+        let start_offset = self.pane_offsets.get(&pane.id).map_or(0, |offset| {
+            cmp::min(offset.saturating_sub(window_start), len)
+        });
+        let start_offset = self.row_start(start_offset);
+        let active_offset = std::mem::replace(&mut self.start_offset, start_offset);
+        self.area = areas[index];
+        let result = f(self);
+        self.drawn_pane = None;
+        self.start_offset = active_offset;
+        self.area = areas[active];
+        result
+    }
+
+    /// Catches up with panes split, closed or focused since the last event, and
+    /// returns whether any were. A new pane starts scrolled like its origin.
+    fn sync_panes(&mut self) -> bool {
+        let panes = self.buffr_collection.panes();
+        let active_id = panes.active().id;
+        let layout = (panes.len(), panes.direction);
+        if active_id == self.active_pane_id && layout == self.pane_layout {
+            return false;
+        }
+
+        if active_id != self.active_pane_id {
+            let previous = panes.iter().find(|pane| pane.id == self.active_pane_id);
+            if let Some(buffer) = previous.and_then(|pane| self.buffr_collection.get(pane.buffer)) {
+                let offset = buffer.data_start_offset + self.start_offset;
+                self.pane_offsets.insert(self.active_pane_id, offset);
+            }
+            let window_start = self.buffr_collection.current().data_start_offset;
+            self.start_offset = self
+                .pane_offsets
+                .remove(&active_id)
+                .map_or(self.start_offset, |offset| {
+                    offset.saturating_sub(window_start)
+                });
+            self.active_pane_id = active_id;
+        }
+        let panes = self.buffr_collection.panes();
+        self.pane_offsets
+            .retain(|id, _| panes.iter().any(|pane| pane.id == *id));
+        self.pane_layout = layout;
+        self.area = self.pane_areas()[panes.active_index()];
+        self.update_bytes_per_line();
+        true
+    }
+
+    /// Columns the minimap sidebar takes at the right edge, if it's shown.
+    fn minimap_width(&self) -> u16 {
+        if self.buffr_collection.options().minimap == MinimapStyle::Off {
+            0
+        } else {
+            MINIMAP_WIDTH
+        }
+    }
+
+    /// Starts scanning the current buffer's file for the minimap when it's turned
+    /// on, the buffer is switched or its changes are written. Returns whether the
+    /// minimap was turned on or off, which lays the panes out anew.
+    fn sync_minimap(&mut self, stdout: &mut impl Write) -> Result<bool> {
+        let shown = self.minimap_width() != 0;
+        let buf = self.buffr_collection.current();
+        let path = buf.path.as_ref().filter(|_| shown);
+        let stale = match (&self.minimap, path) {
+            (Some(minimap), Some(path)) => {
+                minimap.path() != path || (self.minimap_dirty && !buf.dirty)
+            }
+            (None, None) => false,
+            _ => true,
+        };
+        self.minimap_dirty = buf.dirty;
+        if stale {
+            self.minimap = path.and_then(|path| Minimap::scan(path).ok());
+        }
+
+        if shown == self.minimap_shown {
+            return Ok(false);
+        }
+        self.minimap_shown = shown;
+        // Clicks on the minimap jump to the spot
+        if shown {
+            execute!(stdout, event::EnableMouseCapture)?;
+        } else {
+            execute!(stdout, event::DisableMouseCapture)?;
+        }
+        self.area = self.pane_areas()[self.buffr_collection.panes().active_index()];
+        Ok(true)
+    }
+
+    /// The minimap cell for `range` of the file, or `None` where it isn't scanned yet.
+    fn minimap_cell(
+        &self,
+        minimap: &Minimap,
+        range: Range<usize>,
+        depth: ColorDepth,
+    ) -> Option<(style::ContentStyle, &'static str)> {
+        let summary = minimap.summary(range)?;
+        let (color, glyph) = match self.buffr_collection.options().minimap {
+            MinimapStyle::Entropy => {
+                let level = (summary.entropy / 8.0 * 255.0) as u8;
+                let shade = cmp::min((summary.entropy / 2.0) as usize, ENTROPY_SHADES.len() - 1);
+                (
+                    downgrade(gradient_color(level), depth),
+                    ENTROPY_SHADES[shade],
+                )
+            }
+            // Without colours, classes are told apart by their glyphs
+            _ if depth == ColorDepth::None => (style::Color::Reset, summary.class.glyph()),
+            _ => (self.theme().class_color(summary.class), "█"),
+        };
+        Some((
+            style::ContentStyle {
+                foreground_color: Some(color),
+                ..self.theme().text
+            },
+            glyph,
+        ))
+    }
+
+    /// Draws the minimap sidebar: each row stands for an equal stretch of the file,
+    /// shaded by entropy or coloured by class, with a marker beside the rows the
+    /// active pane shows. Rows not scanned yet are dotted.
+    fn draw_minimap(&self, stdout: &mut impl Write) -> Result<()> {
+        let width = self.minimap_width();
+        if width == 0
+            || self
+                .mode
+                .as_any()
+                .downcast_ref::<modes::help::Help>()
+                .is_some()
+        {
+            return Ok(());
+        }
+        let x = self.size.0.saturating_sub(width);
+        let rows = self.size.1.saturating_sub(1) as usize;
+        let len = self.minimap.as_ref().map_or(0, |minimap| minimap.len());
+        // The minimap covers the whole file, the view only the loaded window
+        let window_start = self.buffr_collection.current().data_start_offset;
+        let visible = self.visible_bytes();
+        let visible = window_start + visible.start..window_start + visible.end;
+        let theme = self.theme();
+        // Resolving `auto` reads the environment, so it's done once rather than per cell
+        let depth = self.buffr_collection.options().colors.resolve();
+        for row in 0..rows {
+            let start = len * row / rows;
+            let end = cmp::max(len * (row + 1) / rows, start + 1);
+            let (cell_style, glyph) = match &self.minimap {
+                Some(minimap) if start < len => self
+                    .minimap_cell(minimap, start..end, depth)
+                    .unwrap_or((theme.divider, "·")),
+                _ => (theme.text, " "),
+            };
+            let shown = start < len && start < visible.end && visible.start < end;
+            let marker = if shown { MINIMAP_MARKER } else { " " };
+            queue!(
+                stdout,
+                cursor::MoveTo(x, row as u16),
+                style::PrintStyledContent(theme.divider.apply(VERTICAL)),
+                style::PrintStyledContent(cell_style.apply(glyph)),
+                style::PrintStyledContent(theme.active_divider.apply(marker))
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Jumps to the spot clicked on the minimap.
+    fn handle_mouse(&mut self, stdout: &mut impl Write, mouse: MouseEvent) -> Result<()> {
+        let x = self.size.0.saturating_sub(self.minimap_width());
+        let rows = self.size.1.saturating_sub(1);
+        let clicked = mouse.kind == MouseEventKind::Down(MouseButton::Left);
+        if !clicked || mouse.column < x || mouse.row >= rows || !self.mode.is_idle() {
+            return Ok(());
+        }
+        let offset = match &self.minimap {
+            Some(minimap) => minimap.len() * mouse.row as usize / rows as usize,
+            None => return Ok(()),
+        };
+        let transition = match self
+            .buffr_collection
+            .jump_to_offset(offset, self.bytes_per_line)
+        {
+            Ok(dirty) => ModeTransition::DirtyBytes(dirty),
+            Err(e) => {
+                ModeTransition::new_mode_and_info(modes::normal::Normal::new(), e.to_string())
+            }
+        };
+        self.transition(stdout, transition)
+    }
+
+    /// The row width `bytes-per-line=auto` picks for the terminal. Leaves room for
+    /// the inspector unless that would mean fewer than 8 bytes a row.
+    fn fit_bytes_per_line(&self) -> usize {
+        let width = self.content_width();
+        let fit = |reserved: usize| {
+            BytesPerLine::widths()
+                .rev()
+                .find(|&n| self.row_width(n) + reserved <= width)
+        };
+        fit(BytePropertiesFormatter::width())
+            .filter(|&n| n >= 8)
+            .or_else(|| fit(0))
+            .unwrap_or(1)
+    }
+
+    /// Gives every command an explicit start and end style, so that bytes can be
+    /// drawn out of order, or highlighted by a byte rule without losing the style
+    /// around them. The end style is the one in effect after the byte.
+    fn resolve_styles(&self, styled_bytes: &[(u8, StylingCommand)]) -> Vec<StylingCommand> {
+        let mut current = self.default_style();
+        styled_bytes
+            .iter()
+            .map(|(_, cmd)| {
+                let start = cmd.start.clone().unwrap_or_else(|| current.clone());
+                current = cmd
+                    .end
+                    .clone()
+                    .or_else(|| cmd.mid.clone())
+                    .unwrap_or_else(|| start.clone());
+                StylingCommand {
+                    start: Some(start),
+                    mid: cmd.mid.clone(),
+                    end: Some(current.clone()),
+                }
+            })
+            .collect()
     }
 
     fn draw_hex_row(
@@ -731,29 +1141,96 @@ impl HexView {
         stdout: &mut impl Write,
         styled_bytes: impl IntoIterator<Item = (u8, StylingCommand)>,
     ) -> Result<()> {
-        for (byte, style_cmd) in styled_bytes.into_iter() {
-            self.colorizer.draw_hex_byte(stdout, byte, &style_cmd)?;
+        let options = self.buffr_collection.options();
+        let styled_bytes: Vec<_> = styled_bytes.into_iter().collect();
+        let resolved = self.resolve_styles(&styled_bytes);
+        if options.endian == Endian::Big || options.group == 1 {
+            for (i, ((byte, _), style_cmd)) in styled_bytes.iter().zip(&resolved).enumerate() {
+                self.colorizer().draw_hex_digits(stdout, *byte, style_cmd)?;
+                if (i + 1) % options.group == 0 || i + 1 == styled_bytes.len() {
+                    queue!(stdout, style::Print(&options.group_separator))?;
+                }
+            }
+            return Ok(());
+        }
+
+        // Little endian groups are drawn last byte first, while the caret and
+        // selections stay on the bytes they belong to
+        for (group, styles) in styled_bytes
+            .chunks(options.group)
+            .zip(resolved.chunks(options.group))
+        {
+            for ((byte, _), style_cmd) in group.iter().zip(styles).rev() {
+                self.colorizer().draw_hex_digits(stdout, *byte, style_cmd)?;
+            }
+            let after_group = StylingCommand {
+                start: styles.last().and_then(|cmd| cmd.end.clone()),
+                ..Default::default()
+            };
+            self.colorizer()
+                .draw(stdout, &options.group_separator, &after_group)?;
         }
         Ok(())
     }
 
+    /// Decodes the character column cells for `range`, along with enough bytes
+    /// around it to tell where its characters start.
+    fn char_cells(&self, range: Range<usize>) -> Vec<CharCell> {
+        if range.is_empty() {
+            return vec![];
+        }
+        let data = &self.buffer().data;
+        let encoding = self.buffr_collection.options().encoding;
+        let context_start = range.start.saturating_sub(encoding.context_len());
+        let context_end = cmp::min(data.len(), range.end + encoding.context_len());
+        let mut cells = encoding.decode(
+            &data.slice_to_cow(context_start..context_end),
+            context_start,
+        );
+        cells.drain(..range.start - context_start);
+        cells.truncate(range.len());
+        cells
+    }
+
     fn draw_ascii_row(
         &self,
         stdout: &mut impl Write,
         styled_bytes: impl IntoIterator<Item = (u8, StylingCommand)>,
+        cells: &[CharCell],
     ) -> Result<()> {
-        for (byte, style_cmd) in styled_bytes.into_iter() {
-            self.colorizer.draw_ascii_byte(stdout, byte, &style_cmd)?;
+        let styled_bytes: Vec<_> = styled_bytes.into_iter().collect();
+        let resolved = self.resolve_styles(&styled_bytes);
+        let mut covered = false;
+        for (i, ((byte, _), style_cmd)) in styled_bytes.into_iter().zip(resolved).enumerate() {
+            let glyph = match cells[i] {
+                _ if covered => {
+                    covered = false;
+                    String::new()
+                }
+                // A wide character takes the cell of its first continuation byte too
+                CharCell::Char(c) if is_wide(c) => {
+                    covered = cells.get(i + 1) == Some(&CharCell::Continuation);
+                    if covered {
+                        c.to_string()
+                    } else {
+                        CharCell::Invalid.glyph().to_string()
+                    }
+                }
+                cell => cell.glyph().to_string(),
+            };
+            self.colorizer()
+                .draw_char_cell(stdout, byte, &glyph, &style_cmd)?;
         }
         Ok(())
     }
 
     fn draw_separator(&self, stdout: &mut impl Write) -> Result<()> {
-        queue!(stdout, style::SetForegroundColor(Color::White))?;
+        queue_style(stdout, &self.theme().text)?;
         queue!(stdout, style::Print(format!("{} ", VERTICAL)))
     }
     
     /// Safely calculates if an offset is within valid bounds
+    #[allow(dead_code)]
     fn is_valid_offset(&self, offset: usize) -> bool {
         let buffer_size = self.buffr_collection.current().data.len();
         offset < buffer_size
@@ -775,7 +1252,7 @@ impl HexView {
     /// * `Err` - If calculated row would be outside visible screen area
     /// 
     /// # Example
-    /// ```
+    /// ```text
     /// // If start_offset = 32 (viewing starts at 3rd line of file)
     /// // bytes_per_line = 16
     /// // screen height = 24
@@ -787,29 +1264,35 @@ impl HexView {
     /// 
     /// # Technical Notes
     /// - Screen coordinates are 0-based
-    /// - Assumes fixed-width display (16 bytes per line)
+    /// - Assumes every row holds bytes_per_line bytes
     /// - Must account for start_offset (current scroll position)
-    /// - Must fit within terminal height (self.size.1)
+    /// - Must fit within the pane height (self.area.height)
     /// 
     /// # Error Conditions
     /// - Returns Err if calculated row >= screen height
     /// - Can handle offsets smaller than start_offset (negative rows filtered)
-    /// 
+    ///
     fn offset_to_row(&self, offset: usize) -> Result<u16> {
-        debug_log(&format!("offset_to_row: offset={}, start_offset={}", 
-            offset, self.start_offset));
-    
+        debug_log(&format!(
+            "offset_to_row: offset={}, start_offset={}",
+            offset, self.start_offset
+        ));
+
         // Check for underflow condition
         if offset < self.start_offset {
-            debug_log(&format!("offset_to_row: offset {} is before start_offset {}", 
-                offset, self.start_offset));
+            debug_log(&format!(
+                "offset_to_row: offset {} is before start_offset {}",
+                offset, self.start_offset
+            ));
             return Err(Error::new(ErrorKind::Other, "Offset before visible area"));
         }
-    
+
         let row = (offset - self.start_offset) / self.bytes_per_line;
-        if row >= self.size.1 as usize {
-            debug_log(&format!("offset_to_row: row {} exceeds screen height {}", 
-                row, self.size.1));
+        if row >= self.area.height as usize {
+            debug_log(&format!(
+                "offset_to_row: row {} exceeds pane height {}",
+                row, self.area.height
+            ));
             return Err(Error::new(ErrorKind::Other, "Row outside visible area"));
         }
         Ok(row as u16)
@@ -838,28 +1321,45 @@ impl HexView {
             }
         };
 
-        queue!(stdout, cursor::MoveTo(0, row_num))?;
+        queue!(stdout, cursor::MoveTo(self.area.x, self.area.y + row_num))?;
+        if self.area.x > 0 {
+            queue!(
+                stdout,
+                style::PrintStyledContent(self.theme().divider.apply(VERTICAL))
+            )?;
+        }
         queue!(
             stdout,
             style::Print(" ".to_string()), // Padding
         )?;
+        if self.buffr_collection.options().offset_column {
+            let width = self.offset_label_width();
+            // Rows past the end only carry the inspector
+            let label = if offset <= self.buffer().data.len() {
+                self.offset_label(self.file_offset(offset), width)
+            } else {
+                make_padding(width).into_owned()
+            };
+            queue!(
+                stdout,
+                style::PrintStyledContent(self.theme().offset.apply(label)),
+                style::Print(" "),
+            )?;
+            self.draw_separator(stdout)?;
+        }
         self.draw_hex_row(
             stdout,
             bytes.iter().copied().zip(mark_commands.iter().cloned()),
         )?;
 
-        let mut padding_length = if bytes.is_empty() {
-            self.bytes_per_line * 3
-        } else {
-            (self.bytes_per_line - bytes.len()) % self.bytes_per_line * 3
-        };
+        let mut padding_length = self.hex_width(self.bytes_per_line) - self.hex_width(bytes.len());
 
         if let Some(style_cmd) = &end_style {
-            padding_length -= 2;
+            padding_length = padding_length.saturating_sub(2);
 
-            self.colorizer
+            self.colorizer()
                 .draw(stdout, ' ', &style_cmd.clone().with_mid_to_end())?;
-            self.colorizer
+            self.colorizer()
                 .draw(stdout, ' ', &style_cmd.clone().take_end_only())?;
         }
 
@@ -869,6 +1369,7 @@ impl HexView {
         self.draw_ascii_row(
             stdout,
             bytes.iter().copied().zip(mark_commands.iter().cloned()),
+            &self.char_cells(offset..offset + bytes.len()),
         )?;
 
         let mut padding_length = if bytes.is_empty() {
@@ -879,14 +1380,14 @@ impl HexView {
 
         if let Some(style_cmd) = end_style {
             padding_length -= 1;
-            self.colorizer
+            self.colorizer()
                 .draw(stdout, ' ', &style_cmd.take_end_only())?;
         }
 
         queue!(stdout, style::Print(make_padding(padding_length)))?;
         self.draw_separator(stdout)?;
 
-        byte_properties.draw_line(stdout, &self.colorizer)?;
+        byte_properties.draw_line(stdout, &self.colorizer())?;
 
         queue!(stdout, terminal::Clear(terminal::ClearType::UntilNewLine))?;
 
@@ -896,67 +1397,76 @@ impl HexView {
     fn visible_bytes(&self) -> Range<usize> {
         self.start_offset
             ..cmp::min(
-                self.buffr_collection.current().data.len() + 1,
-                self.start_offset + (self.size.1 - 1) as usize * self.bytes_per_line,
+                self.buffer().data.len() + 1,
+                self.start_offset + self.chunk_size(),
             )
     }
 
+    fn theme(&self) -> &Theme {
+        &self.buffr_collection.options().theme
+    }
+
+    fn colorizer(&self) -> OutputColorizer<'_> {
+        OutputColorizer::new(&self.buffr_collection.options().byte_styles)
+    }
+
     fn default_style(&self) -> PrioritizedStyle {
         PrioritizedStyle {
-            style: style::ContentStyle::new()
-                .with(style::Color::White)
-                .on(style::Color::Reset),
+            style: self.theme().text,
             priority: Priority::Basic,
         }
     }
 
+    fn mark_style(&self) -> PrioritizedStyle {
+        PrioritizedStyle {
+            style: self.theme().mark,
+            priority: Priority::Mark,
+        }
+    }
+
+    fn search_match_style(&self) -> PrioritizedStyle {
+        PrioritizedStyle {
+            style: self.theme().search_match,
+            priority: Priority::Mark,
+        }
+    }
+
+    /// The prompt of an incremental search, if one is being typed.
+    fn search(&self) -> Option<&modes::search::Search> {
+        self.mode.as_any().downcast_ref::<modes::search::Search>()
+    }
+
     fn active_selection_style(&self) -> PrioritizedStyle {
         PrioritizedStyle {
-            style: style::ContentStyle::new()
-                .with(style::Color::Black)
-                .on(style::Color::Rgb {
-                    r: 110,
-                    g: 97,
-                    b: 16,
-                }),
+            style: self.theme().main_selection,
             priority: Priority::Selection,
         }
     }
 
     fn inactive_selection_style(&self) -> PrioritizedStyle {
         PrioritizedStyle {
-            style: style::ContentStyle::new()
-                .with(style::Color::Black)
-                .on(style::Color::DarkGrey),
+            style: self.theme().selection,
             priority: Priority::Selection,
         }
     }
 
     fn active_caret_style(&self) -> PrioritizedStyle {
         PrioritizedStyle {
-            style: style::ContentStyle::new()
-                .with(style::Color::AnsiValue(16))
-                .on(style::Color::Rgb {
-                    r: 107,
-                    g: 108,
-                    b: 128,
-                }),
+            style: self.theme().main_caret,
             priority: Priority::Cursor,
         }
     }
 
     fn inactive_caret_style(&self) -> PrioritizedStyle {
         PrioritizedStyle {
-            style: style::ContentStyle::new()
-                .with(style::Color::Black)
-                .on(style::Color::DarkGrey),
+            style: self.theme().caret,
             priority: Priority::Cursor,
         }
     }
 
     fn empty_caret_style(&self) -> PrioritizedStyle {
         PrioritizedStyle {
-            style: style::ContentStyle::new().on(style::Color::Green),
+            style: self.theme().empty_caret,
             priority: Priority::Cursor,
         }
     }
@@ -964,16 +1474,23 @@ impl HexView {
     fn mark_commands(&self, visible: Range<usize>) -> Vec<StylingCommand> {
         let mut mark_commands = vec![StylingCommand::default(); visible.len()];
         let mut selected_regions = self
-            .buffr_collection
-            .current()
-            .selection
+            .selection()
             .regions_in_range(visible.start, visible.end);
         let mut command_stack = vec![self.default_style()];
         let start = visible.start;
+        let marks = &self.buffer().marks;
+        // Matches are searched for in the active pane's buffer
+        let mut matches = match self.search() {
+            Some(search) if self.area.active => {
+                let first = search.matches.partition_point(|m| m.end <= start);
+                &search.matches[first..]
+            }
+            _ => &[],
+        };
 
         // Add to command stack those commands that being out of bounds
         if !selected_regions.is_empty() && selected_regions[0].min() < start {
-            command_stack.push(if selected_regions[0].is_main() {
+            command_stack.push(if selected_regions[0].is_main() && self.area.active {
                 self.active_selection_style()
             } else {
                 self.inactive_selection_style()
@@ -984,7 +1501,7 @@ impl HexView {
             let normalized = i - start;
             if !selected_regions.is_empty() {
                 if selected_regions[0].min() == i {
-                    command_stack.push(if selected_regions[0].is_main() {
+                    command_stack.push(if selected_regions[0].is_main() && self.area.active {
                         self.active_selection_style()
                     } else {
                         self.inactive_selection_style()
@@ -996,7 +1513,7 @@ impl HexView {
                 if selected_regions[0].caret == i {
                     let base_style = command_stack.last().unwrap().clone();
                     let mut caret_cmd = mark_commands[normalized].clone();
-                    let caret_style = if selected_regions[0].is_main() {
+                    let caret_style = if selected_regions[0].is_main() && self.area.active {
                         self.active_caret_style()
                     } else {
                         self.inactive_caret_style()
@@ -1025,6 +1542,25 @@ impl HexView {
                 }
             }
 
+            // Selections take priority over marks, so only mark bytes outside them
+            if command_stack.len() == 1 && marks.contains_offset(self.file_offset(i)) {
+                mark_commands[normalized] = mark_commands[normalized]
+                    .clone()
+                    .with_start_style(self.mark_style())
+                    .with_end_style(self.default_style());
+            }
+
+            // Search matches show over marks, but not over selections either
+            while matches.first().is_some_and(|m| m.end <= i) {
+                matches = &matches[1..];
+            }
+            if command_stack.len() == 1 && matches.first().is_some_and(|m| m.start <= i) {
+                mark_commands[normalized] = mark_commands[normalized]
+                    .clone()
+                    .with_start_style(self.search_match_style())
+                    .with_end_style(self.default_style());
+            }
+
             if i % self.bytes_per_line == 0 && mark_commands[normalized].start_style().is_none() {
                 // line starts: restore applied style
                 mark_commands[normalized] = mark_commands[normalized]
@@ -1047,122 +1583,102 @@ impl HexView {
         mark_commands
     }
 
-    fn calculate_powerline_length(&self) -> usize {
-        let buf = self.buffr_collection.current();
-        let mut length = 0;
-        length += 1; // leftarrow
-        length += 2 + buf.name().len();
-        if buf.dirty {
-            length += 3;
-        }
-        length += 1; // leftarrow
-        length += 2 + self.mode.name().len();
-        length += 1; // leftarrow
-        length += format!(
-            " {} sels ({}) ",
-            buf.selection.len(),
-            buf.selection.main_selection + 1
+    fn buffer_label(&self) -> String {
+        let buf = self.buffer();
+        format!(
+            " [{}/{}] {}{} ",
+            self.buffer_index() + 1,
+            self.buffr_collection.len(),
+            buf.name(),
+            if buf.dirty { "[+]" } else { "" }
         )
-        .len();
-        length += 1; // leftarrow
-        if !buf.data.is_empty() {
-            length += format!(
-                " {:x}/{:x} ",
-                buf.selection.main_cursor_offset(),
-                buf.data.len() - 1
-            )
-            .len();
-        } else {
-            length += " empty ".len();
+    }
+
+    fn mode_label(&self) -> String {
+        // Only the loaded window is searched
+        let buf = self.buffr_collection.current();
+        let partial = buf.data_start_offset > 0 || buf.total_len() > buf.data.len();
+        let scope = if partial { " loaded" } else { "" };
+        let matches = match self.search() {
+            Some(search) if search.pattern.pieces.is_empty() => String::new(),
+            Some(modes::search::Search {
+                error: Some(err), ..
+            }) => format!(" {}", err),
+            Some(search) => match search.current_match {
+                Some(k) => format!(" match {} of {}{}", k + 1, search.matches.len(), scope),
+                None => format!(" no{} matches", scope),
+            },
+            None => String::new(),
+        };
+        match self.buffr_collection.macros().recording_register() {
+            Some(register) => format!(" {}{} [rec {}] ", self.mode.name(), matches, register),
+            None => format!(" {}{} ", self.mode.name(), matches),
         }
-        length
     }
 
-    fn draw_statusline_here(&self, stdout: &mut impl Write) -> Result<()> {
+    /// The status line's segments, each with its style.
+    fn statusline_segments(&self) -> [(style::ContentStyle, String); 4] {
         let buf = self.buffr_collection.current();
-        queue!(
-            stdout,
-            style::PrintStyledContent(style::style(LEFTARROW).with(Color::Red)),
-            style::PrintStyledContent(
-                style::style(format!(
-                    " {}{} ",
-                    self.buffr_collection.current().name(),
-                    if self.buffr_collection.current().dirty {
-                        "[+]"
-                    } else {
-                        ""
-                    }
-                ))
-                .with(Color::White)
-                .on(Color::Red)
-            ),
-            style::PrintStyledContent(
-                style::style(LEFTARROW)
-                    .with(Color::DarkYellow)
-                    .on(Color::Red)
-            ),
-            style::PrintStyledContent(
-                style::style(format!(" {} ", self.mode.name()))
-                    .with(Color::AnsiValue(16))
-                    .on(Color::DarkYellow)
-            ),
-            style::PrintStyledContent(
-                style::style(LEFTARROW)
-                    .with(Color::White)
-                    .on(Color::DarkYellow)
-            ),
-            style::PrintStyledContent(
-                style::style(format!(
+        let theme = self.theme();
+        let position = if buf.data.is_empty() {
+            " empty ".to_owned()
+        } else {
+            self.position_label()
+        };
+        [
+            (theme.status_buffer, self.buffer_label()),
+            (theme.status_mode, self.mode_label()),
+            (
+                theme.status_selections,
+                format!(
                     " {} sels ({}) ",
                     buf.selection.len(),
                     buf.selection.main_selection + 1
-                ))
-                .with(Color::AnsiValue(16))
-                .on(Color::White)
-            ),
-        )?;
-        if !buf.data.is_empty() {
-            queue!(
-                stdout,
-                style::PrintStyledContent(
-                    style::style(LEFTARROW).with(Color::Blue).on(Color::White)
-                ),
-                style::PrintStyledContent(
-                    style::style(format!(
-                        " {:x}/{:x} ",
-                        buf.selection.main_cursor_offset(),
-                        buf.data.len() - 1,
-                    ))
-                    .with(Color::White)
-                    .on(Color::Blue),
                 ),
-            )?;
-        } else {
+            ),
+            (theme.status_position, position),
+        ]
+    }
+
+    fn calculate_powerline_length(&self) -> usize {
+        self.statusline_segments()
+            .iter()
+            .map(|(_, text)| 1 + text.chars().count()) // 1 for the separator
+            .sum()
+    }
+
+    fn draw_statusline_here(&self, stdout: &mut impl Write) -> Result<()> {
+        let mut previous: Option<style::ContentStyle> = None;
+        for (segment_style, text) in self.statusline_segments() {
+            let separator = match self.buffr_collection.options().statusline {
+                // The arrow points from the previous segment into this one
+                StatuslineStyle::Powerline => style::ContentStyle {
+                    foreground_color: segment_style.background_color,
+                    background_color: previous.and_then(|style| style.background_color),
+                    attributes: Default::default(),
+                }
+                .apply(LEFTARROW),
+                StatuslineStyle::Ascii => segment_style.apply("|"),
+            };
             queue!(
                 stdout,
-                style::PrintStyledContent(
-                    style::style(LEFTARROW).with(Color::Blue).on(Color::White)
-                ),
-                style::PrintStyledContent(
-                    style::style(" empty ").with(Color::White).on(Color::Blue),
-                ),
+                style::PrintStyledContent(separator),
+                style::PrintStyledContent(segment_style.apply(text)),
             )?;
+            previous = Some(segment_style);
         }
         Ok(())
     }
 
     fn draw_statusline(&self, stdout: &mut impl Write) -> Result<()> {
         let line_length = self.calculate_powerline_length();
-        if let Some(info) = &self.info {
+        if let Some(info) = self.info.clone().or_else(|| self.mode.hints()) {
+            let info: String = info.chars().take(self.size.0 as usize).collect();
             queue!(
                 stdout,
                 cursor::MoveTo(0, self.size.1 - 1),
                 terminal::Clear(terminal::ClearType::CurrentLine),
-                style::PrintStyledContent(
-                    style::style(info)
-                        .with(style::Color::White)
-                        .on(style::Color::Blue)
-                ),
+                style::PrintStyledContent(self.theme().info.apply(info)),
                 cursor::MoveTo(self.size.0 - line_length as u16, self.size.1),
             )?;
         } else {
@@ -1178,6 +1694,8 @@ impl HexView {
         let any_mode = self.mode.as_any();
         let prompter = if let Some(statusliner) = any_mode.downcast_ref::<modes::search::Search>() {
             Some(statusliner as &dyn StatusLinePrompter)
+        } else if let Some(statusliner) = any_mode.downcast_ref::<modes::inspect::InspectInput>() {
+            Some(statusliner as &dyn StatusLinePrompter)
         } else {
             any_mode
                 .downcast_ref::<modes::command::Command>()
@@ -1187,7 +1705,12 @@ impl HexView {
         if let Some(statusliner) = prompter {
             queue!(stdout, cursor::MoveTo(0, self.size.1))?;
             let prev_col = self.last_visible_prompt_col.get();
-            let new_col = statusliner.render_with_size(stdout, self.size.0 as usize, prev_col)?;
+            let new_col = statusliner.render_with_size(
+                stdout,
+                self.theme(),
+                self.size.0 as usize,
+                prev_col,
+            )?;
             self.last_visible_prompt_col.set(new_col);
         }
 
@@ -1195,22 +1718,24 @@ impl HexView {
     }
 
     fn overflow_cursor_style(&self) -> Option<StylingCommand> {
-        self.buffr_collection.current().overflow_sel_style().map(|style| {
-            match style {
-                OverflowSelectionStyle::CursorTail | OverflowSelectionStyle::Cursor
-                    if self.mode.has_half_cursor() =>
-                {
-                    StylingCommand::default().with_mid_style(self.empty_caret_style())
-                }
-                OverflowSelectionStyle::CursorTail | OverflowSelectionStyle::Cursor => {
-                    StylingCommand::default().with_start_style(self.empty_caret_style())
+        self.buffer()
+            .overflow_sel_style(self.selection())
+            .map(|style| {
+                match style {
+                    OverflowSelectionStyle::CursorTail | OverflowSelectionStyle::Cursor
+                        if self.mode.has_half_cursor() =>
+                    {
+                        StylingCommand::default().with_mid_style(self.empty_caret_style())
+                    }
+                    OverflowSelectionStyle::CursorTail | OverflowSelectionStyle::Cursor => {
+                        StylingCommand::default().with_start_style(self.empty_caret_style())
+                    }
+                    OverflowSelectionStyle::Tail => StylingCommand::default(),
                 }
-                OverflowSelectionStyle::Tail => StylingCommand::default(),
-            }
-            .with_end_style(self.default_style())
-        })
+                .with_end_style(self.default_style())
+            })
     }
-    
+
     fn ensure_visible_data(&mut self) -> Result<()> {
         debug_log("Checking visible data availability");
         let visible = self.visible_bytes();
@@ -1220,8 +1745,9 @@ impl HexView {
         
         if visible.end > current_size {
             debug_log("Need to load more data");
+            let chunk_size = self.chunk_size();
             let current_buffer = self.buffr_collection.current_mut();
-            match current_buffer.load_next_chunk(64) {
+            match current_buffer.load_next_chunk(chunk_size) {
                 Ok(true) => {
                     debug_log(&format!("Loaded chunk. New size: {}", current_buffer.data.len()));
                     Ok(())
@@ -1241,43 +1767,59 @@ impl HexView {
     }
     
 
+    /// The bytes the inspector decodes, from the main caret on.
+    fn inspected_bytes(&self) -> Cow<'_, [u8]> {
+        let current_buffer = self.buffer();
+        let caret = self.selection().main_cursor_offset();
+        let end = cmp::min(current_buffer.data.len(), caret + MAX_INSPECTED_BYTES);
+        if caret >= end {
+            return Cow::Borrowed(&[]);
+        }
+        current_buffer.data.slice_to_cow(caret..end)
+    }
+
+    /// The inspector row being edited, if any.
+    fn inspected_row(&self) -> Option<usize> {
+        if !self.area.active {
+            return None;
+        }
+        let any_mode = self.mode.as_any();
+        if let Some(inspect) = any_mode.downcast_ref::<modes::inspect::Inspect>() {
+            Some(inspect.row)
+        } else {
+            any_mode
+                .downcast_ref::<modes::inspect::InspectInput>()
+                .map(|input| input.row)
+        }
+    }
+
+    /// Rows at the top of the screen the inspector draws on.
+    fn inspector_height(&self) -> usize {
+        BytePropertiesFormatter::height(self.buffr_collection.options())
+    }
+
     fn draw_rows(&mut self, stdout: &mut impl Write, invalidated_rows: &BTreeSet<u16>) -> Result<()> {
         
-        // Try to load more data if needed
-        self.ensure_visible_data()?;
+        // Try to load more data if needed. Inactive panes show what's loaded.
+        if self.drawn_pane.is_none() {
+            self.ensure_visible_data()?;
+        }
         
         let visible_bytes = self.visible_bytes();
         let start_index = visible_bytes.start;
         let end_index = visible_bytes.end;
 
-        let visible_bytes_cow = self
-            .buffr_collection
-            .current()
-            .data
-            .slice_to_cow(start_index..end_index);
+        let visible_bytes_cow = self.buffer().data.slice_to_cow(start_index..end_index);
 
         let max_bytes = visible_bytes_cow.len();
         let mark_commands = self.mark_commands(visible_bytes.clone());
 
-        let current_bytes = self
-            .buffr_collection
-            .current()
-            .selection
-            .regions_in_range(visible_bytes.start, visible_bytes.end)
-            .iter()
-            .find(|region| region.is_main())
-            .map(|v| {
-                let start = v.caret - start_index;
-                let end = if start + 4 > visible_bytes_cow.len() {
-                    visible_bytes_cow.len()
-                } else {
-                    start + 4
-                };
-                &visible_bytes_cow[start..end]
-            })
-            .unwrap_or_else(|| &[]);
-
-        let mut byte_properties = BytePropertiesFormatter::new(current_bytes);
+        let inspected_bytes = self.inspected_bytes();
+        let mut byte_properties = BytePropertiesFormatter::new(
+            &inspected_bytes,
+            self.buffr_collection.options(),
+            self.inspected_row(),
+        );
 
         for i in visible_bytes.step_by(self.bytes_per_line) {
             if !invalidated_rows.contains(&self.offset_to_row(i).unwrap()) {
@@ -1291,7 +1833,7 @@ impl HexView {
                 &visible_bytes_cow[normalized_i..normalized_end],
                 i,
                 &mark_commands[normalized_i..normalized_end],
-                if i + self.bytes_per_line > self.buffr_collection.current().data.len() {
+                if i + self.bytes_per_line > self.buffer().data.len() {
                     self.overflow_cursor_style()
                 } else {
                     None
@@ -1306,7 +1848,7 @@ impl HexView {
         } else {
             a + 1
         }) * self.bytes_per_line;
-        while !byte_properties.are_all_printed() {
+        while !byte_properties.are_all_printed() && offset < self.start_offset + self.chunk_size() {
             self.draw_row(stdout, &[], offset, &[], None, &mut byte_properties)?;
             offset += self.bytes_per_line;
         }
@@ -1314,103 +1856,121 @@ impl HexView {
         Ok(())
     }
 
+    fn draw_help(&self, stdout: &mut impl Write, scroll: usize) -> Result<()> {
+        let rows = (self.size.1 - 1) as usize; // -1 for statusline
+        let width = self.size.0 as usize;
+        for (row, line) in modes::help::Help::lines()
+            .iter()
+            .skip(scroll)
+            .take(rows)
+            .enumerate()
+        {
+            let shown: String = line.chars().take(width).collect();
+            queue!(
+                stdout,
+                cursor::MoveTo(0, row as u16),
+                style::PrintStyledContent(self.theme().help.apply(shown))
+            )?;
+        }
+        Ok(())
+    }
+
     fn draw(&mut self, stdout: &mut impl Write) -> Result<time::Duration> {
         let begin = time::Instant::now();
 
-        // Try to load more data if needed
-        self.ensure_visible_data()?;
-        
         queue!(
             stdout,
             cursor::MoveTo(0, 0),
             terminal::Clear(terminal::ClearType::All)
         )?;
 
-        let visible_bytes = self.visible_bytes();
-        let start_index = visible_bytes.start;
-        let end_index = visible_bytes.end;
-        let visible_bytes_cow = self
-            .buffr_collection
-            .current()
-            .data
-            .slice_to_cow(start_index..end_index);
-
-        let max_bytes = visible_bytes_cow.len();
-        let mark_commands = self.mark_commands(visible_bytes.clone());
+        if let Some(help) = self.mode.as_any().downcast_ref::<modes::help::Help>() {
+            self.draw_help(stdout, help.scroll)?;
+            self.draw_statusline(stdout)?;
+            return Ok(begin.elapsed());
+        }
 
-        let current_bytes = self
-            .buffr_collection
-            .current()
-            .selection
-            .regions_in_range(visible_bytes.start, visible_bytes.end)
-            .iter()
-            .find(|region| region.is_main())
-            .map(|v| {
-                let start = v.caret - start_index;
-                let end = if start + 4 > visible_bytes_cow.len() {
-                    visible_bytes_cow.len()
-                } else {
-                    start + 4
-                };
-                &visible_bytes_cow[start..end]
-            })
-            .unwrap_or_else(|| &[]);
+        for index in 0..self.buffr_collection.panes().len() {
+            self.with_pane(index, |view| view.draw_pane(stdout))?;
+        }
+        self.draw_pane_dividers(stdout)?;
+        self.draw_minimap(stdout)?;
+        self.draw_statusline(stdout)?;
 
-        let mut byte_properties = BytePropertiesFormatter::new(current_bytes);
+        Ok(begin.elapsed())
+    }
 
-        for i in visible_bytes.step_by(self.bytes_per_line) {
-            let normalized_i = i - start_index;
-            let normalized_end = std::cmp::min(max_bytes, normalized_i + self.bytes_per_line);
-            self.draw_row(
-                stdout,
-                &visible_bytes_cow[normalized_i..normalized_end],
-                i,
-                &mark_commands[normalized_i..normalized_end],
-                if i + self.bytes_per_line > self.buffr_collection.current().data.len() {
-                    self.overflow_cursor_style()
-                } else {
-                    None
-                },
-                &mut byte_properties,
-            )?;
-        }
+    fn draw_pane(&mut self, stdout: &mut impl Write) -> Result<()> {
+        // Edits from other panes may have left this one past the end
+        let len = self.buffer().data.len();
+        self.start_offset = self.row_start(cmp::min(self.start_offset, len));
 
-        let a = end_index / self.bytes_per_line;
-        let mut offset = (if end_index % self.bytes_per_line == 0 {
-            a
-        } else {
-            a + 1
-        }) * self.bytes_per_line;
-        while !byte_properties.are_all_printed() {
-            self.draw_row(stdout, &[], offset, &[], None, &mut byte_properties)?;
-            offset += self.bytes_per_line;
-        }
+        let all_rows: BTreeSet<u16> = (0..self.area.height).collect();
+        self.draw_rows(stdout, &all_rows)?;
 
-        let new_full_rows =
-            (end_index - start_index + self.bytes_per_line - 1) / self.bytes_per_line;
+        let visible_bytes = self.visible_bytes();
+        let new_full_rows = (visible_bytes.len() + self.bytes_per_line - 1) / self.bytes_per_line;
         if new_full_rows != self.last_visible_rows.get() {
             self.last_visible_rows.set(new_full_rows);
         }
+        Ok(())
+    }
 
-        self.draw_statusline(stdout)?;
+    /// Draws the rows between stacked panes, each naming the buffer of the pane
+    /// above it, highlighted for the active pane.
+    fn draw_pane_dividers(&mut self, stdout: &mut impl Write) -> Result<()> {
+        if self.buffr_collection.panes().direction != SplitDirection::Horizontal {
+            return Ok(());
+        }
+        let areas = self.pane_areas();
+        for (index, area) in areas.iter().enumerate().take(areas.len() - 1) {
+            let label = self.with_pane(index, |view| view.buffer_label());
+            let line: String = format!("{}{} ", HORIZONTAL, label)
+                .chars()
+                .chain(std::iter::repeat(HORIZONTAL.chars().next().unwrap()))
+                .take(area.width as usize)
+                .collect();
+            let divider_style = if area.active {
+                self.theme().active_divider
+            } else {
+                self.theme().divider
+            };
+            queue!(
+                stdout,
+                cursor::MoveTo(area.x, area.y + area.height),
+                style::PrintStyledContent(divider_style.apply(line))
+            )?;
+        }
+        Ok(())
+    }
 
-        Ok(begin.elapsed())
+    /// Redraws whole screen lines across every pane, left to right, as each row
+    /// clears the rest of its line.
+    fn draw_lines(&mut self, stdout: &mut impl Write, lines: &BTreeSet<u16>) -> Result<()> {
+        for index in 0..self.buffr_collection.panes().len() {
+            self.with_pane(index, |view| {
+                let rows: BTreeSet<u16> = lines
+                    .iter()
+                    .filter_map(|line| line.checked_sub(view.area.y))
+                    .filter(|&row| row < view.area.height)
+                    .collect();
+                view.draw_rows(stdout, &rows)
+            })?;
+        }
+        Ok(())
     }
 
     fn handle_event_default(&mut self, stdout: &mut impl Write, event: Event) -> Result<()> {
         match event {
             Event::Resize(x, y) => {
                 self.size = (x, y);
-                self.draw(stdout)?;
-                Ok(())
+                self.area = self.pane_areas()[self.buffr_collection.panes().active_index()];
+                self.update_bytes_per_line();
+                self.maybe_update_offset_and_draw(stdout)
             }
             Event::Key(KeyEvent { code, modifiers }) => match (code, modifiers) {
                 (KeyCode::Char('e'), KeyModifiers::CONTROL) => {
-                    
-                    // Configurable chunk size (e.g., 368 bytes)
-                    // default 23 rows x 16 bytes is 368)
-                    let (_, height) = terminal::size().unwrap_or((80, 23));
-                    let chunk_size = (height as usize - 1) * 16;  // Subtract status line
+                    let chunk_size = self.chunk_size();
                     let _ = self.buffr_collection.current_mut().load_next_chunk(chunk_size)?;
                              
                     // In view or wherever scrolling occurs
@@ -1449,11 +2009,8 @@ impl HexView {
     
 
     fn scroll_down(&mut self, stdout: &mut impl Write, line_count: usize) -> Result<()> {
-        // Configurable chunk size (e.g., 368 bytes)
-        // default 23 rows x 16 bytes is 368)
-        let (_, height) = terminal::size().unwrap_or((80, 23));
-        let chunk_size = (height as usize - 1) * 16;  // Subtract status line
-            
+        let chunk_size = self.chunk_size();
+
         debug_log("\n=== Scroll Down Event ===");
         debug_log(&format!("scroll_down -> chunk_size -> {}", chunk_size));
         debug_log(&format!("Line count: {}", line_count));
@@ -1469,13 +2026,13 @@ impl HexView {
             current_buffer.data.len()
         };
         debug_log(&format!("Current buffer size: {}", current_size));
-        
-        let next_position = self.start_offset + (line_count * 16);
+
+        let next_position = self.start_offset + (line_count * self.bytes_per_line);
         debug_log(&format!("Next position would be: {}", next_position));
-        
+
         // Calculate how many rows we can display
-        let visible_rows = (self.size.1 - 1) as usize;  // -1 for status line
-        let needed_bytes = next_position + (visible_rows * 16);
+        let visible_rows = self.area.height as usize;
+        let needed_bytes = next_position + (visible_rows * self.bytes_per_line);
         debug_log(&format!("Need bytes up to: {}", needed_bytes));
         
         // // If need more data for full display
@@ -1492,7 +2049,7 @@ impl HexView {
         // }
 
         // Calculate next visible range
-        let next_visible_end = next_position + (self.size.1 as usize * self.bytes_per_line);
+        let next_visible_end = next_position + self.chunk_size();
         let current_size = self.buffr_collection.current().data.len();
         debug_log(&format!("Need bytes up to: {}, have: {}", next_visible_end, current_size));
 
@@ -1519,10 +2076,7 @@ impl HexView {
                     //     self.trim_buffer_top(chunk_size);
                     // }
                     // Check if we should trim (after releasing the borrow)
-                    let should_trim = self.should_trim_buffer();
                     if self.should_trim_buffer() {
-                        let (_, height) = terminal::size().unwrap_or((80, 23));
-                        let chunk_size = (height as usize - 1) * 16;
                         if chunk_size > 0 {  // Only trim if we have a valid chunk size
                             debug_log(&format!("Trimming with chunk_size: {}", chunk_size));
                             self.trim_buffer_top(chunk_size);
@@ -1581,7 +2135,8 @@ impl HexView {
         self.start_offset = next_position;
         debug_log(&format!("Updated start_offset: {} -> {}", old_start, self.start_offset));
 
-        if line_count > (self.size.1 - 1) as usize {
+        // Terminal scrolling would move the other panes too
+        if line_count > self.area.height as usize || self.buffr_collection.panes().len() > 1 {
             self.draw(stdout)?;
             Ok(())
         } else {
@@ -1594,21 +2149,20 @@ impl HexView {
 
             let mut invalidated_rows: BTreeSet<u16> =
                 (self.size.1 - 1 - line_count as u16..=self.size.1 - 2).collect();
-            invalidated_rows.extend(0..BytePropertiesFormatter::height() as u16);
-            let _ = self.draw_rows(stdout, &invalidated_rows)?;
-            Ok(())
+            invalidated_rows.extend(0..self.inspector_height() as u16);
+            self.draw_rows(stdout, &invalidated_rows)
         }
     }
 
     fn scroll_up(&mut self, stdout: &mut impl Write, line_count: usize) -> Result<()> {
-        if self.start_offset < 0x10 * line_count {
+        if self.start_offset < self.bytes_per_line * line_count {
             // we already at the top the file
             return Ok(());
         }
 
-        self.start_offset -= 0x10 * line_count;
+        self.start_offset -= self.bytes_per_line * line_count;
 
-        if line_count > (self.size.1 - 1) as usize {
+        if line_count > self.area.height as usize || self.buffr_collection.panes().len() > 1 {
             self.draw(stdout)?;
             Ok(())
         } else {
@@ -1620,7 +2174,7 @@ impl HexView {
             )?;
 
             let invalidated_rows: BTreeSet<u16> =
-                (0..(line_count + BytePropertiesFormatter::height()) as u16).collect();
+                (0..(line_count + self.inspector_height()) as u16).collect();
             self.draw_rows(stdout, &invalidated_rows) // -1 is statusline
         }
     }
@@ -1643,7 +2197,7 @@ impl HexView {
         if delta < 0 {
             let line_delta =
                 (delta - self.bytes_per_line as isize + 1) / self.bytes_per_line as isize;
-            self.scroll_up(stdout, line_delta.abs() as usize)
+            self.scroll_up(stdout, line_delta.unsigned_abs())
         } else {
             let line_delta =
                 (delta + self.bytes_per_line as isize - 1) / self.bytes_per_line as isize;
@@ -1655,12 +2209,10 @@ impl HexView {
         let main_cursor_offset = self.buffr_collection.current().selection.main_cursor_offset();
         let visible_bytes = self.visible_bytes();
         if main_cursor_offset < visible_bytes.start {
-            self.start_offset = main_cursor_offset - main_cursor_offset % self.bytes_per_line;
+            self.start_offset = self.row_start(main_cursor_offset);
         } else if main_cursor_offset >= visible_bytes.end {
-            let bytes_per_screen = (self.size.1 as usize - 1) * self.bytes_per_line; // -1 for statusline
-            self.start_offset = (main_cursor_offset - main_cursor_offset % self.bytes_per_line
-                + self.bytes_per_line)
-                .saturating_sub(bytes_per_screen);
+            self.start_offset = (self.row_start(main_cursor_offset) + self.bytes_per_line)
+                .saturating_sub(self.chunk_size());
         }
 
         self.draw(stdout)?;
@@ -1676,28 +2228,89 @@ impl HexView {
             DirtyBytes::ChangeInPlace(intervals) => {
                 self.maybe_update_offset(stdout)?;
 
-                let visible: Interval = self.visible_bytes().into();
-                let mut invalidated_rows: BTreeSet<u16> = intervals
-                    .into_iter()
-                    .flat_map(|x| {
-                        let intersection = visible.intersect(x);
-                        if intersection.is_empty() {
-                            0..0
-                        } else {
-                            intersection.start..intersection.end
+                // Every pane showing the changed buffer has the same rows to redraw
+                let buffer = self.buffr_collection.current_index();
+                let mut invalidated_lines = BTreeSet::new();
+                for index in 0..self.buffr_collection.panes().len() {
+                    self.with_pane(index, |view| {
+                        if view.buffer_index() != buffer {
+                            return;
                         }
-                    })
-                    .map(|byte| ((byte - self.start_offset) / self.bytes_per_line) as u16)
-                    .collect();
-
-                invalidated_rows.extend(0..BytePropertiesFormatter::height() as u16);
-                self.draw_rows(stdout, &invalidated_rows)
+                        let visible: Interval = view.visible_bytes().into();
+                        invalidated_lines.extend(
+                            intervals
+                                .iter()
+                                .flat_map(|&x| {
+                                    let intersection = visible.intersect(x);
+                                    if intersection.is_empty() {
+                                        0..0
+                                    } else {
+                                        intersection.start..intersection.end
+                                    }
+                                })
+                                .map(|byte| {
+                                    view.area.y
+                                        + ((byte - view.start_offset) / view.bytes_per_line) as u16
+                                }),
+                        );
+                        // Rows are drawn along with the inspector lines beside them
+                        invalidated_lines.extend(
+                            (0..view.inspector_height() as u16).map(|row| view.area.y + row),
+                        );
+                    });
+                }
+                self.draw_lines(stdout, &invalidated_lines)
             }
             DirtyBytes::ChangeLength => self.maybe_update_offset_and_draw(stdout),
         }
     }
 
     fn transition(&mut self, stdout: &mut impl Write, transition: ModeTransition) -> Result<()> {
+        let had_hints = self.mode.hints().is_some();
+        let searching = self.search().is_some();
+        // The mode may have changed `bytes-per-line` or the panes, so lay rows out
+        // anew first
+        let repaned = self.sync_panes();
+        let reminimapped = self.sync_minimap(stdout)?;
+        let relaid = self.update_bytes_per_line();
+        self.apply_transition(stdout, transition)?;
+        if relaid || repaned || reminimapped {
+            self.maybe_update_offset_and_draw(stdout)?;
+        } else if had_hints && self.mode.hints().is_none() {
+            // Hints cover a row of data, which has to be redrawn once they're gone
+            self.draw(stdout)?;
+        }
+        self.follow_search(stdout, searching)
+    }
+
+    /// Shows the current match of an incremental search, scrolling from where the
+    /// search started if it's out of view, and scrolls back once the search is over.
+    fn follow_search(&mut self, stdout: &mut impl Write, was_searching: bool) -> Result<()> {
+        let current = match self.search() {
+            Some(search) => search.current_match.map(|k| search.matches[k].start),
+            None => {
+                if let Some(origin) = self.search_origin.take().filter(|_| was_searching) {
+                    self.start_offset = origin;
+                    self.maybe_update_offset_and_draw(stdout)?;
+                }
+                return Ok(());
+            }
+        };
+        self.start_offset = *self.search_origin.get_or_insert(self.start_offset);
+        if let Some(offset) = current.filter(|offset| !self.visible_bytes().contains(offset)) {
+            let rows_above = self.area.height as usize / 2;
+            self.start_offset =
+                self.row_start(offset.saturating_sub(rows_above * self.bytes_per_line));
+        }
+        self.draw(stdout)?;
+        Ok(())
+    }
+
+    fn apply_transition(
+        &mut self,
+        stdout: &mut impl Write,
+        transition: ModeTransition,
+    ) -> Result<()> {
         self.info = None;
         match transition {
             ModeTransition::None => Ok(()),
@@ -1717,12 +2330,82 @@ impl HexView {
                 self.info = Some(info);
                 Ok(())
             }
+            ModeTransition::ModeAndDirtyBytesAndInfo(mode, dirty_bytes, info) => {
+                self.mode = mode;
+                self.transition_dirty_bytes(stdout, dirty_bytes)?;
+                self.info = Some(info);
+                Ok(())
+            }
         }
     }
 
+    /// Remembers `key` as part of the change `.` repeats, given whether the mode was
+    /// idle and the change stamp from before the key was handled.
+    fn track_change(&mut self, key: KeyEvent, idle: bool, stamp: (usize, usize)) {
+        let after = self.buffr_collection.change_stamp();
+        let macros = self.buffr_collection.macros_mut();
+        macros.track_key(key, idle, stamp);
+        macros.track_result(self.mode.is_idle(), after);
+    }
+
+    /// Feeds a macro requested by the last transition back through the modes,
+    /// stopping at the first key that isn't handled.
+    fn replay_pending_macro(&mut self, stdout: &mut impl Write, depth: usize) -> Result<()> {
+        let (keys, count) = match self.buffr_collection.macros_mut().take_pending_replay() {
+            Some(replay) => replay,
+            None => return Ok(()),
+        };
+        if depth >= MAX_REPLAY_DEPTH {
+            self.info = Some("macro replay nested too deeply".to_owned());
+            return Ok(());
+        }
+
+        for _ in 0..count {
+            for key in keys.iter() {
+                if !self.mode.takes_input() {
+                    return Ok(());
+                }
+                let (idle, stamp) = (self.mode.is_idle(), self.buffr_collection.change_stamp());
+                let transition = self.mode.transition(
+                    &Event::Key(*key),
+                    &mut self.buffr_collection,
+                    self.bytes_per_line,
+                );
+                match transition {
+                    // A step that reports an error or info stops the replay, so that a
+                    // failing step doesn't run again for every count
+                    Some(transition) if transition.has_info() => {
+                        self.transition(stdout, transition)?;
+                        self.track_change(*key, idle, stamp);
+                        return Ok(());
+                    }
+                    Some(transition) => {
+                        self.transition(stdout, transition)?;
+                        self.track_change(*key, idle, stamp);
+                    }
+                    None => {
+                        self.info = Some(format!("macro stopped at {}", format_key(key)));
+                        return Ok(());
+                    }
+                }
+                self.replay_pending_macro(stdout, depth + 1)?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn run_event_loop(mut self, stdout: &mut impl Write) -> Result<()> {
-        execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+        // Rows too wide for their pane are cut off rather than wrapped
+        execute!(
+            stdout,
+            terminal::EnterAlternateScreen,
+            terminal::DisableLineWrap,
+            cursor::Hide
+        )?;
 
+        // The config may have turned the minimap on
+        self.sync_minimap(stdout)?;
+        self.update_bytes_per_line();
         self.last_draw_time = self.draw(stdout)?;
         terminal::enable_raw_mode()?;
         stdout.flush()?;
@@ -1731,21 +2414,110 @@ impl HexView {
             if !self.mode.takes_input() {
                 break;
             }
+            // Fill the minimap in as its file is scanned, between events
+            if self
+                .minimap
+                .as_ref()
+                .is_some_and(|minimap| !minimap.is_done())
+                && !event::poll(MINIMAP_REFRESH)?
+            {
+                if self.minimap.as_mut().is_some_and(|minimap| minimap.poll()) {
+                    self.draw_minimap(stdout)?;
+                    stdout.flush()?;
+                }
+                continue;
+            }
             let evt = event::read()?;
+            if let Event::Mouse(mouse) = evt {
+                self.handle_mouse(stdout, mouse)?;
+                self.draw_minimap(stdout)?;
+                self.draw_statusline(stdout)?;
+                stdout.flush()?;
+                continue;
+            }
+            if let Event::Key(key) = evt {
+                self.buffr_collection.macros_mut().record(key);
+            }
+            let (idle, stamp) = (self.mode.is_idle(), self.buffr_collection.change_stamp());
             let transition = self
                 .mode
                 .transition(&evt, &mut self.buffr_collection, self.bytes_per_line);
             if let Some(transition) = transition {
                 self.transition(stdout, transition)?;
+                if let Event::Key(key) = evt {
+                    self.track_change(key, idle, stamp);
+                }
+                self.replay_pending_macro(stdout, 0)?;
             } else {
                 self.handle_event_default(stdout, evt)?;
             }
+            if let Some(sequence) = self.buffr_collection.take_osc52() {
+                queue!(stdout, style::Print(sequence))?;
+            }
 
+            self.draw_minimap(stdout)?;
             self.draw_statusline(stdout)?;
             stdout.flush()?;
         }
-        execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen)?;
+        if self.minimap_shown {
+            execute!(stdout, event::DisableMouseCapture)?;
+        }
+        execute!(
+            stdout,
+            cursor::Show,
+            terminal::EnableLineWrap,
+            terminal::LeaveAlternateScreen
+        )?;
         terminal::disable_raw_mode()?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modes::find::Find;
+    use crate::modes::search::{Pattern, PatternPiece, Search};
+
+    #[test]
+    fn test_follow_search() {
+        let mut buffer = CurrentBuffer::from_data_and_path(vec![0; 0x1000], None::<&str>);
+        buffer
+            .selection
+            .map_selections(|region| vec![region.jump_to(0x120)]);
+        let mut view = HexView::with_buffr_collection(BuffrCollection::with_current_buffer(buffer));
+        let mut stdout = vec![];
+        view.size = (80, 11);
+        view.area = view.pane_areas()[0];
+        view.start_offset = 0x100;
+
+        let mut search = Search::new(Find { forward: true }, false);
+        search.pattern = Pattern {
+            pieces: vec![PatternPiece::Literal(0)],
+            regex: false,
+        };
+        search.matches = std::rc::Rc::new(vec![0x110..0x111, 0x800..0x801]);
+        search.current_match = Some(1);
+        view.mode = Box::new(search);
+        view.follow_search(&mut stdout, false).unwrap();
+        // The match is centred in the 10 rows
+        assert_eq!(view.start_offset, 0x800 - 5 * 0x10);
+
+        // Cancelling scrolls back to where the search started
+        view.mode = Box::new(modes::normal::Normal::new());
+        view.follow_search(&mut stdout, true).unwrap();
+        assert_eq!(view.start_offset, 0x100);
+    }
+
+    #[test]
+    fn test_rows_start_on_file_offsets() {
+        let mut buffer = CurrentBuffer::from_data_and_path(vec![0; 0x1000], None::<&str>);
+        buffer.data_start_offset = 0x3000;
+        let mut view = HexView::with_buffr_collection(BuffrCollection::with_current_buffer(buffer));
+        view.start_offset = 0x100;
+        view.set_bytes_per_line(24);
+        // 0x3100 is 16 bytes into a row of 24
+        assert_eq!(view.start_offset, 0x100 - 16);
+        assert_eq!(view.row_start(8), 0);
+    }
+}
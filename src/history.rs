@@ -77,6 +77,9 @@ pub struct History {
 
     undo: Vec<(Action, Selection)>,
     redo: Vec<(Action, Selection)>,
+
+    // Counts completed changes; unlike the undo stack, undo and redo don't move it
+    changes: usize,
 }
 
 impl History {
@@ -88,6 +91,7 @@ impl History {
         self.undo
             .push((Action::from_delta(delta).invert(current_rope), selection));
         self.redo = vec![];
+        self.changes += 1;
     }
 
     pub fn perform_partial(
@@ -114,9 +118,14 @@ impl History {
         if let Some((partial, selection)) = self.partial.take() {
             self.undo.push((partial, selection));
             self.redo = vec![];
+            self.changes += 1;
         }
     }
 
+    pub fn change_count(&self) -> usize {
+        self.changes
+    }
+
     pub fn undo(
         &mut self,
         current_rope: &Rope,
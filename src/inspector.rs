@@ -0,0 +1,574 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::options::Endian;
+
+/// The most bytes after the caret any inspector row looks at.
+pub const MAX_INSPECTED_BYTES: usize = 16;
+
+// LEB128 encodes 64-bit values in at most 10 bytes
+const MAX_LEB128_BYTES: usize = 10;
+
+/// A row of the data inspector, showing the bytes at the main caret as one kind of
+/// value. Rows are picked with the `inspector` option.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Field {
+    Hex,
+    Bin,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    F16,
+    F32,
+    F64,
+    Leb128,
+    Utf8,
+    Utf16,
+}
+
+/// A value shown in a row, or the text of a value that doesn't decode.
+pub type Decoded = (String, Result<String, String>);
+
+fn read_uint(data: &[u8], endian: Endian) -> u64 {
+    let mut bytes = [0; 8];
+    match endian {
+        Endian::Big => {
+            bytes[8 - data.len()..].copy_from_slice(data);
+            u64::from_be_bytes(bytes)
+        }
+        Endian::Little => {
+            bytes[..data.len()].copy_from_slice(data);
+            u64::from_le_bytes(bytes)
+        }
+    }
+}
+
+// Sign extends the low `bits` bits
+fn to_signed(value: u64, bits: u32) -> i64 {
+    let shift = 64 - bits;
+    ((value << shift) as i64) >> shift
+}
+
+pub fn f16_to_f64(bits: u16) -> f64 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let fraction = (bits & 0x3ff) as f64;
+    sign * match exponent {
+        0 => fraction * 2f64.powi(-24),
+        0x1f if fraction == 0.0 => f64::INFINITY,
+        0x1f => f64::NAN,
+        _ => (1.0 + fraction / 1024.0) * 2f64.powi(exponent - 15),
+    }
+}
+
+// Very large and very small floats would print hundreds of digits
+pub fn format_float<T: fmt::Display + fmt::LowerExp + Into<f64> + Copy>(value: T) -> String {
+    let magnitude = value.into().abs();
+    if magnitude != 0.0 && magnitude.is_finite() && !(1e-6..1e16).contains(&magnitude) {
+        format!("{:e}", value)
+    } else {
+        format!("{}", value)
+    }
+}
+
+/// Decodes an unsigned LEB128 value, returning it and how many bytes it took.
+/// Values that don't fit 64 bits decode as `None` along with their length.
+pub fn read_uleb128(data: &[u8]) -> Option<(Option<u64>, usize)> {
+    let mut value = 0u64;
+    let mut fits = true;
+    for (i, byte) in data.iter().take(MAX_LEB128_BYTES).enumerate() {
+        let shift = 7 * i as u32;
+        let bits = (byte & 0x7f) as u64;
+        if shift >= 64 || (bits << shift) >> shift != bits {
+            fits = fits && bits == 0;
+        } else {
+            value |= bits << shift;
+        }
+        if byte & 0x80 == 0 {
+            return Some((Some(value).filter(|_| fits), i + 1));
+        }
+    }
+    None
+}
+
+/// Like `read_uleb128`, for the signed variant.
+pub fn read_sleb128(data: &[u8]) -> Option<(Option<i64>, usize)> {
+    let (_, len) = read_uleb128(data)?;
+    let mut value = 0i64;
+    let mut shift = 0;
+    for byte in &data[..len] {
+        if shift < 64 {
+            value |= ((byte & 0x7f) as i64) << shift;
+        }
+        shift += 7;
+    }
+    if shift < 64 && data[len - 1] & 0x40 != 0 {
+        value |= -1 << shift;
+    }
+    // Only 10 byte encodings can run past 64 bits
+    let fits = len < MAX_LEB128_BYTES || matches!(data[len - 1], 0x00 | 0x7f);
+    Some((Some(value).filter(|_| fits), len))
+}
+
+fn format_char(c: char) -> String {
+    if c.is_ascii_graphic() {
+        c.to_string()
+    } else {
+        format!("{:x?}", c)
+    }
+}
+
+fn utf8_into_char(data: &[u8]) -> Result<char, char> {
+    for i in 1..=std::cmp::min(data.len(), 4) {
+        if let Ok(s) = std::str::from_utf8(&data[..i]) {
+            return Ok(s.chars().next().unwrap());
+        }
+    }
+    Err('�')
+}
+
+fn utf16_into_char(data: &[u8], endian: Endian) -> Result<char, char> {
+    let units = data
+        .chunks_exact(2)
+        .take(2)
+        .map(|pair| read_uint(pair, endian) as u16);
+    match char::decode_utf16(units).next() {
+        Some(Ok(c)) => Ok(c),
+        _ => Err('�'),
+    }
+}
+
+fn write_uint(value: u64, size: usize, endian: Endian) -> Vec<u8> {
+    match endian {
+        Endian::Big => value.to_be_bytes()[8 - size..].to_vec(),
+        Endian::Little => value.to_le_bytes()[..size].to_vec(),
+    }
+}
+
+// Rounds halfway cases to the even neighbour, as IEEE 754 conversions do
+fn round_ties_even(value: f64) -> f64 {
+    if (value - value.trunc()).abs() == 0.5 {
+        2.0 * (value / 2.0).round()
+    } else {
+        value.round()
+    }
+}
+
+/// Rounds to the nearest half precision float, ties to even.
+pub fn f64_to_f16(value: f64) -> u16 {
+    let sign = if value.is_sign_negative() { 0x8000 } else { 0 };
+    let magnitude = value.abs();
+    if magnitude.is_nan() {
+        return sign | 0x7e00;
+    }
+    // Below the smallest normal value, steps are 2^-24 apart
+    if magnitude < 2f64.powi(-14) {
+        return sign | round_ties_even(magnitude * 2f64.powi(24)) as u16;
+    }
+    if magnitude.is_infinite() {
+        return sign | 0x7c00;
+    }
+    let mut exponent = ((magnitude.to_bits() >> 52) & 0x7ff) as i32 - 1023;
+    let mut fraction = round_ties_even((magnitude / 2f64.powi(exponent) - 1.0) * 1024.0) as u16;
+    if fraction == 0x400 {
+        fraction = 0;
+        exponent += 1;
+    }
+    if exponent > 15 {
+        return sign | 0x7c00;
+    }
+    sign | (((exponent + 15) as u16) << 10) | fraction
+}
+
+// Integers may be typed in decimal or, with a 0x prefix, in hex
+fn parse_int(text: &str) -> Result<i128, String> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+    if digits.starts_with(['-', '+']) {
+        return Err(format!("{} is not an integer", text));
+    }
+    let magnitude = match digits.strip_prefix("0x") {
+        Some(hex) => i128::from_str_radix(hex, 16),
+        None => digits.parse::<i128>(),
+    }
+    .map_err(|_| format!("{} is not an integer", text))?;
+    Ok(if negative { -magnitude } else { magnitude })
+}
+
+fn parse_float(text: &str) -> Result<f64, String> {
+    text.parse::<f64>()
+        .map_err(|_| format!("{} is not a number", text))
+}
+
+fn parse_digits(text: &str, radix: u32, digits_per_byte: usize) -> Result<Vec<u8>, String> {
+    let digits: String = text.split_whitespace().collect();
+    if digits.is_empty() || digits.len() % digits_per_byte != 0 || !digits.is_ascii() {
+        return Err(format!("{} is not a whole number of bytes", text));
+    }
+    (0..digits.len())
+        .step_by(digits_per_byte)
+        .map(|i| {
+            u8::from_str_radix(&digits[i..i + digits_per_byte], radix)
+                .map_err(|_| format!("{} is not a valid digit string", text))
+        })
+        .collect()
+}
+
+fn write_uleb128(mut value: u64) -> Vec<u8> {
+    let mut bytes = vec![];
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return bytes;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn write_sleb128(mut value: i64) -> Vec<u8> {
+    let mut bytes = vec![];
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            bytes.push(byte);
+            return bytes;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn endian_label(name: &str, endian: Endian) -> String {
+    match endian {
+        Endian::Big => format!("{} be", name),
+        Endian::Little => format!("{} le", name),
+    }
+}
+
+impl Field {
+    pub const ALL: &'static [Field] = &[
+        Field::Hex,
+        Field::Bin,
+        Field::Int8,
+        Field::Int16,
+        Field::Int32,
+        Field::Int64,
+        Field::F16,
+        Field::F32,
+        Field::F64,
+        Field::Leb128,
+        Field::Utf8,
+        Field::Utf16,
+    ];
+
+    /// How many bytes the row needs; fewer bytes left before the end of the
+    /// buffer grey it out.
+    pub fn size(self) -> usize {
+        match self {
+            Field::Hex | Field::Bin | Field::Int8 | Field::Leb128 | Field::Utf8 => 1,
+            Field::Int16 | Field::F16 | Field::Utf16 => 2,
+            Field::Int32 | Field::F32 => 4,
+            Field::Int64 | Field::F64 => 8,
+        }
+    }
+
+    /// What the row is called when editing it, with the byte order when it matters.
+    pub fn label(self, endian: Endian) -> String {
+        match self {
+            Field::Hex | Field::Bin | Field::Int8 | Field::Leb128 | Field::Utf8 => self.to_string(),
+            _ => endian_label(&self.to_string(), endian),
+        }
+    }
+
+    /// The labelled values the row shows for `data`, the bytes from the caret on.
+    /// Returns `None` when there aren't enough bytes for the row.
+    pub fn decode(self, data: &[u8], endian: Endian) -> Option<Vec<Decoded>> {
+        if data.len() < self.size() {
+            return None;
+        }
+        let int = |bits: u32| {
+            let value = read_uint(&data[..bits as usize / 8], endian);
+            let (unsigned, signed) = if bits == 8 {
+                ("u8".to_owned(), "i8".to_owned())
+            } else {
+                (
+                    endian_label(&format!("u{}", bits), endian),
+                    endian_label(&format!("i{}", bits), endian),
+                )
+            };
+            vec![
+                (unsigned, Ok(value.to_string())),
+                (signed, Ok(to_signed(value, bits).to_string())),
+            ]
+        };
+        Some(match self {
+            Field::Hex => vec![(
+                "hex".to_owned(),
+                Ok(data
+                    .iter()
+                    .take(8)
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect::<Vec<_>>()
+                    .join(" ")),
+            )],
+            Field::Bin => vec![(
+                "bin".to_owned(),
+                Ok(data
+                    .iter()
+                    .take(4)
+                    .map(|byte| format!("{:08b}", byte))
+                    .collect::<Vec<_>>()
+                    .join(" ")),
+            )],
+            Field::Int8 => int(8),
+            Field::Int16 => int(16),
+            Field::Int32 => int(32),
+            Field::Int64 => int(64),
+            Field::F16 => vec![(
+                endian_label("f16", endian),
+                Ok(format_float(
+                    f16_to_f64(read_uint(&data[..2], endian) as u16) as f32,
+                )),
+            )],
+            Field::F32 => vec![(
+                endian_label("f32", endian),
+                Ok(format_float(f32::from_bits(
+                    read_uint(&data[..4], endian) as u32
+                ))),
+            )],
+            Field::F64 => vec![(
+                endian_label("f64", endian),
+                Ok(format_float(f64::from_bits(read_uint(&data[..8], endian)))),
+            )],
+            Field::Leb128 => {
+                let (unsigned, len) = read_uleb128(data)?;
+                let (signed, _) = read_sleb128(data)?;
+                let format = |value: Option<String>| match value {
+                    Some(value) => Ok(format!("{} ({})", value, len)),
+                    None => Err(format!("overflow ({})", len)),
+                };
+                vec![
+                    (
+                        "uleb128".to_owned(),
+                        format(unsigned.map(|x| x.to_string())),
+                    ),
+                    ("sleb128".to_owned(), format(signed.map(|x| x.to_string()))),
+                ]
+            }
+            Field::Utf8 => vec![(
+                "utf-8".to_owned(),
+                utf8_into_char(&data[..std::cmp::min(data.len(), 4)])
+                    .map(format_char)
+                    .map_err(String::from),
+            )],
+            Field::Utf16 => vec![(
+                endian_label("utf-16", endian),
+                utf16_into_char(&data[..std::cmp::min(data.len(), 4)], endian)
+                    .map(format_char)
+                    .map_err(String::from),
+            )],
+        })
+    }
+
+    /// The bytes that make the row show `text`. Integers accept both their
+    /// signed and unsigned range; hex and bin take whole bytes, and the text
+    /// rows take any string.
+    pub fn encode(self, text: &str, endian: Endian) -> Result<Vec<u8>, String> {
+        let text = text.trim();
+        let int = |bits: u32| {
+            let value = parse_int(text)?;
+            if value < -(1i128 << (bits - 1)) || value >= 1i128 << bits {
+                return Err(format!("{} doesn't fit {} bits", text, bits));
+            }
+            Ok(write_uint(value as u64, bits as usize / 8, endian))
+        };
+        let float = |value: f64, rounded: f64| {
+            if value.is_finite() && rounded.is_infinite() {
+                Err(format!("{} is out of range for {}", text, self))
+            } else {
+                Ok(())
+            }
+        };
+        match self {
+            Field::Hex => parse_digits(text, 16, 2),
+            Field::Bin => parse_digits(text, 2, 8),
+            Field::Int8 => int(8),
+            Field::Int16 => int(16),
+            Field::Int32 => int(32),
+            Field::Int64 => int(64),
+            Field::F16 => {
+                let value = parse_float(text)?;
+                let bits = f64_to_f16(value);
+                float(value, f16_to_f64(bits))?;
+                Ok(write_uint(bits as u64, 2, endian))
+            }
+            Field::F32 => {
+                let value = parse_float(text)?;
+                float(value, value as f32 as f64)?;
+                Ok(write_uint((value as f32).to_bits() as u64, 4, endian))
+            }
+            Field::F64 => {
+                let value = parse_float(text)?;
+                Ok(write_uint(value.to_bits(), 8, endian))
+            }
+            Field::Leb128 => match parse_int(text)? {
+                value if value >= 0 && value <= u64::MAX as i128 => Ok(write_uleb128(value as u64)),
+                value if value < 0 && value >= i64::MIN as i128 => Ok(write_sleb128(value as i64)),
+                _ => Err(format!("{} doesn't fit 64 bits", text)),
+            },
+            Field::Utf8 | Field::Utf16 if text.is_empty() => Err("nothing to write".to_owned()),
+            Field::Utf8 => Ok(text.as_bytes().to_vec()),
+            Field::Utf16 => Ok(text
+                .encode_utf16()
+                .flat_map(|unit| write_uint(unit as u64, 2, endian))
+                .collect()),
+        }
+    }
+}
+
+impl FromStr for Field {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Field::ALL
+            .iter()
+            .copied()
+            .find(|field| field.to_string() == s)
+            .ok_or_else(|| format!("unknown inspector row {}", s))
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Field::Hex => "hex",
+            Field::Bin => "bin",
+            Field::Int8 => "int8",
+            Field::Int16 => "int16",
+            Field::Int32 => "int32",
+            Field::Int64 => "int64",
+            Field::F16 => "f16",
+            Field::F32 => "f32",
+            Field::F64 => "f64",
+            Field::Leb128 => "leb128",
+            Field::Utf8 => "utf8",
+            Field::Utf16 => "utf16",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Parses a comma separated list of inspector rows, such as `hex,int32,f32`.
+pub fn parse_fields(value: &str) -> Result<Vec<Field>, String> {
+    if value.is_empty() {
+        return Ok(vec![]);
+    }
+    value.split(',').map(|name| name.trim().parse()).collect()
+}
+
+pub fn format_fields(fields: &[Field]) -> String {
+    fields
+        .iter()
+        .map(|field| field.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(field: Field, data: &[u8], endian: Endian) -> Vec<String> {
+        field
+            .decode(data, endian)
+            .unwrap()
+            .into_iter()
+            .map(|(_, value)| value.unwrap_or_else(|e| e))
+            .collect()
+    }
+
+    #[test]
+    fn test_endianness() {
+        let data = [0xff, 0xfe, 0x00, 0x00];
+        assert_eq!(values(Field::Int16, &data, Endian::Big), ["65534", "-2"]);
+        assert_eq!(
+            values(Field::Int16, &data, Endian::Little),
+            ["65279", "-257"]
+        );
+        assert_eq!(
+            values(Field::Int32, &data, Endian::Little),
+            ["65279", "65279"]
+        );
+        assert_eq!(Field::Int64.decode(&data, Endian::Little), None);
+        assert_eq!(values(Field::F16, &[0x3c, 0x00], Endian::Big), ["1"]);
+        assert_eq!(
+            values(Field::F32, &[0, 0, 0xc0, 0x3f], Endian::Little),
+            ["1.5"]
+        );
+        assert_eq!(
+            utf16_into_char(&[0xd8, 0x01, 0xdc, 0x37], Endian::Big),
+            Ok('𐐷')
+        );
+    }
+
+    #[test]
+    fn test_leb128() {
+        assert_eq!(
+            read_uleb128(&[0xe5, 0x8e, 0x26, 0xff]),
+            Some((Some(624485), 3))
+        );
+        assert_eq!(read_sleb128(&[0xc0, 0xbb, 0x78]), Some((Some(-123456), 3)));
+        assert_eq!(read_uleb128(&[0x80, 0x80]), None);
+        assert_eq!(
+            read_uleb128(&[0xff; 9].iter().chain(&[0x7f]).copied().collect::<Vec<_>>()),
+            Some((None, 10))
+        );
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(
+            Field::Int16.encode("-2", Endian::Little),
+            Ok(vec![0xfe, 0xff])
+        );
+        assert_eq!(
+            Field::Int16.encode("0xffff", Endian::Big),
+            Ok(vec![0xff, 0xff])
+        );
+        assert!(Field::Int8.encode("256", Endian::Big).is_err());
+        assert!(Field::Int8.encode("-129", Endian::Big).is_err());
+        assert!(Field::Int32.encode("1.5", Endian::Big).is_err());
+        assert_eq!(
+            Field::F32.encode("1.5", Endian::Little),
+            Ok(vec![0, 0, 0xc0, 0x3f])
+        );
+        assert!(Field::F32.encode("1e39", Endian::Big).is_err());
+        assert_eq!(Field::F16.encode("1", Endian::Big), Ok(vec![0x3c, 0x00]));
+        assert_eq!(
+            Field::F16.encode("-65504", Endian::Big),
+            Ok(vec![0xfb, 0xff])
+        );
+        assert!(Field::F16.encode("65520", Endian::Big).is_err());
+        assert_eq!(
+            Field::Leb128.encode("624485", Endian::Big),
+            Ok(vec![0xe5, 0x8e, 0x26])
+        );
+        assert_eq!(
+            Field::Leb128.encode("-123456", Endian::Big),
+            Ok(vec![0xc0, 0xbb, 0x78])
+        );
+        assert_eq!(
+            Field::Hex.encode("de ad be", Endian::Big),
+            Ok(vec![0xde, 0xad, 0xbe])
+        );
+        assert!(Field::Hex.encode("abc", Endian::Big).is_err());
+        assert_eq!(Field::Bin.encode("00000001", Endian::Big), Ok(vec![1]));
+        assert_eq!(Field::Utf16.encode("A", Endian::Little), Ok(vec![0x41, 0]));
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+use crate::byte_rope::RopeDelta;
+use crate::marks::transform_offsets;
+
+/// How many jumps are remembered before the oldest ones are dropped.
+pub const JUMP_LIST_SIZE: usize = 100;
+
+/// File offsets in one buffer that jump list entries point at. Like marks,
+/// they follow edits to the buffer.
+#[derive(Debug, Default, Clone)]
+pub struct Anchors {
+    next_id: usize,
+    offsets: HashMap<usize, usize>,
+}
+
+impl Anchors {
+    pub fn insert(&mut self, offset: usize) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.offsets.insert(id, offset);
+        id
+    }
+
+    pub fn get(&self, id: usize) -> Option<usize> {
+        self.offsets.get(&id).copied()
+    }
+
+    pub fn remove(&mut self, id: usize) {
+        self.offsets.remove(&id);
+    }
+
+    pub fn apply_delta(&mut self, delta: &RopeDelta, start: usize, max_len: usize) {
+        transform_offsets(self.offsets.values_mut(), delta, start, max_len);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Jump {
+    pub buffer: usize,
+    pub anchor: usize,
+}
+
+/// The places big caret moves started from, walked with `<c-o>` and `<c-i>`.
+///
+/// `position` is the entry being visited; it equals `entries.len()` when not
+/// walking the list.
+#[derive(Debug, Default, Clone)]
+pub struct JumpList {
+    entries: Vec<Jump>,
+    position: usize,
+}
+
+impl JumpList {
+    pub fn is_walking(&self) -> bool {
+        self.position < self.entries.len()
+    }
+
+    pub fn last(&self) -> Option<Jump> {
+        self.entries.last().copied()
+    }
+
+    /// Drops the entry being visited and those after it, as a new jump starts a
+    /// new branch. Returns the dropped entries so their anchors can be freed.
+    pub fn truncate(&mut self) -> Vec<Jump> {
+        self.entries.drain(self.position..).collect()
+    }
+
+    /// Appends a jump and stops walking. Returns the oldest entry if it had to
+    /// make room for this one.
+    pub fn push(&mut self, jump: Jump) -> Option<Jump> {
+        self.entries.push(jump);
+        let evicted = if self.entries.len() > JUMP_LIST_SIZE {
+            Some(self.entries.remove(0))
+        } else {
+            None
+        };
+        self.position = self.entries.len();
+        evicted
+    }
+
+    pub fn back(&mut self, count: usize) -> Option<Jump> {
+        self.position = self.position.checked_sub(count)?;
+        Some(self.entries[self.position])
+    }
+
+    pub fn forward(&mut self, count: usize) -> Option<Jump> {
+        if self.position + count >= self.entries.len() {
+            return None;
+        }
+        self.position += count;
+        Some(self.entries[self.position])
+    }
+
+    /// Forgets the jumps into a closed buffer and renumbers those after it.
+    pub fn remove_buffer(&mut self, buffer: usize) {
+        let removed_before = self.entries[..self.position]
+            .iter()
+            .filter(|jump| jump.buffer == buffer)
+            .count();
+        self.position -= removed_before;
+        self.entries.retain(|jump| jump.buffer != buffer);
+        for jump in self.entries.iter_mut() {
+            if jump.buffer > buffer {
+                jump.buffer -= 1;
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 
-use crossterm::event::{Event, KeyEvent, KeyModifiers};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct KeyMap<T: Copy> {
@@ -8,6 +9,46 @@ pub struct KeyMap<T: Copy> {
 }
 
 impl<T: Copy> KeyMap<T> {
+    /// Lists the bindings as `(keys, description)` rows for the help view and the
+    /// pending mode hints. Keys with the same description share a row, and actions
+    /// without one are left out.
+    pub fn describe(
+        &self,
+        describe: impl Fn(T) -> Option<Cow<'static, str>>,
+    ) -> Vec<(String, String)> {
+        let mut bindings: Vec<_> = self
+            .maps
+            .iter()
+            .map(|(key, action)| (format_key(key), *action))
+            .collect();
+        bindings.sort_by_key(|(key, _)| (key.to_lowercase(), key.clone()));
+
+        let mut rows: Vec<(String, String)> = vec![];
+        for (key, action) in bindings {
+            let description = match describe(action) {
+                Some(description) => description,
+                None => continue,
+            };
+            match rows.iter_mut().find(|(_, row)| *row == description) {
+                Some((keys, _)) => {
+                    keys.push(' ');
+                    keys.push_str(&key);
+                }
+                None => rows.push((key, description.into_owned())),
+            }
+        }
+        rows
+    }
+
+    /// A one-line summary of the bindings, like `h line start  l line end`.
+    pub fn hints(&self, describe: impl Fn(T) -> Option<Cow<'static, str>>) -> String {
+        self.describe(describe)
+            .iter()
+            .map(|(keys, description)| format!("{} {}", keys, description))
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+
     pub fn event_to_action(&self, evt: &Event) -> Option<T> {
         if let Event::Key(evt) = evt {
             self.maps
@@ -55,7 +96,7 @@ macro_rules! k {
         $map.insert(norm, $act);
     };
 
-    ($map:ident, (key $key:path => $act:expr)) => {
+    ($map:ident, (key $key:expr => $act:expr)) => {
         $map.insert(
             KeyEvent {
                 code: $key,
@@ -75,3 +116,99 @@ macro_rules! keys {
     	}
 	}
 }
+
+const NAMED_KEYS: &[(&str, KeyCode)] = &[
+    ("ret", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("backspace", KeyCode::Backspace),
+    ("del", KeyCode::Delete),
+    ("tab", KeyCode::Tab),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("space", KeyCode::Char(' ')),
+    ("lt", KeyCode::Char('<')),
+];
+
+/// Formats a key in the `<a-s>` style notation used by macros and the help view.
+pub fn format_key(key: &KeyEvent) -> String {
+    let named = NAMED_KEYS
+        .iter()
+        .find(|(_, code)| *code == key.code)
+        .map(|(name, _)| *name);
+    let modifiers = key.modifiers & !KeyModifiers::SHIFT;
+    let mut prefix = String::new();
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        prefix.push_str("c-");
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        prefix.push_str("a-");
+    }
+
+    match (key.code, named) {
+        (KeyCode::Char(ch), None) if prefix.is_empty() => ch.to_string(),
+        (KeyCode::Char(ch), None) => format!("<{}{}>", prefix, ch),
+        (KeyCode::F(n), _) => format!("<{}f{}>", prefix, n),
+        (_, Some(name)) => format!("<{}{}>", prefix, name),
+        (code, None) => format!("<{}{:?}>", prefix, code).to_lowercase(),
+    }
+}
+
+pub fn format_keys(keys: &[KeyEvent]) -> String {
+    keys.iter().map(format_key).collect()
+}
+
+fn parse_key_name(name: &str) -> Result<KeyEvent, String> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = name;
+    loop {
+        if let Some(stripped) = rest.strip_prefix("c-") {
+            modifiers |= KeyModifiers::CONTROL;
+            rest = stripped;
+        } else if let Some(stripped) = rest.strip_prefix("a-") {
+            modifiers |= KeyModifiers::ALT;
+            rest = stripped;
+        } else {
+            break;
+        }
+    }
+
+    let mut chars = rest.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(ch), None) => KeyCode::Char(ch),
+        _ => match NAMED_KEYS.iter().find(|(key_name, _)| *key_name == rest) {
+            Some((_, code)) => *code,
+            None => match rest.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                Some(n) => KeyCode::F(n),
+                None => return Err(format!("unknown key <{}>", name)),
+            },
+        },
+    };
+    Ok(KeyEvent { code, modifiers })
+}
+
+/// Parses the notation produced by `format_keys`. Line breaks are skipped so that
+/// longer key sequences can be spread over several lines.
+pub fn parse_keys(text: &str) -> Result<Vec<KeyEvent>, String> {
+    let mut keys = vec![];
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\n' | '\r' => {}
+            '<' => {
+                let name = chars.by_ref().take_while(|&c| c != '>').collect::<String>();
+                keys.push(parse_key_name(&name)?);
+            }
+            ch => keys.push(KeyEvent {
+                code: KeyCode::Char(ch),
+                modifiers: KeyModifiers::NONE,
+            }),
+        }
+    }
+    Ok(keys)
+}
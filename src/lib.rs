@@ -1,14 +1,30 @@
 #![deny(clippy::all)]
 
+mod clipboard;
+pub mod config;
 mod current_buffer;
+mod decoders;
+mod encoding;
 mod byte_rope;
+pub mod headless;
 pub mod hex_view;
 mod history;
+mod inspector;
+mod jumps;
 #[macro_use]
 mod keymap;
 mod cmd_count;
+mod macros;
+mod marks;
+mod minimap;
 mod modes;
 mod operations;
+mod options;
+mod panes;
+mod registers;
 mod selection;
+mod theme;
 
 pub use current_buffer::{CurrentBuffer, BuffrCollection};
+pub use keymap::{format_keys, parse_keys};
+pub use options::DEFAULT_BYTES_PER_LINE;
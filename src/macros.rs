@@ -0,0 +1,83 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+/// The register macros are recorded into and replayed from by default.
+pub const DEFAULT_MACRO_REGISTER: char = '@';
+
+/// Replays may invoke other macros, but not without bound.
+pub const MAX_REPLAY_DEPTH: usize = 16;
+
+/// Tracks the macro being recorded, the keys of the last change (for `.`) and any
+/// replay requested by a mode.
+///
+/// Modes can't feed events to themselves, so a replay is only queued here and
+/// carried out by whoever drives `Mode::transition` (the view or a headless script).
+#[derive(Debug, Default)]
+pub struct Macros {
+    recording: Option<(char, Vec<KeyEvent>)>,
+    pending_replay: Option<(Vec<KeyEvent>, usize)>,
+
+    change: Vec<KeyEvent>,
+    change_start: (usize, usize),
+    last_change: Option<Vec<KeyEvent>>,
+}
+
+impl Macros {
+    pub fn recording_register(&self) -> Option<char> {
+        self.recording.as_ref().map(|(reg, _)| *reg)
+    }
+
+    pub fn start_recording(&mut self, reg: char) {
+        self.recording = Some((reg, vec![]));
+    }
+
+    /// Stops recording, dropping the trailing key that stopped it.
+    pub fn stop_recording(&mut self) -> Option<(char, Vec<KeyEvent>)> {
+        let (reg, mut keys) = self.recording.take()?;
+        if let Some(KeyEvent {
+            code: KeyCode::Char('Q'),
+            ..
+        }) = keys.last()
+        {
+            keys.pop();
+        }
+        Some((reg, keys))
+    }
+
+    pub fn record(&mut self, key: KeyEvent) {
+        if let Some((_, keys)) = &mut self.recording {
+            keys.push(key);
+        }
+    }
+
+    pub fn request_replay(&mut self, keys: Vec<KeyEvent>, count: usize) {
+        self.pending_replay = Some((keys, count));
+    }
+
+    pub fn take_pending_replay(&mut self) -> Option<(Vec<KeyEvent>, usize)> {
+        self.pending_replay.take()
+    }
+
+    /// Call before a mode handles `key`. A key pressed while the mode is idle starts
+    /// a new candidate change; `stamp` is `BuffrCollection::change_stamp`.
+    pub fn track_key(&mut self, key: KeyEvent, idle: bool, stamp: (usize, usize)) {
+        if idle {
+            self.change.clear();
+            self.change_start = stamp;
+        }
+        self.change.push(key);
+    }
+
+    /// Call after the transition is applied. Once the mode is idle again, the keys
+    /// since the last idle point become the last change if they edited the buffer
+    /// they started in.
+    pub fn track_result(&mut self, idle: bool, stamp: (usize, usize)) {
+        let (start_buffer, start_changes) = self.change_start;
+        if idle && stamp.0 == start_buffer && stamp.1 != start_changes {
+            self.last_change = Some(std::mem::take(&mut self.change));
+        }
+    }
+
+    pub fn last_change(&self) -> Option<&[KeyEvent]> {
+        self.last_change.as_deref()
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use xi_rope::Transformer;
+
+use crate::byte_rope::RopeDelta;
+
+/// Named file offsets, set with `m<letter>` and jumped to with `'<letter>`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Marks {
+    offsets: BTreeMap<char, usize>,
+}
+
+/// Moves file offsets along with the bytes they point at, the same way
+/// selections follow edits to the loaded window, which starts at `start` and is
+/// `max_len` bytes long. An offset on a deleted byte moves to the byte after it.
+pub fn transform_offsets<'a>(
+    offsets: impl Iterator<Item = &'a mut usize>,
+    delta: &RopeDelta,
+    start: usize,
+    max_len: usize,
+) {
+    let new_max_len = delta.new_document_len();
+    let mut transformer = Transformer::new(delta);
+    for offset in offsets {
+        if *offset >= start + max_len {
+            *offset = *offset + new_max_len - max_len;
+        } else if *offset >= start {
+            let moved = transformer.transform(*offset - start, true);
+            *offset = start + std::cmp::min(new_max_len, moved);
+        }
+    }
+}
+
+/// Marks for `foo.bin` are kept next to it in `.foo.bin.tofu-marks`.
+pub fn sidecar_path(path: &Path) -> Option<PathBuf> {
+    let file_name = path.file_name()?.to_string_lossy();
+    Some(path.with_file_name(format!(".{}.tofu-marks", file_name)))
+}
+
+impl Marks {
+    pub fn new() -> Marks {
+        Default::default()
+    }
+
+    pub fn is_valid_name(name: char) -> bool {
+        name.is_ascii_alphabetic()
+    }
+
+    pub fn set(&mut self, name: char, offset: usize) {
+        self.offsets.insert(name, offset);
+    }
+
+    pub fn remove(&mut self, name: char) -> bool {
+        self.offsets.remove(&name).is_some()
+    }
+
+    pub fn get(&self, name: char) -> Option<usize> {
+        self.offsets.get(&name).copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (char, usize)> + '_ {
+        self.offsets.iter().map(|(&name, &offset)| (name, offset))
+    }
+
+    pub fn contains_offset(&self, offset: usize) -> bool {
+        self.offsets.values().any(|&x| x == offset)
+    }
+
+    pub fn apply_delta(&mut self, delta: &RopeDelta, start: usize, max_len: usize) {
+        transform_offsets(self.offsets.values_mut(), delta, start, max_len);
+    }
+
+    /// Reads the marks saved for `path`. A missing sidecar just means no marks.
+    pub fn load(path: &Path) -> io::Result<Marks> {
+        let sidecar = match sidecar_path(path) {
+            Some(sidecar) => sidecar,
+            None => return Ok(Marks::new()),
+        };
+        match fs::read_to_string(sidecar) {
+            Ok(text) => Ok(Marks::parse(&text)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Marks::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Writes the marks for `path`, removing the sidecar once no marks are left.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let sidecar = match sidecar_path(path) {
+            Some(sidecar) => sidecar,
+            None => return Ok(()),
+        };
+        if !self.is_empty() {
+            return fs::write(sidecar, self.format());
+        }
+        match fs::remove_file(sidecar) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    // One `<name> <hex offset>` pair per line; anything else is skipped.
+    fn parse(text: &str) -> Marks {
+        let mut marks = Marks::new();
+        for line in text.lines() {
+            let mut parts = line.split_whitespace();
+            let name = parts.next().and_then(|name| {
+                let mut chars = name.chars();
+                chars.next().filter(|_| chars.next().is_none())
+            });
+            let offset = parts.next().and_then(|x| usize::from_str_radix(x, 16).ok());
+            if let (Some(name), Some(offset)) = (name, offset) {
+                if Marks::is_valid_name(name) {
+                    marks.set(name, offset);
+                }
+            }
+        }
+        marks
+    }
+
+    fn format(&self) -> String {
+        self.iter()
+            .map(|(name, offset)| format!("{} {:x}\n", name, offset))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::byte_rope::Rope;
+    use xi_rope::DeltaBuilder;
+
+    #[test]
+    fn test_marks_follow_edits() {
+        let base: Rope = vec![0, 1, 2, 3, 4].into();
+        let mut marks = Marks::new();
+        marks.set('a', 1);
+        marks.set('b', 3);
+
+        let mut builder = DeltaBuilder::new(base.len());
+        builder.replace(1..1, Into::<Rope>::into(vec![9, 9]).into_node());
+        builder.delete(2..3);
+        marks.apply_delta(&builder.build(), 0, base.len());
+
+        assert_eq!(marks.get('a'), Some(3));
+        assert_eq!(marks.get('b'), Some(4));
+    }
+
+    #[test]
+    fn test_marks_outside_the_window() {
+        let base: Rope = vec![0, 1, 2, 3].into();
+        let mut marks = Marks::new();
+        marks.set('a', 5);
+        marks.set('b', 0x12);
+        marks.set('c', 0x20);
+
+        // The window holds offsets 0x10 to 0x14 of the file
+        let mut builder = DeltaBuilder::new(base.len());
+        builder.delete(0..2);
+        marks.apply_delta(&builder.build(), 0x10, base.len());
+
+        assert_eq!(marks.get('a'), Some(5));
+        assert_eq!(marks.get('b'), Some(0x10));
+        assert_eq!(marks.get('c'), Some(0x1e));
+    }
+
+    #[test]
+    fn test_format_roundtrip() {
+        let mut marks = Marks::new();
+        marks.set('a', 0x1f);
+        marks.set('Z', 0);
+        assert_eq!(marks.format(), "Z 0\na 1f\n");
+        assert_eq!(Marks::parse(&marks.format()), marks);
+        assert_eq!(Marks::parse("ab 1\n# 2\nc zz\nd 10"), {
+            let mut only_d = Marks::new();
+            only_d.set('d', 0x10);
+            only_d
+        });
+    }
+}
@@ -0,0 +1,184 @@
+use std::cmp;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use crate::theme::ByteClass;
+
+// Files are summarized in at most this many blocks, of at least `MIN_BLOCK_SIZE`
+const MAX_BLOCKS: usize = 4096;
+const MIN_BLOCK_SIZE: usize = 1024;
+
+/// What a block of the file looks like: its entropy in bits per byte and how many
+/// of its bytes are in each class.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Block {
+    entropy: f32,
+    classes: [u32; 5],
+}
+
+impl Block {
+    fn of(bytes: &[u8]) -> Block {
+        let mut counts = [0u32; 256];
+        for &byte in bytes {
+            counts[byte as usize] += 1;
+        }
+        let len = bytes.len() as f32;
+        let mut entropy = 0.0;
+        let mut classes = [0u32; 5];
+        for (byte, &count) in counts.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            let p = count as f32 / len;
+            entropy -= p * p.log2();
+            classes[ByteClass::of(byte as u8) as usize] += count;
+        }
+        Block { entropy, classes }
+    }
+}
+
+/// What a stretch of the file looks like, for one row of the minimap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    /// Mean entropy of the blocks, from 0 to 8 bits per byte.
+    pub entropy: f32,
+    /// The class most of the bytes are in.
+    pub class: ByteClass,
+}
+
+/// Block summaries of a file on disk, read on a background thread so that large
+/// files don't hold up the editor. `poll` picks up the blocks read so far.
+#[derive(Debug)]
+pub struct Minimap {
+    path: PathBuf,
+    len: usize,
+    block_size: usize,
+    blocks: Vec<Block>,
+    receiver: Receiver<Block>,
+    done: bool,
+}
+
+impl Minimap {
+    pub fn scan(path: &Path) -> std::io::Result<Minimap> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len() as usize;
+        let block_size = cmp::max(MIN_BLOCK_SIZE, (len + MAX_BLOCKS - 1) / MAX_BLOCKS);
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(file);
+            let mut block = Vec::with_capacity(block_size);
+            loop {
+                block.clear();
+                match (&mut reader)
+                    .take(block_size as u64)
+                    .read_to_end(&mut block)
+                {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {
+                        // Stops once the minimap is dropped
+                        if sender.send(Block::of(&block)).is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+        Ok(Minimap {
+            path: path.to_owned(),
+            len,
+            block_size,
+            blocks: vec![],
+            receiver,
+            done: false,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The length of the file when the scan started.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Takes the blocks read since the last call, returning whether there were any.
+    pub fn poll(&mut self) -> bool {
+        let before = self.blocks.len();
+        loop {
+            match self.receiver.try_recv() {
+                Ok(block) => self.blocks.push(block),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.done = true;
+                    break;
+                }
+            }
+        }
+        self.blocks.len() != before
+    }
+
+    /// Sums up the blocks `range` falls in, or `None` if none are read yet.
+    pub fn summary(&self, range: Range<usize>) -> Option<Summary> {
+        let first = range.start / self.block_size;
+        let last = cmp::max(
+            (range.end + self.block_size - 1) / self.block_size,
+            first + 1,
+        );
+        let blocks = self.blocks.get(first..last.min(self.blocks.len()))?;
+        if blocks.is_empty() {
+            return None;
+        }
+        let entropy = blocks.iter().map(|block| block.entropy).sum::<f32>() / blocks.len() as f32;
+        let class = ByteClass::ALL
+            .into_iter()
+            .max_by_key(|&class| {
+                blocks
+                    .iter()
+                    .map(|block| block.classes[class as usize])
+                    .sum::<u32>()
+            })
+            .unwrap();
+        Some(Summary { entropy, class })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimap_scan() {
+        let path = std::env::temp_dir().join(format!("tofu-minimap-{}", std::process::id()));
+        let mut data = vec![0u8; 4096];
+        data.extend((0..4096).map(|i| (i % 256) as u8));
+        data.extend(b"hello world ".iter().cycle().take(4096));
+        std::fs::write(&path, &data).unwrap();
+
+        let mut minimap = Minimap::scan(&path).unwrap();
+        while !minimap.is_done() {
+            minimap.poll();
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(minimap.len(), data.len());
+        let zeros = minimap.summary(0..4096).unwrap();
+        assert_eq!(zeros.class, ByteClass::Null);
+        assert_eq!(zeros.entropy, 0.0);
+        let counting = minimap.summary(4096..8192).unwrap();
+        assert!((counting.entropy - 8.0).abs() < 0.01);
+        assert_eq!(
+            minimap.summary(8192..12288).unwrap().class,
+            ByteClass::Printable
+        );
+        assert_eq!(minimap.summary(20000..30000), None);
+    }
+}
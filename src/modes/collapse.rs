@@ -19,7 +19,10 @@ impl SearchAcceptor for Collapse {
         if pattern.pieces.is_empty() {
             return ModeTransition::new_mode(Normal::new());
         }
-        let matched_ranges = pattern.map_selections_to_matches(current_buffer);
+        let matched_ranges = match pattern.map_selections_to_matches(current_buffer) {
+            Ok(matched_ranges) => matched_ranges,
+            Err(err) => return ModeTransition::new_mode_and_info(Normal::new(), err),
+        };
         let matched_len: usize = matched_ranges
             .iter()
             .flatten()
@@ -31,6 +34,8 @@ impl SearchAcceptor for Collapse {
             return ModeTransition::new_mode(Normal::new());
         }
 
+        buffr_collection.record_jump();
+        let current_buffer = buffr_collection.current_mut();
         let mut remaining_matched_ranges = &matched_ranges[..];
         ModeTransition::new_mode_and_dirty(
             Normal::new(),
@@ -4,9 +4,8 @@ use std::fs;
 
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
 use lazy_static::lazy_static;
-use maplit::hashmap;
 
-use crate::keymap::KeyMap;
+use crate::keymap::{self, KeyMap};
 use crate::modes::{
     mode::{Mode, ModeTransition},
     normal::Normal,
@@ -45,6 +44,9 @@ mod cmd {
     use super::*;
     use crate::modes::mode::DirtyBytes;
     use crate::modes::quitting;
+    use crate::operations as ops;
+    use crate::options::Options;
+    use crate::panes::SplitDirection;
 
     pub fn quit(buf: &mut BuffrCollection, _: &str) -> ModeTransition {
         if buf.iter().any(|x| x.dirty && x.path.is_some()) {
@@ -69,16 +71,21 @@ mod cmd {
         };
 
         if let Some(path) = path {
-            if let Err(e) = fs::write(&path, buf.current().data.slice_to_cow(..)) {
+            let owned_path = path.to_owned();
+            let buf_mut = buf.current_mut();
+            if let Err(e) = buf_mut.write_to(&owned_path) {
                 return ModeTransition::new_mode_and_info(
                     Normal::new(),
                     format!("write failed: {}", e),
                 );
             }
-
-            let owned_path = path.to_owned();
-            let buf_mut = buf.current_mut();
             buf_mut.dirty = false;
+            if let Err(e) = buf_mut.marks.save(&owned_path) {
+                return ModeTransition::new_mode_and_info(
+                    Normal::new(),
+                    format!("saving marks failed: {}", e),
+                );
+            }
             buf_mut.update_path_if_missing(owned_path);
             ModeTransition::new_mode(Normal::new())
         } else {
@@ -86,16 +93,106 @@ mod cmd {
         }
     }
 
+    pub fn write_selection(buf: &mut BuffrCollection, filename: &str) -> ModeTransition {
+        if filename.is_empty() {
+            return ModeTransition::new_mode_and_info(Normal::new(), "no filename given".into());
+        }
+
+        let contents = buf.current().selected_bytes().concat();
+        if let Err(e) = fs::write(filename, &contents) {
+            return ModeTransition::new_mode_and_info(
+                Normal::new(),
+                format!("write failed: {}", e),
+            );
+        }
+        ModeTransition::new_mode_and_info(
+            Normal::new(),
+            format!("wrote 0x{:x} bytes to {}", contents.len(), filename),
+        )
+    }
+
+    pub fn write_selections(buf: &mut BuffrCollection, filename: &str) -> ModeTransition {
+        if filename.is_empty() {
+            return ModeTransition::new_mode_and_info(Normal::new(), "no filename given".into());
+        }
+
+        let selections = buf.current().selected_bytes();
+        // Selection indexing in the UI starts at 1, so do the file suffixes
+        for (i, contents) in selections.iter().enumerate() {
+            let path = format!("{}.{}", filename, i + 1);
+            if let Err(e) = fs::write(&path, contents) {
+                return ModeTransition::new_mode_and_info(
+                    Normal::new(),
+                    format!("write to {} failed: {}", path, e),
+                );
+            }
+        }
+        ModeTransition::new_mode_and_info(
+            Normal::new(),
+            format!(
+                "wrote {} files {}.1..{}.{}",
+                selections.len(),
+                filename,
+                filename,
+                selections.len()
+            ),
+        )
+    }
+
+    fn read_into(buf: &mut BuffrCollection, filename: &str, overwrite: bool) -> ModeTransition {
+        if filename.is_empty() {
+            return ModeTransition::new_mode_and_info(Normal::new(), "no filename given".into());
+        }
+
+        let contents = match fs::read(filename) {
+            Ok(contents) => contents,
+            Err(e) => {
+                return ModeTransition::new_mode_and_info(
+                    Normal::new(),
+                    format!("read failed: {}", e),
+                )
+            }
+        };
+        if contents.is_empty() {
+            return ModeTransition::new_mode_and_info(
+                Normal::new(),
+                format!("{} is empty", filename),
+            );
+        }
+
+        let current_buffer = buf.current_mut();
+        let delta = if overwrite {
+            ops::overwrite(&current_buffer.data, &current_buffer.selection, contents)
+        } else {
+            ops::insert(&current_buffer.data, &current_buffer.selection, contents)
+        };
+        ModeTransition::new_mode_and_dirty(Normal::new(), current_buffer.apply_delta(delta))
+    }
+
+    pub fn read_insert(buf: &mut BuffrCollection, filename: &str) -> ModeTransition {
+        read_into(buf, filename, false)
+    }
+
+    pub fn read_overwrite(buf: &mut BuffrCollection, filename: &str) -> ModeTransition {
+        read_into(buf, filename, true)
+    }
+
     pub fn write_all(buffr_collection: &mut BuffrCollection, _: &str) -> ModeTransition {
         for buf in buffr_collection.iter_mut() {
-            if let Some(path) = buf.path.as_ref() {
-                if let Err(e) = fs::write(&path, buf.data.slice_to_cow(..)) {
+            if let Some(path) = buf.path.clone() {
+                if let Err(e) = buf.write_to(&path) {
                     return ModeTransition::new_mode_and_info(
                         Normal::new(),
                         format!("write failed: {}", e),
                     );
                 }
                 buf.dirty = false;
+                if let Err(e) = buf.marks.save(&path) {
+                    return ModeTransition::new_mode_and_info(
+                        Normal::new(),
+                        format!("saving marks failed: {}", e),
+                    );
+                }
             }
         }
         ModeTransition::new_mode(Normal::new())
@@ -103,14 +200,20 @@ mod cmd {
 
     pub fn write_quit(buffr_collection: &mut BuffrCollection, _: &str) -> ModeTransition {
         for buf in buffr_collection.iter_mut() {
-            if let Some(path) = buf.path.as_ref() {
-                if let Err(e) = fs::write(&path, buf.data.slice_to_cow(..)) {
+            if let Some(path) = buf.path.clone() {
+                if let Err(e) = buf.write_to(&path) {
                     return ModeTransition::new_mode_and_info(
                         Normal::new(),
                         format!("write failed: {}", e),
                     );
                 }
                 buf.dirty = false;
+                if let Err(e) = buf.marks.save(&path) {
+                    return ModeTransition::new_mode_and_info(
+                        Normal::new(),
+                        format!("saving marks failed: {}", e),
+                    );
+                }
             }
         }
         ModeTransition::new_mode(quitting::Quitting {})
@@ -139,41 +242,331 @@ mod cmd {
         buffr_collection.delete_current();
         ModeTransition::new_mode_and_dirty(Normal::new(), DirtyBytes::ChangeLength)
     }
+
+    pub fn list_buffers(buffr_collection: &mut BuffrCollection, _: &str) -> ModeTransition {
+        let listing = buffr_collection
+            .iter()
+            .enumerate()
+            .map(|(i, buf)| {
+                let flag = if i == buffr_collection.current_index() {
+                    "%"
+                } else if Some(i) == buffr_collection.alternate_index() {
+                    "#"
+                } else {
+                    " "
+                };
+                format!(
+                    "{}{} {}{} 0x{:x}",
+                    i + 1,
+                    flag,
+                    buf.name(),
+                    if buf.dirty { "[+]" } else { "" },
+                    buf.total_len()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" | ");
+        ModeTransition::new_mode_and_info(Normal::new(), listing)
+    }
+
+    pub fn switch_buffer(buffr_collection: &mut BuffrCollection, target: &str) -> ModeTransition {
+        let target = target.trim();
+        let switched = if target.is_empty() || target == "#" {
+            buffr_collection.switch_to_alternate()
+        } else if let Ok(index) = target.parse::<usize>() {
+            index > 0 && buffr_collection.switch_to_index(index - 1)
+        } else if let Some(index) = buffr_collection.find_by_name(target) {
+            buffr_collection.switch_to_index(index)
+        } else {
+            false
+        };
+
+        if switched {
+            ModeTransition::new_mode_and_dirty(Normal::new(), DirtyBytes::ChangeLength)
+        } else {
+            ModeTransition::new_mode_and_info(Normal::new(), format!("no such buffer: {}", target))
+        }
+    }
+
+    pub fn list_registers(buffr_collection: &mut BuffrCollection, _: &str) -> ModeTransition {
+        const PREVIEW_LEN: usize = 8;
+
+        let registers = buffr_collection.registers();
+        if registers.is_empty() {
+            return ModeTransition::new_mode_and_info(Normal::new(), "no registers set".into());
+        }
+
+        let listing = registers
+            .iter()
+            .map(|(name, contents)| {
+                let first = contents.first().map(Vec::as_slice).unwrap_or(&[]);
+                let shown = &first[..std::cmp::min(PREVIEW_LEN, first.len())];
+                let ellipsis = if first.len() > PREVIEW_LEN { "…" } else { "" };
+                let hex = shown
+                    .iter()
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let ascii = shown
+                    .iter()
+                    .map(|&byte| {
+                        if byte.is_ascii_graphic() || byte == b' ' {
+                            byte as char
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect::<String>();
+                format!(
+                    "\"{} [{}] {}{} |{}{}|",
+                    name,
+                    contents.len(),
+                    hex,
+                    ellipsis,
+                    ascii,
+                    ellipsis
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        ModeTransition::new_mode_and_info(Normal::new(), listing)
+    }
+
+    pub fn list_marks(buffr_collection: &mut BuffrCollection, _: &str) -> ModeTransition {
+        let marks = &buffr_collection.current().marks;
+        if marks.is_empty() {
+            return ModeTransition::new_mode_and_info(Normal::new(), "no marks set".into());
+        }
+
+        let listing = marks
+            .iter()
+            .map(|(name, offset)| format!("'{} 0x{:x}", name, offset))
+            .collect::<Vec<_>>()
+            .join("  ");
+        ModeTransition::new_mode_and_info(Normal::new(), listing)
+    }
+
+    pub fn delete_marks(buffr_collection: &mut BuffrCollection, names: &str) -> ModeTransition {
+        let current_buffer = buffr_collection.current_mut();
+        for name in names.chars().filter(|ch| !ch.is_whitespace()) {
+            if !current_buffer.marks.remove(name) {
+                return ModeTransition::new_mode_and_info(
+                    Normal::new(),
+                    format!("no mark {}", name),
+                );
+            }
+        }
+        match current_buffer.save_marks_if_clean() {
+            Ok(()) => ModeTransition::new_mode_and_dirty(Normal::new(), DirtyBytes::ChangeLength),
+            Err(e) => ModeTransition::new_mode_and_info(
+                Normal::new(),
+                format!("saving marks failed: {}", e),
+            ),
+        }
+    }
+
+    pub fn set_option(buffr_collection: &mut BuffrCollection, args: &str) -> ModeTransition {
+        let args = args.trim();
+        let options = buffr_collection.options_mut();
+        if args.is_empty() {
+            let listing = Options::NAMES
+                .iter()
+                .map(|name| format!("{}={}", name, options.get(name).unwrap_or_default()))
+                .collect::<Vec<_>>()
+                .join("  ");
+            return ModeTransition::new_mode_and_info(Normal::new(), listing);
+        }
+
+        let (name, value) = match args.find(['=', ' ']) {
+            Some(i) => (&args[..i], args[i + 1..].trim()),
+            None => {
+                return ModeTransition::new_mode_and_info(
+                    Normal::new(),
+                    match options.get(args) {
+                        Some(value) => format!("{}={}", args, value),
+                        None => format!("unknown option {}", args),
+                    },
+                )
+            }
+        };
+        match options.set(name, value) {
+            Ok(()) => ModeTransition::new_mode_and_dirty(Normal::new(), DirtyBytes::ChangeLength),
+            Err(e) => ModeTransition::new_mode_and_info(Normal::new(), e),
+        }
+    }
+
+    pub fn define_macro(buffr_collection: &mut BuffrCollection, args: &str) -> ModeTransition {
+        let mut chars = args.chars();
+        let register = match chars.next() {
+            Some(register) if register.is_ascii_graphic() => register,
+            _ => {
+                return ModeTransition::new_mode_and_info(
+                    Normal::new(),
+                    "usage: macro <register> <keys>".into(),
+                )
+            }
+        };
+        let keys = chars.as_str().trim_start();
+        if let Err(e) = keymap::parse_keys(keys) {
+            return ModeTransition::new_mode_and_info(Normal::new(), e);
+        }
+        match buffr_collection
+            .registers_mut()
+            .set(register, vec![keys.as_bytes().to_vec()])
+        {
+            Ok(()) => ModeTransition::new_mode(Normal::new()),
+            Err(e) => ModeTransition::new_mode_and_info(Normal::new(), e),
+        }
+    }
+
+    pub fn next_buffer(buffr_collection: &mut BuffrCollection, _: &str) -> ModeTransition {
+        buffr_collection.switch_to_next(1);
+        ModeTransition::new_mode_and_dirty(Normal::new(), DirtyBytes::ChangeLength)
+    }
+
+    pub fn prev_buffer(buffr_collection: &mut BuffrCollection, _: &str) -> ModeTransition {
+        buffr_collection.switch_to_prev(1);
+        ModeTransition::new_mode_and_dirty(Normal::new(), DirtyBytes::ChangeLength)
+    }
+
+    fn split(
+        buffr_collection: &mut BuffrCollection,
+        filename: &str,
+        direction: SplitDirection,
+    ) -> ModeTransition {
+        buffr_collection.split_pane(direction);
+        if filename.trim().is_empty() {
+            return ModeTransition::new_mode_and_dirty(Normal::new(), DirtyBytes::ChangeLength);
+        }
+        edit(buffr_collection, filename)
+    }
+
+    pub fn split_horizontal(
+        buffr_collection: &mut BuffrCollection,
+        filename: &str,
+    ) -> ModeTransition {
+        split(buffr_collection, filename, SplitDirection::Horizontal)
+    }
+
+    pub fn split_vertical(
+        buffr_collection: &mut BuffrCollection,
+        filename: &str,
+    ) -> ModeTransition {
+        split(buffr_collection, filename, SplitDirection::Vertical)
+    }
+
+    pub fn close_pane(buffr_collection: &mut BuffrCollection, _: &str) -> ModeTransition {
+        if buffr_collection.close_pane() {
+            ModeTransition::new_mode_and_dirty(Normal::new(), DirtyBytes::ChangeLength)
+        } else {
+            ModeTransition::new_mode_and_info(Normal::new(), "can't close the last pane".to_owned())
+        }
+    }
+
+    pub fn only_pane(buffr_collection: &mut BuffrCollection, _: &str) -> ModeTransition {
+        buffr_collection.only_pane();
+        ModeTransition::new_mode_and_dirty(Normal::new(), DirtyBytes::ChangeLength)
+    }
+
+    pub fn help(_: &mut BuffrCollection, _: &str) -> ModeTransition {
+        ModeTransition::new_mode_and_dirty(
+            crate::modes::help::Help::new(),
+            DirtyBytes::ChangeLength,
+        )
+    }
 }
 
 type CommandHandler = fn(&mut BuffrCollection, &str) -> ModeTransition;
 
+/// A command, the names it can be run by and a line of help for `:help`.
+struct CommandSpec {
+    names: &'static [&'static str],
+    handler: CommandHandler,
+    help: &'static str,
+}
+
 macro_rules! make_commands {
-    ($($string:tt => $cmd:ident,)*) => {
-        hashmap![
-            $($string.to_string() => (cmd::$cmd as CommandHandler),)*
+    ($($($string:literal)|+ => $cmd:ident: $help:literal,)*) => {
+        vec![
+            $(CommandSpec {
+                names: &[$($string),+],
+                handler: cmd::$cmd as CommandHandler,
+                help: $help,
+            },)*
         ]
     }
 }
 
-fn default_commands() -> HashMap<String, CommandHandler> {
+fn default_commands() -> Vec<CommandSpec> {
     make_commands![
-        "q" => quit,
-        "quit" => quit,
-        "q!" => force_quit,
-        "quit!" => force_quit,
-        "w" => write,
-        "write" => write,
-        "wq" => write_quit,
-        "wa" => write_all,
-        "write-all" => write_all,
-        "e" => edit,
-        "edit" => edit,
-        "db" => delete_current_buffer,
-        "delete-current_buffer" => delete_current_buffer,
-        "db!" => force_delete_current_buffer,
-        "delete-current_buffer!" => force_delete_current_buffer,
+        "q" | "quit" => quit: "quit, unless there are unsaved changes",
+        "q!" | "quit!" => force_quit: "quit, discarding unsaved changes",
+        "w" | "write" => write: "write the buffer, optionally to the given file",
+        "ws" | "write-selection" => write_selection: "write the selected bytes to a file",
+        "wss" | "write-selections" => write_selections: "write each selection to its own file",
+        "r" | "read" => read_insert: "insert a file's contents at each cursor",
+        "ro" | "read-overwrite" => read_overwrite: "overwrite with a file's contents at each cursor",
+        "wq" => write_quit: "write all buffers, then quit",
+        "wa" | "write-all" => write_all: "write all buffers",
+        "e" | "edit" => edit: "open a file in a new buffer",
+        "db" | "delete-current_buffer" => delete_current_buffer: "close the buffer",
+        "db!" | "delete-current_buffer!" => force_delete_current_buffer: "close the buffer, discarding changes",
+        "set" => set_option: "show or change options",
+        "macro" => define_macro: "store keys as a macro in a register",
+        "reg" | "registers" => list_registers: "list register contents",
+        "marks" => list_marks: "list marks and their offsets",
+        "delm" | "delmarks" => delete_marks: "delete the given marks",
+        "ls" | "buffers" => list_buffers: "list open buffers",
+        "b" | "buffer" => switch_buffer: "switch to a buffer by number or name",
+        "bn" | "buffer-next" => next_buffer: "switch to the next buffer",
+        "bp" | "buffer-prev" => prev_buffer: "switch to the previous buffer",
+        "sp" | "split" => split_horizontal: "split the pane in two, one above the other, optionally opening a file",
+        "vs" | "vsplit" => split_vertical: "split the pane in two, side by side, optionally opening a file",
+        "clo" | "close" => close_pane: "close the pane",
+        "on" | "only" => only_pane: "close every other pane",
+        "help" => help: "show the key and command reference",
     ]
 }
 
 lazy_static! {
     static ref DEFAULT_MAPS: KeyMap<Action> = default_maps();
-    static ref DEFAULT_COMMANDS: HashMap<String, CommandHandler> = default_commands();
+    static ref COMMAND_SPECS: Vec<CommandSpec> = default_commands();
+    static ref DEFAULT_COMMANDS: HashMap<&'static str, CommandHandler> = COMMAND_SPECS
+        .iter()
+        .flat_map(|spec| spec.names.iter().map(move |&name| (name, spec.handler)))
+        .collect();
+}
+
+pub fn help() -> Vec<(String, String)> {
+    DEFAULT_MAPS.describe(|action| {
+        Some(
+            match action {
+                Action::RemoveLast => "delete the character before the cursor",
+                Action::RemoveThis => "delete the character under the cursor",
+                Action::CursorLeft => "move the cursor left",
+                Action::CursorRight => "move the cursor right",
+                Action::Finish => "run the command",
+                Action::Cancel => "cancel",
+            }
+            .into(),
+        )
+    })
+}
+
+/// Rows of `(names, description)` for every command, for the help view.
+pub fn command_help() -> Vec<(String, String)> {
+    COMMAND_SPECS
+        .iter()
+        .map(|spec| {
+            let names = spec
+                .names
+                .iter()
+                .map(|name| format!(":{}", name))
+                .collect::<Vec<_>>()
+                .join(" ");
+            (names, spec.help.to_owned())
+        })
+        .collect()
 }
 
 impl Command {
@@ -185,14 +578,20 @@ impl Command {
     }
 
     fn finish(&self, buffr_collection: &mut BuffrCollection) -> ModeTransition {
-        let (name, rest) = self
-            .command
-            .split_at(self.command.find(' ').unwrap_or_else(|| self.command.len()));
-        if let Some(handler) = DEFAULT_COMMANDS.get(name) {
-            handler(buffr_collection, if rest.is_empty() { rest } else { &rest[1..] })
-        } else {
-            ModeTransition::new_mode_and_info(Normal::new(), format!("Unknown command {}", name))
-        }
+        execute(buffr_collection, &self.command)
+    }
+}
+
+/// Runs a command line as if it had been typed after `:`.
+pub fn execute(buffr_collection: &mut BuffrCollection, command: &str) -> ModeTransition {
+    let (name, rest) = command.split_at(command.find(' ').unwrap_or(command.len()));
+    if let Some(handler) = DEFAULT_COMMANDS.get(name) {
+        handler(
+            buffr_collection,
+            if rest.is_empty() { rest } else { &rest[1..] },
+        )
+    } else {
+        ModeTransition::new_mode_and_info(Normal::new(), format!("Unknown command {}", name))
     }
 }
 
@@ -0,0 +1,125 @@
+use std::borrow::Cow;
+
+use crossterm::event::Event;
+
+use crate::modes::mode::DirtyBytes;
+use crate::modes::search::{Pattern, SearchAcceptor};
+use crate::modes::{
+    mode::{Mode, ModeTransition},
+    normal::Normal,
+};
+use crate::selection::SelRegion;
+use crate::BuffrCollection;
+
+/// Holds the patterns of past `/` and `?` searches, most recent first.
+pub const SEARCH_REGISTER: char = '/';
+
+/// How many past search patterns are remembered.
+pub const SEARCH_HISTORY_SIZE: usize = 50;
+
+/// Searches for a pattern from the main selection, as started by `/` and `?`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Find {
+    pub forward: bool,
+}
+
+impl SearchAcceptor for Find {
+    fn apply_search(
+        &self,
+        pattern: Pattern,
+        buffr_collection: &mut BuffrCollection,
+        _: usize,
+    ) -> ModeTransition {
+        if pattern.pieces.is_empty() {
+            return ModeTransition::new_mode(Normal::new());
+        }
+        buffr_collection.remember_search(pattern.clone(), self.forward);
+        find_next(buffr_collection, &pattern, self.forward, 1)
+    }
+
+    fn forward(&self) -> bool {
+        self.forward
+    }
+}
+
+impl Mode for Find {
+    fn name(&self) -> Cow<'static, str> {
+        "SEARCH".into()
+    }
+
+    fn transition(&self, _: &Event, _: &mut BuffrCollection, _: usize) -> Option<ModeTransition> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// Moves the main selection onto the `count`th match after it, or before it when
+/// not `forward`, wrapping around the ends of the loaded window. Only the
+/// loaded window is searched, which the status message says when it isn't the
+/// whole buffer.
+pub fn find_next(
+    buffr_collection: &mut BuffrCollection,
+    pattern: &Pattern,
+    forward: bool,
+    count: usize,
+) -> ModeTransition {
+    let buf = buffr_collection.current();
+    let partial = buf.data_start_offset > 0 || buf.total_len() > buf.data.len();
+    let scope = if partial { " of the loaded bytes" } else { "" };
+    let matches = match pattern.find_all(&buf.data.slice_to_cow(..)) {
+        Ok(matches) => matches,
+        Err(err) => return ModeTransition::new_mode_and_info(Normal::new(), err),
+    };
+    if matches.is_empty() {
+        let info = if partial {
+            "no matches in the loaded bytes"
+        } else {
+            "no matches"
+        };
+        return ModeTransition::new_mode_and_info(Normal::new(), info.to_owned());
+    }
+
+    let main = buffr_collection.current().selection.main();
+    let mut start = main.min();
+    let mut wrapped = false;
+    let mut found = 0;
+    for _ in 0..count {
+        found = if forward {
+            match matches.partition_point(|m| m.start <= start) {
+                i if i == matches.len() => {
+                    wrapped = true;
+                    0
+                }
+                i => i,
+            }
+        } else {
+            match matches.partition_point(|m| m.start < start) {
+                0 => {
+                    wrapped = true;
+                    matches.len() - 1
+                }
+                i => i - 1,
+            }
+        };
+        start = matches[found].start;
+    }
+
+    buffr_collection.record_jump();
+    let found = &matches[found];
+    let region = SelRegion::new(found.end - 1, found.start).inherit_direction(&main);
+    buffr_collection.current_mut().selection.move_main(region);
+    let dirty = DirtyBytes::ChangeInPlace(vec![
+        (main.min()..main.max() + 1).into(),
+        found.clone().into(),
+    ]);
+    if wrapped {
+        let end = if forward { "top" } else { "bottom" };
+        let info = format!("search wrapped to the {}{}", end, scope);
+        ModeTransition::new_mode_dirty_and_info(Normal::new(), dirty, info)
+    } else {
+        ModeTransition::new_mode_and_dirty(Normal::new(), dirty)
+    }
+}
@@ -0,0 +1,171 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use lazy_static::lazy_static;
+
+use crate::keymap::KeyMap;
+use crate::modes::{
+    command, insert, inspect, jumpto,
+    mode::{DirtyBytes, Mode, ModeTransition},
+    normal::{self, Normal},
+    replace, search, split,
+};
+use crate::BuffrCollection;
+
+// The mode doesn't know the terminal height, so paging moves a fixed amount
+const PAGE_LINES: usize = 10;
+const MAX_KEYS_WIDTH: usize = 24;
+
+/// A scrollable reference of the key bindings and commands.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Help {
+    pub scroll: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Action {
+    Down(usize),
+    Up(usize),
+    Top,
+    Bottom,
+    Close,
+}
+
+fn default_maps() -> KeyMap<Action> {
+    KeyMap {
+        maps: keys!(
+            ('j' => Action::Down(1)),
+            (key KeyCode::Down => Action::Down(1)),
+            ('k' => Action::Up(1)),
+            (key KeyCode::Up => Action::Up(1)),
+            (' ' => Action::Down(PAGE_LINES)),
+            (key KeyCode::PageDown => Action::Down(PAGE_LINES)),
+            (key KeyCode::PageUp => Action::Up(PAGE_LINES)),
+            ('g' => Action::Top),
+            (key KeyCode::Home => Action::Top),
+            ('G' => Action::Bottom),
+            (key KeyCode::End => Action::Bottom),
+            ('q' => Action::Close),
+            (key KeyCode::Esc => Action::Close),
+            (key KeyCode::F(1) => Action::Close)
+        ),
+    }
+}
+
+lazy_static! {
+    static ref DEFAULT_MAPS: KeyMap<Action> = default_maps();
+}
+
+fn push_section(lines: &mut Vec<String>, title: &str, rows: &[(String, String)]) {
+    let width = rows
+        .iter()
+        .map(|(keys, _)| keys.chars().count())
+        .filter(|&width| width <= MAX_KEYS_WIDTH)
+        .max()
+        .unwrap_or(0);
+    lines.push(title.to_owned());
+    for (keys, description) in rows {
+        lines.push(format!("  {:width$}  {}", keys, description, width = width));
+    }
+    lines.push(String::new());
+}
+
+impl Help {
+    pub fn new() -> Help {
+        Help { scroll: 0 }
+    }
+
+    /// The help text, generated from the key maps and command table in use.
+    pub fn lines() -> Vec<String> {
+        let view_keys = [
+            ("<c-e>".to_owned(), "scroll down".to_owned()),
+            ("<c-y>".to_owned(), "scroll up".to_owned()),
+        ];
+        let count_keys = [
+            ("0-9".to_owned(), "count for the next command".to_owned()),
+            (
+                "x".to_owned(),
+                "switch the count between hex and decimal".to_owned(),
+            ),
+        ];
+
+        let mut lines = vec![
+            "tofu help (j/k to scroll, q to close)".to_owned(),
+            String::new(),
+        ];
+        push_section(&mut lines, "Normal mode", &normal::help());
+        push_section(&mut lines, "Counts", &count_keys);
+        push_section(&mut lines, "View", &view_keys);
+        push_section(&mut lines, "Jump (g, G)", &jumpto::help());
+        push_section(&mut lines, "Split (<a-s>)", &split::help());
+        push_section(&mut lines, "Insert (i, a, o, c)", &insert::help());
+        push_section(&mut lines, "Inspector (<a-i>)", &inspect::help());
+        push_section(&mut lines, "Replace (r, R)", &replace::help());
+        push_section(&mut lines, "Pattern entry (s, S)", &search::help());
+        push_section(&mut lines, "Command line (:)", &command::help());
+        push_section(&mut lines, "Commands", &command::command_help());
+        lines
+    }
+}
+
+impl Default for Help {
+    fn default() -> Self {
+        Help::new()
+    }
+}
+
+impl Mode for Help {
+    fn name(&self) -> Cow<'static, str> {
+        "HELP".into()
+    }
+
+    fn transition(&self, evt: &Event, _: &mut BuffrCollection, _: usize) -> Option<ModeTransition> {
+        let last_line = Help::lines().len().saturating_sub(1);
+        let scroll = match DEFAULT_MAPS.event_to_action(evt) {
+            Some(Action::Down(lines)) => std::cmp::min(self.scroll + lines, last_line),
+            Some(Action::Up(lines)) => self.scroll.saturating_sub(lines),
+            Some(Action::Top) => 0,
+            Some(Action::Bottom) => last_line,
+            Some(Action::Close) => {
+                return Some(ModeTransition::new_mode_and_dirty(
+                    Normal::new(),
+                    DirtyBytes::ChangeLength,
+                ))
+            }
+            None => match evt {
+                Event::Key(KeyEvent { modifiers, .. })
+                    if (*modifiers & !KeyModifiers::SHIFT).is_empty() =>
+                {
+                    return Some(ModeTransition::None)
+                }
+                _ => return None,
+            },
+        };
+        Some(ModeTransition::new_mode_and_dirty(
+            Help { scroll },
+            DirtyBytes::ChangeLength,
+        ))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_follow_bindings() {
+        let lines = Help::lines();
+        assert!(lines
+            .iter()
+            .any(|line| line.contains("<left> h") && line.ends_with("move left")));
+        assert!(lines.iter().any(|line| line.starts_with("  :q :quit ")));
+        assert!(lines
+            .iter()
+            .any(|line| line.contains("<f1>") && line.ends_with("show this help")));
+    }
+}
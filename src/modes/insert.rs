@@ -4,6 +4,7 @@ use std::collections::HashMap;
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
 use lazy_static::lazy_static;
 
+use crate::encoding::Encoding;
 use crate::keymap::KeyMap;
 use crate::modes::{
     mode::{Mode, ModeTransition},
@@ -57,15 +58,36 @@ lazy_static! {
     static ref DEFAULT_MAPS: KeyMap<Action> = default_maps();
 }
 
+pub fn help() -> Vec<(String, String)> {
+    DEFAULT_MAPS.describe(|action| {
+        Some(match action {
+            Action::InsertNull => "insert a null byte".into(),
+            Action::SwitchInputMode => "switch between ascii and hex input".into(),
+            Action::RemoveLast => "delete the byte before the cursor".into(),
+            Action::RemoveThis => "delete the byte under the cursor".into(),
+            Action::Exit => "back to normal mode".into(),
+            Action::Move(direction) => format!("move {}", direction.name()).into(),
+        })
+    })
+}
+
 fn transition_ascii_insertion(
     key: char,
     current_buffer: &mut CurrentBuffer,
-    mode: InsertionMode,
+    insert: Insert,
+    encoding: Encoding,
 ) -> ModeTransition {
-    let mut inserted_bytes = vec![0u8; key.len_utf8()];
-    key.encode_utf8(&mut inserted_bytes);
+    let inserted_bytes = match encoding.encode(key) {
+        Some(bytes) => bytes,
+        None => {
+            return ModeTransition::new_mode_and_info(
+                insert,
+                format!("{} has no {}", encoding, key),
+            )
+        }
+    };
 
-    match mode {
+    match insert.mode {
         InsertionMode::Append | InsertionMode::Insert => {
             let delta = ops::insert(&current_buffer.data, &current_buffer.selection, inserted_bytes);
             ModeTransition::DirtyBytes(current_buffer.apply_incomplete_delta(delta))
@@ -151,6 +173,7 @@ impl Mode for Insert {
         buffr_collection: &mut BuffrCollection,
         bytes_per_line: usize,
     ) -> Option<ModeTransition> {
+        let encoding = buffr_collection.options().encoding;
         let current_buffer = buffr_collection.current_mut();
         if let Some(action) = DEFAULT_MAPS.event_to_action(evt) {
             let new_state = if self.hex_half.is_some() {
@@ -243,7 +266,12 @@ impl Mode for Insert {
             if self.hex {
                 transition_hex_insertion(*key, current_buffer, self.mode, self.hex_half)
             } else {
-                Some(transition_ascii_insertion(*key, current_buffer, self.mode))
+                Some(transition_ascii_insertion(
+                    *key,
+                    current_buffer,
+                    *self,
+                    encoding,
+                ))
             }
         } else {
             None
@@ -0,0 +1,262 @@
+use std::borrow::Cow;
+use std::cmp;
+use std::collections::HashMap;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use lazy_static::lazy_static;
+
+use crate::keymap::KeyMap;
+use crate::modes::{
+    mode::{DirtyBytes, Mode, ModeTransition},
+    normal::Normal,
+};
+use crate::operations as ops;
+use crate::BuffrCollection;
+
+/// Picks a data inspector row to edit the value under the main caret through.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Inspect {
+    pub row: usize,
+}
+
+/// Reads a value for an inspector row, then writes its bytes at every caret.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct InspectInput {
+    pub row: usize,
+    pub label: String,
+    pub text: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Action {
+    Down,
+    Up,
+    ToggleEndian,
+    Edit,
+    Close,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum InputAction {
+    RemoveLast,
+    Finish,
+    Cancel,
+}
+
+fn default_maps() -> KeyMap<Action> {
+    KeyMap {
+        maps: keys!(
+            ('j' => Action::Down),
+            (key KeyCode::Down => Action::Down),
+            ('k' => Action::Up),
+            (key KeyCode::Up => Action::Up),
+            ('e' => Action::ToggleEndian),
+            ('c' => Action::Edit),
+            (key KeyCode::Enter => Action::Edit),
+            ('q' => Action::Close),
+            (key KeyCode::Esc => Action::Close)
+        ),
+    }
+}
+
+fn input_maps() -> KeyMap<InputAction> {
+    KeyMap {
+        maps: keys!(
+            (key KeyCode::Backspace => InputAction::RemoveLast),
+            (key KeyCode::Enter => InputAction::Finish),
+            (key KeyCode::Esc => InputAction::Cancel)
+        ),
+    }
+}
+
+lazy_static! {
+    static ref DEFAULT_MAPS: KeyMap<Action> = default_maps();
+    static ref INPUT_MAPS: KeyMap<InputAction> = input_maps();
+}
+
+fn describe(action: Action) -> Option<Cow<'static, str>> {
+    Some(match action {
+        Action::Down => "next row".into(),
+        Action::Up => "previous row".into(),
+        Action::ToggleEndian => "switch endian".into(),
+        Action::Edit => "type a value to write at each cursor".into(),
+        Action::Close => "back to normal mode".into(),
+    })
+}
+
+pub fn help() -> Vec<(String, String)> {
+    DEFAULT_MAPS.describe(describe)
+}
+
+impl Inspect {
+    pub fn new() -> Inspect {
+        Inspect { row: 0 }
+    }
+}
+
+impl Default for Inspect {
+    fn default() -> Self {
+        Inspect::new()
+    }
+}
+
+impl Mode for Inspect {
+    fn name(&self) -> Cow<'static, str> {
+        "INSPECT".into()
+    }
+
+    fn transition(
+        &self,
+        evt: &Event,
+        buffr_collection: &mut BuffrCollection,
+        _: usize,
+    ) -> Option<ModeTransition> {
+        let options = buffr_collection.options_mut();
+        let rows = options.inspector.len();
+        if rows == 0 {
+            return Some(ModeTransition::new_mode_and_info(
+                Normal::new(),
+                "the inspector has no rows".into(),
+            ));
+        }
+        let row = match DEFAULT_MAPS.event_to_action(evt) {
+            Some(Action::Down) => cmp::min(self.row + 1, rows - 1),
+            Some(Action::Up) => self.row.saturating_sub(1),
+            Some(Action::ToggleEndian) => {
+                options.inspector_endian = options.inspector_endian.other();
+                self.row
+            }
+            Some(Action::Edit) => {
+                let field = options.inspector[cmp::min(self.row, rows - 1)];
+                return Some(ModeTransition::new_mode_and_dirty(
+                    InspectInput {
+                        row: self.row,
+                        label: field.label(options.inspector_endian),
+                        text: String::new(),
+                    },
+                    DirtyBytes::ChangeLength,
+                ));
+            }
+            Some(Action::Close) => {
+                return Some(ModeTransition::new_mode_and_dirty(
+                    Normal::new(),
+                    DirtyBytes::ChangeLength,
+                ))
+            }
+            None => match evt {
+                Event::Key(KeyEvent { modifiers, .. })
+                    if (*modifiers & !KeyModifiers::SHIFT).is_empty() =>
+                {
+                    return Some(ModeTransition::None)
+                }
+                _ => return None,
+            },
+        };
+        Some(ModeTransition::new_mode_and_dirty(
+            Inspect { row },
+            DirtyBytes::ChangeLength,
+        ))
+    }
+
+    fn hints(&self) -> Option<String> {
+        Some(DEFAULT_MAPS.hints(describe))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl InspectInput {
+    fn finish(&self, buffr_collection: &mut BuffrCollection) -> ModeTransition {
+        let options = buffr_collection.options();
+        let field = match options.inspector.get(self.row) {
+            Some(field) => *field,
+            None => return ModeTransition::new_mode(Normal::new()),
+        };
+        let bytes = match field.encode(&self.text, options.inspector_endian) {
+            Ok(bytes) => bytes,
+            Err(e) => return ModeTransition::new_mode_and_info(Inspect { row: self.row }, e),
+        };
+
+        let current_buffer = buffr_collection.current_mut();
+        // Writing stops at the next caret and at the end of the data, so check that
+        // the whole value fits at every caret rather than write part of it
+        let carets: Vec<_> = current_buffer
+            .selection
+            .iter()
+            .map(|region| region.caret)
+            .collect();
+        let limits = carets[1..]
+            .iter()
+            .copied()
+            .chain(std::iter::once(current_buffer.data.len()));
+        if carets
+            .iter()
+            .zip(limits)
+            .any(|(&caret, limit)| limit.saturating_sub(caret) < bytes.len())
+        {
+            return ModeTransition::new_mode_and_info(
+                Inspect { row: self.row },
+                format!("{} bytes don't fit at every caret", bytes.len()),
+            );
+        }
+        let delta = ops::overwrite(&current_buffer.data, &current_buffer.selection, bytes);
+        // The carets stay on the value so the inspector shows what was written
+        let dirty = current_buffer.apply_delta_in_place(delta);
+        ModeTransition::new_mode_and_dirty(Inspect { row: self.row }, dirty)
+    }
+}
+
+impl Mode for InspectInput {
+    fn name(&self) -> Cow<'static, str> {
+        "INSPECT".into()
+    }
+
+    fn transition(
+        &self,
+        evt: &Event,
+        buffr_collection: &mut BuffrCollection,
+        _: usize,
+    ) -> Option<ModeTransition> {
+        if let Some(action) = INPUT_MAPS.event_to_action(evt) {
+            let mut text = self.text.clone();
+            match action {
+                InputAction::RemoveLast => {
+                    text.pop();
+                }
+                InputAction::Finish => return Some(self.finish(buffr_collection)),
+                InputAction::Cancel => {
+                    return Some(ModeTransition::new_mode_and_dirty(
+                        Inspect { row: self.row },
+                        DirtyBytes::ChangeLength,
+                    ))
+                }
+            }
+            Some(ModeTransition::new_mode(InspectInput {
+                text,
+                ..self.clone()
+            }))
+        } else if let Event::Key(KeyEvent {
+            code: KeyCode::Char(ch),
+            modifiers,
+        }) = evt
+        {
+            if !(*modifiers & !KeyModifiers::SHIFT).is_empty() {
+                return None;
+            }
+            let mut text = self.text.clone();
+            text.push(*ch);
+            Some(ModeTransition::new_mode(InspectInput {
+                text,
+                ..self.clone()
+            }))
+        } else {
+            None
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -6,7 +6,7 @@ use lazy_static::lazy_static;
 
 use crate::keymap::KeyMap;
 use crate::modes::{
-    mode::{Mode, ModeTransition},
+    mode::{DirtyBytes, Mode, ModeTransition},
     normal::Normal,
 };
 use crate::selection::Direction;
@@ -17,23 +17,44 @@ pub struct JumpTo {
     pub extend: bool,
 }
 
-fn default_maps() -> KeyMap<Direction> {
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Action {
+    Boundary(Direction),
+    AlternateBuffer,
+}
+
+fn default_maps() -> KeyMap<Action> {
     KeyMap {
         maps: keys!(
-            (key KeyCode::Left => Direction::Left),
-            ('h' => Direction::Left),
-            (key KeyCode::Down => Direction::Down),
-            ('j' => Direction::Down),
-            (key KeyCode::Up => Direction::Up),
-            ('k' => Direction::Up),
-            (key KeyCode::Right => Direction::Right),
-            ('l' => Direction::Right)
+            (key KeyCode::Left => Action::Boundary(Direction::Left)),
+            ('h' => Action::Boundary(Direction::Left)),
+            (key KeyCode::Down => Action::Boundary(Direction::Down)),
+            ('j' => Action::Boundary(Direction::Down)),
+            (key KeyCode::Up => Action::Boundary(Direction::Up)),
+            ('k' => Action::Boundary(Direction::Up)),
+            (key KeyCode::Right => Action::Boundary(Direction::Right)),
+            ('l' => Action::Boundary(Direction::Right)),
+            ('a' => Action::AlternateBuffer)
         ),
     }
 }
 
 lazy_static! {
-    static ref DEFAULT_MAPS: KeyMap<Direction> = default_maps();
+    static ref DEFAULT_MAPS: KeyMap<Action> = default_maps();
+}
+
+fn describe(action: Action) -> Option<Cow<'static, str>> {
+    Some(match action {
+        Action::Boundary(Direction::Left) => "line start".into(),
+        Action::Boundary(Direction::Right) => "line end".into(),
+        Action::Boundary(Direction::Up) => "file start".into(),
+        Action::Boundary(Direction::Down) => "file end".into(),
+        Action::AlternateBuffer => "alternate buffer".into(),
+    })
+}
+
+pub fn help() -> Vec<(String, String)> {
+    DEFAULT_MAPS.describe(describe)
 }
 
 impl Mode for JumpTo {
@@ -51,8 +72,23 @@ impl Mode for JumpTo {
         buffr_collection: &mut BuffrCollection,
         bytes_per_line: usize,
     ) -> Option<ModeTransition> {
+        if let Some(Action::AlternateBuffer) = DEFAULT_MAPS.event_to_action(evt) {
+            return Some(if !self.extend && buffr_collection.switch_to_alternate() {
+                ModeTransition::new_mode_and_dirty(Normal::new(), DirtyBytes::ChangeLength)
+            } else {
+                ModeTransition::new_mode(Normal::new())
+            });
+        }
+
+        // Jumps to the start or end of the file can be walked back with <c-o>
+        if let Some(Action::Boundary(Direction::Up | Direction::Down)) =
+            DEFAULT_MAPS.event_to_action(evt)
+        {
+            buffr_collection.record_jump();
+        }
+
         let current_buffer = buffr_collection.current_mut();
-        if let Some(direction) = DEFAULT_MAPS.event_to_action(evt) {
+        if let Some(Action::Boundary(direction)) = DEFAULT_MAPS.event_to_action(evt) {
             let max_bytes = current_buffer.data.len();
             Some(ModeTransition::new_mode_and_dirty(
                 Normal::new(),
@@ -73,6 +109,10 @@ impl Mode for JumpTo {
         }
     }
 
+    fn hints(&self) -> Option<String> {
+        Some(DEFAULT_MAPS.hints(describe))
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
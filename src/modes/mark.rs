@@ -0,0 +1,94 @@
+use std::borrow::Cow;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+use crate::marks::Marks;
+use crate::modes::{
+    mode::{DirtyBytes, Mode, ModeTransition},
+    normal::Normal,
+};
+use crate::BuffrCollection;
+
+/// Waits for the mark name following `m` (set) or `'` (jump) in normal mode.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SelectMark {
+    pub jump: bool,
+}
+
+impl SelectMark {
+    fn set_mark(&self, name: char, buffr_collection: &mut BuffrCollection) -> ModeTransition {
+        let current_buffer = buffr_collection.current_mut();
+        let offset = current_buffer.selection.main_cursor_offset();
+        current_buffer
+            .marks
+            .set(name, current_buffer.data_start_offset + offset);
+        match current_buffer.save_marks_if_clean() {
+            Ok(()) => ModeTransition::new_mode_and_dirty(
+                Normal::new(),
+                DirtyBytes::ChangeInPlace(vec![(offset..offset + 1).into()]),
+            ),
+            Err(e) => ModeTransition::new_mode_dirty_and_info(
+                Normal::new(),
+                DirtyBytes::ChangeInPlace(vec![(offset..offset + 1).into()]),
+                format!("saving marks failed: {}", e),
+            ),
+        }
+    }
+
+    fn jump_to_mark(
+        &self,
+        name: char,
+        buffr_collection: &mut BuffrCollection,
+        bytes_per_line: usize,
+    ) -> ModeTransition {
+        let offset = match buffr_collection.current().marks.get(name) {
+            Some(offset) => offset,
+            None => {
+                return ModeTransition::new_mode_and_info(
+                    Normal::new(),
+                    format!("mark {} is not set", name),
+                )
+            }
+        };
+        match buffr_collection.jump_to_offset(offset, bytes_per_line) {
+            Ok(dirty) => ModeTransition::new_mode_and_dirty(Normal::new(), dirty),
+            Err(e) => ModeTransition::new_mode_and_info(Normal::new(), e.to_string()),
+        }
+    }
+}
+
+impl Mode for SelectMark {
+    fn name(&self) -> Cow<'static, str> {
+        if self.jump {
+            "GOTO MARK".into()
+        } else {
+            "SET MARK".into()
+        }
+    }
+
+    fn transition(
+        &self,
+        evt: &Event,
+        buffr_collection: &mut BuffrCollection,
+        bytes_per_line: usize,
+    ) -> Option<ModeTransition> {
+        match evt {
+            Event::Key(KeyEvent {
+                code: KeyCode::Char(ch),
+                modifiers,
+            }) if (*modifiers & !KeyModifiers::SHIFT).is_empty() && Marks::is_valid_name(*ch) => {
+                Some(if self.jump {
+                    self.jump_to_mark(*ch, buffr_collection, bytes_per_line)
+                } else {
+                    self.set_mark(*ch, buffr_collection)
+                })
+            }
+            Event::Key(_) => Some(ModeTransition::new_mode(Normal::new())),
+            _ => None,
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
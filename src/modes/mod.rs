@@ -27,10 +27,15 @@ pub mod quitting {
 
 pub mod collapse;
 pub mod command;
+pub mod find;
+pub mod help;
 pub mod insert;
+pub mod inspect;
 pub mod jumpto;
+pub mod mark;
 pub mod mode;
 pub mod normal;
+pub mod register;
 pub mod replace;
 pub mod search;
 pub mod split;
@@ -21,6 +21,15 @@ pub trait Mode: 'static {
     fn has_half_cursor(&self) -> bool {
         false
     }
+    /// Whether the mode is waiting for a fresh command, with no count, register or
+    /// other keys pending. Used to find where a repeatable change starts and ends.
+    fn is_idle(&self) -> bool {
+        false
+    }
+    /// Keys that can follow in a pending mode, shown in place of the info line.
+    fn hints(&self) -> Option<String> {
+        None
+    }
     fn as_any(&self) -> &dyn std::any::Any;
 }
 
@@ -36,6 +45,7 @@ pub enum ModeTransition {
     DirtyBytes(DirtyBytes),
     ModeAndDirtyBytes(Box<dyn Mode>, DirtyBytes),
     ModeAndInfo(Box<dyn Mode>, String),
+    ModeAndDirtyBytesAndInfo(Box<dyn Mode>, DirtyBytes, String),
 }
 
 impl ModeTransition {
@@ -50,4 +60,20 @@ impl ModeTransition {
     pub fn new_mode_and_info(mode: impl Mode, info: String) -> ModeTransition {
         ModeTransition::ModeAndInfo(Box::new(mode), info)
     }
+
+    pub fn new_mode_dirty_and_info(
+        mode: impl Mode,
+        dirty: DirtyBytes,
+        info: String,
+    ) -> ModeTransition {
+        ModeTransition::ModeAndDirtyBytesAndInfo(Box::new(mode), dirty, info)
+    }
+
+    /// Whether the transition reports something in the status line.
+    pub fn has_info(&self) -> bool {
+        matches!(
+            self,
+            ModeTransition::ModeAndInfo(..) | ModeTransition::ModeAndDirtyBytesAndInfo(..)
+        )
+    }
 }
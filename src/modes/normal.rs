@@ -4,8 +4,11 @@ use std::collections::HashMap;
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
 use lazy_static::lazy_static;
 
-use crate::keymap::KeyMap;
+use crate::keymap::{self, KeyMap};
+use crate::macros::DEFAULT_MACRO_REGISTER;
 use crate::operations as ops;
+use crate::options::BytesPerLine;
+use crate::registers::Registers;
 use crate::selection::Direction;
 use crate::{
     cmd_count, modes,
@@ -18,6 +21,16 @@ use super::insert::InsertionMode;
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct Normal {
     count_state: cmd_count::State,
+    register: Option<char>,
+    paste_cycle: Option<PasteCycle>,
+}
+
+/// Remembers the last paste so that it can be swapped for older yank ring entries.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct PasteCycle {
+    after: bool,
+    count: usize,
+    next_ring_index: usize,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -28,12 +41,16 @@ enum Action {
     JumpToMode,
     ExtendToMode,
     CollapseMode { hex: bool },
+    FindMode { forward: bool, hex: bool },
+    FindNext { reverse: bool },
     CommandMode,
+    SelectRegister,
     SwapCaret,
     CollapseSelection,
     Delete { register: char },
     Yank { register: char },
     Paste { after: bool, register: char },
+    CyclePaste,
     Change { hex: bool, register: char },
     Insert { hex: bool },
     Append { hex: bool },
@@ -47,6 +64,19 @@ enum Action {
     Measure,
     Undo,
     Redo,
+    RecordMacro,
+    ReplayMacro,
+    RepeatChange,
+    SetMark,
+    JumpToMark,
+    JumpBack,
+    JumpForward,
+    Wider,
+    Narrower,
+    ToggleInspectorEndian,
+    InspectMode,
+    NextPane,
+    Help,
 }
 
 fn default_maps() -> KeyMap<Action> {
@@ -68,6 +98,7 @@ fn default_maps() -> KeyMap<Action> {
             ('G' => Action::ExtendToMode),
             (alt 's' => Action::SplitMode),
             (':' => Action::CommandMode),
+            ('"' => Action::SelectRegister),
             (';' => Action::CollapseSelection),
             (alt ';' => Action::SwapCaret),
             ('%' => Action::SelectAll),
@@ -78,9 +109,25 @@ fn default_maps() -> KeyMap<Action> {
             ('M' => Action::Measure),
             ('u' => Action::Undo),
             ('U' => Action::Redo),
+            ('Q' => Action::RecordMacro),
+            ('q' => Action::ReplayMacro),
+            ('.' => Action::RepeatChange),
+            ('m' => Action::SetMark),
+            ('\'' => Action::JumpToMark),
+            (ctrl 'o' => Action::JumpBack),
+            (ctrl 'i' => Action::JumpForward),
+            // Terminals send <c-i> as a tab
+            (key KeyCode::Tab => Action::JumpForward),
+            ('>' => Action::Wider),
+            ('<' => Action::Narrower),
+            (alt 'e' => Action::ToggleInspectorEndian),
+            (alt 'i' => Action::InspectMode),
+            (ctrl 'w' => Action::NextPane),
+            (key KeyCode::F(1) => Action::Help),
 
             ('p' => Action::Paste{after: true, register: '"'}),
             ('P' => Action::Paste{after: false, register: '"'}),
+            (alt 'p' => Action::CyclePaste),
             ('d' => Action::Delete{register: '"'}),
             ('y' => Action::Yank{register: '"'}),
             ('c' => Action::Change{hex: false, register: '"'}),
@@ -96,7 +143,13 @@ fn default_maps() -> KeyMap<Action> {
             ('O' => Action::Overwrite{hex: true}),
 
             ('s' => Action::CollapseMode{hex: false}),
-            ('S' => Action::CollapseMode{hex: true})
+            ('S' => Action::CollapseMode{hex: true}),
+            ('/' => Action::FindMode{forward: true, hex: false}),
+            ('?' => Action::FindMode{forward: false, hex: false}),
+            (alt '/' => Action::FindMode{forward: true, hex: true}),
+            (alt '?' => Action::FindMode{forward: false, hex: true}),
+            ('n' => Action::FindNext{reverse: false}),
+            ('N' => Action::FindNext{reverse: true})
         ),
     }
 }
@@ -105,9 +158,90 @@ lazy_static! {
     static ref DEFAULT_MAPS: KeyMap<Action> = default_maps();
 }
 
+fn describe(action: Action) -> Option<Cow<'static, str>> {
+    Some(match action {
+        Action::Move(direction) => format!("move {}", direction.name()).into(),
+        Action::Extend(direction) => format!("extend selections {}", direction.name()).into(),
+        Action::SplitMode => "split selections".into(),
+        Action::JumpToMode => "jump (to address with a count)".into(),
+        Action::ExtendToMode => "extend selections (to address with a count)".into(),
+        Action::CollapseMode { hex: false } => "keep text pattern matches".into(),
+        Action::CollapseMode { hex: true } => "keep hex pattern matches".into(),
+        Action::FindMode {
+            forward: true,
+            hex: false,
+        } => "search forward for a text pattern".into(),
+        Action::FindMode {
+            forward: false,
+            hex: false,
+        } => "search backward for a text pattern".into(),
+        Action::FindMode {
+            forward: true,
+            hex: true,
+        } => "search forward for a hex pattern".into(),
+        Action::FindMode {
+            forward: false,
+            hex: true,
+        } => "search backward for a hex pattern".into(),
+        Action::FindNext { reverse: false } => "repeat the last search".into(),
+        Action::FindNext { reverse: true } => "repeat the last search the other way".into(),
+        Action::CommandMode => "enter a command".into(),
+        Action::SelectRegister => "use a register for the next command".into(),
+        Action::SwapCaret => "swap cursor and selection end".into(),
+        Action::CollapseSelection => "collapse selections to cursors".into(),
+        Action::Delete { .. } => "delete selections".into(),
+        Action::Yank { .. } => "yank selections".into(),
+        Action::Paste { after: true, .. } => "paste after selections".into(),
+        Action::Paste { after: false, .. } => "paste before selections".into(),
+        Action::CyclePaste => "swap the last paste for an older yank".into(),
+        Action::Change { hex: false, .. } => "change selections (ascii)".into(),
+        Action::Change { hex: true, .. } => "change selections (hex)".into(),
+        Action::Insert { hex: false } => "insert before selections (ascii)".into(),
+        Action::Insert { hex: true } => "insert before selections (hex)".into(),
+        Action::Append { hex: false } => "append after selections (ascii)".into(),
+        Action::Append { hex: true } => "append after selections (hex)".into(),
+        Action::Overwrite { hex: false } => "overwrite selections (ascii)".into(),
+        Action::Overwrite { hex: true } => "overwrite selections (hex)".into(),
+        Action::RemoveMain => "drop the main selection".into(),
+        Action::RetainMain => "keep only the main selection".into(),
+        Action::SelectPrev => "make the previous selection main".into(),
+        Action::SelectNext => "make the next selection main".into(),
+        Action::SelectAll => {
+            "select everything; with a count, jump to that percentage of the file".into()
+        }
+        Action::ReplaceMode { hex: false } => "replace selected bytes (ascii)".into(),
+        Action::ReplaceMode { hex: true } => "replace selected bytes (hex)".into(),
+        Action::Measure => "measure the main selection".into(),
+        Action::Undo => "undo".into(),
+        Action::Redo => "redo".into(),
+        Action::RecordMacro => "start or stop recording a macro".into(),
+        Action::ReplayMacro => "replay a macro".into(),
+        Action::RepeatChange => "repeat the last change".into(),
+        Action::SetMark => "set a mark".into(),
+        Action::JumpToMark => "jump to a mark".into(),
+        Action::JumpBack => "go back in the jump list".into(),
+        Action::JumpForward => "go forward in the jump list".into(),
+        Action::Wider => "show more bytes per line".into(),
+        Action::Narrower => "show fewer bytes per line".into(),
+        Action::ToggleInspectorEndian => {
+            "switch the inspector between big and little endian".into()
+        }
+        Action::InspectMode => "edit the value under the cursor through the inspector".into(),
+        Action::NextPane => "focus the next pane (the pane given by a count)".into(),
+        Action::Help => "show this help".into(),
+    })
+}
+
+pub fn help() -> Vec<(String, String)> {
+    DEFAULT_MAPS.describe(describe)
+}
+
 impl Mode for Normal {
     fn name(&self) -> Cow<'static, str> {
-        format!("NORMAL{}", self.count_state).into()
+        match self.register {
+            Some(register) => format!("NORMAL \"{}{}", register, self.count_state).into(),
+            None => format!("NORMAL{}", self.count_state).into(),
+        }
     }
 
     fn transition(
@@ -120,25 +254,48 @@ impl Mode for Normal {
         if let cmd_count::Transition::Update(new_state) = self.count_state.transition(event) {
             Some(ModeTransition::new_mode(Normal {
                 count_state: new_state,
+                ..*self
             }))
         } else if let Some(action) = DEFAULT_MAPS.event_to_action(event) {
+            let action = self.with_selected_register(action);
             Some(match action {
                 Action::JumpToMode => match self.count_state {
                     cmd_count::State::None => {
                         ModeTransition::new_mode(modes::jumpto::JumpTo { extend: false })
                     }
-                    cmd_count::State::Some { count: offset, .. } => {
-                        ModeTransition::new_mode_and_dirty(
-                            Normal::new(),
-                            current_buffer.map_selections(|region| vec![region.jump_to(offset)]),
-                        )
+                    cmd_count::State::Some { count: address, .. } => {
+                        let offset = match address_to_offset(buffr_collection, address) {
+                            Ok(offset) => offset,
+                            Err(info) => {
+                                return Some(ModeTransition::new_mode_and_info(Normal::new(), info))
+                            }
+                        };
+                        match buffr_collection.jump_to_offset(offset, bytes_per_line) {
+                            Ok(dirty) => ModeTransition::new_mode_and_dirty(Normal::new(), dirty),
+                            Err(e) => {
+                                ModeTransition::new_mode_and_info(Normal::new(), e.to_string())
+                            }
+                        }
                     }
                 },
                 Action::ExtendToMode => match self.count_state {
                     cmd_count::State::None => {
                         ModeTransition::new_mode(modes::jumpto::JumpTo { extend: true })
                     }
-                    cmd_count::State::Some { count: offset, .. } => {
+                    cmd_count::State::Some { count: address, .. } => {
+                        let offset = match address_to_offset(buffr_collection, address) {
+                            Ok(offset) => offset,
+                            Err(info) => {
+                                return Some(ModeTransition::new_mode_and_info(Normal::new(), info))
+                            }
+                        };
+                        buffr_collection.record_jump();
+                        let current_buffer = buffr_collection.current_mut();
+                        // Selections can only reach as far as the loaded window
+                        let offset = std::cmp::min(
+                            offset.saturating_sub(current_buffer.data_start_offset),
+                            current_buffer.data.len().saturating_sub(1),
+                        );
                         ModeTransition::new_mode_and_dirty(
                             Normal::new(),
                             current_buffer.map_selections(|region| vec![region.extend_to(offset)]),
@@ -216,48 +373,97 @@ impl Mode for Normal {
                     current_buffer.map_selections(|region| vec![region.collapse()]),
                 ),
                 Action::Delete { register } => {
-                    current_buffer.yank_selections(register);
-                    if !current_buffer.data.is_empty() {
+                    let yanked = buffr_collection.yank_selections(register);
+                    let current_buffer = buffr_collection.current_mut();
+                    let dirty = if !current_buffer.data.is_empty() {
                         let delta = ops::deletion(&current_buffer.data, &current_buffer.selection);
-                        ModeTransition::DirtyBytes(current_buffer.apply_delta(delta))
+                        Some(current_buffer.apply_delta(delta))
                     } else {
-                        ModeTransition::None
-                    }
+                        None
+                    };
+                    after_yank(Normal::new(), dirty, yanked)
                 }
                 Action::Change { hex, register } => {
-                    current_buffer.yank_selections(register);
-                    if !current_buffer.data.is_empty() {
+                    let yanked = buffr_collection.yank_selections(register);
+                    let current_buffer = buffr_collection.current_mut();
+                    let dirty = if !current_buffer.data.is_empty() {
                         let delta = ops::deletion(&current_buffer.data, &current_buffer.selection);
-                        ModeTransition::new_mode_and_dirty(
-                            modes::insert::Insert {
-                                hex,
-                                mode: InsertionMode::Insert,
-                                hex_half: None,
-                            },
-                            current_buffer.apply_delta(delta),
-                        )
+                        Some(current_buffer.apply_delta(delta))
                     } else {
-                        ModeTransition::new_mode(modes::insert::Insert {
+                        None
+                    };
+                    after_yank(
+                        modes::insert::Insert {
                             hex,
                             mode: InsertionMode::Insert,
                             hex_half: None,
-                        })
-                    }
+                        },
+                        dirty,
+                        yanked,
+                    )
                 }
                 Action::Yank { register } => {
-                    current_buffer.yank_selections(register);
-                    ModeTransition::None
+                    let yanked = buffr_collection.yank_selections(register);
+                    after_yank(Normal::new(), None, yanked)
                 }
                 Action::Paste { register, after } => {
+                    if let Err(e) = buffr_collection.sync_register(register) {
+                        return Some(ModeTransition::new_mode_and_info(Normal::new(), e));
+                    }
+                    let (current_buffer, registers) = buffr_collection.current_and_registers_mut();
+                    let empty = vec![vec![]];
+                    let pasted = registers.get(register).unwrap_or(&empty);
+                    let count = self.count_state.to_count();
                     let delta = ops::paste(
                         &current_buffer.data,
                         &current_buffer.selection,
-                        current_buffer.registers.get(&register).unwrap_or(&vec![vec![]]),
+                        pasted,
                         after,
-                        self.count_state.to_count(),
+                        count,
                     );
-                    ModeTransition::DirtyBytes(current_buffer.apply_delta(delta))
+                    // Cycling shouldn't offer the entry that was just pasted
+                    let next_ring_index = (registers.ring_entry(0) == Some(pasted)) as usize;
+                    ModeTransition::new_mode_and_dirty(
+                        Normal {
+                            paste_cycle: Some(PasteCycle {
+                                after,
+                                count,
+                                next_ring_index,
+                            }),
+                            ..Normal::new()
+                        },
+                        current_buffer.apply_delta(delta),
+                    )
                 }
+                Action::CyclePaste => match self.paste_cycle {
+                    Some(cycle) if buffr_collection.registers().ring_len() > 0 => {
+                        let (current_buffer, registers) =
+                            buffr_collection.current_and_registers_mut();
+                        let ring_index = cycle.next_ring_index % registers.ring_len();
+                        current_buffer.perform_undo();
+                        let delta = ops::paste(
+                            &current_buffer.data,
+                            &current_buffer.selection,
+                            registers.ring_entry(ring_index).unwrap(),
+                            cycle.after,
+                            cycle.count,
+                        );
+                        ModeTransition::new_mode_and_dirty(
+                            Normal {
+                                paste_cycle: Some(PasteCycle {
+                                    next_ring_index: ring_index + 1,
+                                    ..cycle
+                                }),
+                                ..Normal::new()
+                            },
+                            current_buffer.apply_delta(delta),
+                        )
+                    }
+                    _ => ModeTransition::new_mode_and_info(
+                        Normal::new(),
+                        "nothing to cycle: paste first".to_owned(),
+                    ),
+                },
                 // selection indexing in the UI starts at 1
                 // hence we check for count > 0 and offset by -1
                 Action::RemoveMain => match self.count_state {
@@ -292,6 +498,14 @@ impl Mode for Normal {
                     Normal::new(),
                     current_buffer.select_prev(self.count_state.to_count()),
                 ),
+                Action::SelectAll if matches!(self.count_state, cmd_count::State::Some { .. }) => {
+                    let percent = self.count_state.to_count().min(100);
+                    let offset = current_buffer.total_len() * percent / 100;
+                    match buffr_collection.jump_to_offset(offset, bytes_per_line) {
+                        Ok(dirty) => ModeTransition::new_mode_and_dirty(Normal::new(), dirty),
+                        Err(e) => ModeTransition::new_mode_and_info(Normal::new(), e.to_string()),
+                    }
+                }
                 Action::SelectAll => {
                     current_buffer.selection.select_all(current_buffer.data.len());
                     ModeTransition::DirtyBytes(DirtyBytes::ChangeInPlace(vec![(0..current_buffer
@@ -302,6 +516,24 @@ impl Mode for Normal {
                 Action::CollapseMode { hex } => ModeTransition::new_mode(
                     modes::search::Search::new(modes::collapse::Collapse(), hex),
                 ),
+                Action::FindMode { forward, hex } => ModeTransition::new_mode(
+                    modes::search::Search::new(modes::find::Find { forward }, hex),
+                ),
+                Action::FindNext { reverse } => match buffr_collection.last_search() {
+                    Some((pattern, forward)) => {
+                        let pattern = pattern.clone();
+                        modes::find::find_next(
+                            buffr_collection,
+                            &pattern,
+                            forward != reverse,
+                            self.count_state.to_count(),
+                        )
+                    }
+                    None => ModeTransition::new_mode_and_info(
+                        Normal::new(),
+                        "no previous search".to_owned(),
+                    ),
+                },
                 Action::Measure => ModeTransition::new_mode_and_info(
                     Normal::new(),
                     format!(
@@ -311,6 +543,144 @@ impl Mode for Normal {
                     ),
                 ),
                 Action::CommandMode => ModeTransition::new_mode(modes::command::Command::new()),
+                Action::SelectRegister => {
+                    ModeTransition::new_mode(modes::register::SelectRegister {
+                        count_state: self.count_state,
+                    })
+                }
+                Action::SetMark => {
+                    ModeTransition::new_mode(modes::mark::SelectMark { jump: false })
+                }
+                Action::JumpToMark => {
+                    ModeTransition::new_mode(modes::mark::SelectMark { jump: true })
+                }
+                Action::JumpBack => {
+                    let count = self.count_state.to_count();
+                    match buffr_collection.jump_back(count, bytes_per_line) {
+                        Ok(true) => ModeTransition::new_mode_and_dirty(
+                            Normal::new(),
+                            DirtyBytes::ChangeLength,
+                        ),
+                        Ok(false) => ModeTransition::new_mode_and_info(
+                            Normal::new(),
+                            "no older jump".to_owned(),
+                        ),
+                        Err(e) => ModeTransition::new_mode_and_info(Normal::new(), e.to_string()),
+                    }
+                }
+                Action::JumpForward => {
+                    let count = self.count_state.to_count();
+                    match buffr_collection.jump_forward(count, bytes_per_line) {
+                        Ok(true) => ModeTransition::new_mode_and_dirty(
+                            Normal::new(),
+                            DirtyBytes::ChangeLength,
+                        ),
+                        Ok(false) => ModeTransition::new_mode_and_info(
+                            Normal::new(),
+                            "no newer jump".to_owned(),
+                        ),
+                        Err(e) => ModeTransition::new_mode_and_info(Normal::new(), e.to_string()),
+                    }
+                }
+                Action::NextPane => {
+                    let panes = buffr_collection.panes();
+                    let index = match self.count_state {
+                        cmd_count::State::Some { count, .. } => count.wrapping_sub(1),
+                        cmd_count::State::None => (panes.active_index() + 1) % panes.len(),
+                    };
+                    if buffr_collection.focus_pane(index) {
+                        ModeTransition::new_mode_and_dirty(Normal::new(), DirtyBytes::ChangeLength)
+                    } else {
+                        ModeTransition::new_mode_and_info(Normal::new(), "no such pane".to_owned())
+                    }
+                }
+                Action::Wider | Action::Narrower => {
+                    let step = if action == Action::Wider {
+                        BytesPerLine::wider
+                    } else {
+                        BytesPerLine::narrower
+                    };
+                    let width =
+                        (0..self.count_state.to_count()).fold(bytes_per_line, |n, _| step(n));
+                    buffr_collection.options_mut().bytes_per_line = BytesPerLine::Fixed(width);
+                    ModeTransition::new_mode_dirty_and_info(
+                        Normal::new(),
+                        DirtyBytes::ChangeLength,
+                        format!("{} bytes per line", width),
+                    )
+                }
+                Action::ToggleInspectorEndian => {
+                    let options = buffr_collection.options_mut();
+                    options.inspector_endian = options.inspector_endian.other();
+                    ModeTransition::new_mode_dirty_and_info(
+                        Normal::new(),
+                        DirtyBytes::ChangeLength,
+                        format!("inspector endian: {}", options.inspector_endian),
+                    )
+                }
+                Action::InspectMode => ModeTransition::new_mode_and_dirty(
+                    modes::inspect::Inspect::new(),
+                    DirtyBytes::ChangeLength,
+                ),
+                Action::Help => ModeTransition::new_mode_and_dirty(
+                    modes::help::Help::new(),
+                    DirtyBytes::ChangeLength,
+                ),
+                Action::RecordMacro => {
+                    let macros = buffr_collection.macros_mut();
+                    if let Some((register, keys)) = macros.stop_recording() {
+                        let text = keymap::format_keys(&keys);
+                        match buffr_collection
+                            .registers_mut()
+                            .set(register, vec![text.into_bytes()])
+                        {
+                            Ok(()) => ModeTransition::new_mode(Normal::new()),
+                            Err(e) => ModeTransition::new_mode_and_info(Normal::new(), e),
+                        }
+                    } else {
+                        let register = self.register.unwrap_or(DEFAULT_MACRO_REGISTER);
+                        match Registers::check_writable(register) {
+                            Ok(()) => {
+                                macros.start_recording(register);
+                                ModeTransition::new_mode(Normal::new())
+                            }
+                            Err(e) => ModeTransition::new_mode_and_info(Normal::new(), e),
+                        }
+                    }
+                }
+                Action::ReplayMacro => {
+                    let register = self.register.unwrap_or(DEFAULT_MACRO_REGISTER);
+                    let text = buffr_collection
+                        .registers()
+                        .get(register)
+                        .map(|contents| String::from_utf8_lossy(&contents.concat()).into_owned());
+                    match text.map(|text| keymap::parse_keys(&text)) {
+                        Some(Ok(keys)) => {
+                            buffr_collection
+                                .macros_mut()
+                                .request_replay(keys, self.count_state.to_count());
+                            ModeTransition::new_mode(Normal::new())
+                        }
+                        Some(Err(e)) => ModeTransition::new_mode_and_info(Normal::new(), e),
+                        None => ModeTransition::new_mode_and_info(
+                            Normal::new(),
+                            format!("register {} is empty", register),
+                        ),
+                    }
+                }
+                Action::RepeatChange => match buffr_collection.macros().last_change() {
+                    Some(keys) => {
+                        let keys = keys.to_vec();
+                        buffr_collection
+                            .macros_mut()
+                            .request_replay(keys, self.count_state.to_count());
+                        ModeTransition::new_mode(Normal::new())
+                    }
+                    None => ModeTransition::new_mode_and_info(
+                        Normal::new(),
+                        "no change to repeat".to_owned(),
+                    ),
+                },
                 Action::Undo => current_buffer.perform_undo().map_or_else(
                     || {
                         ModeTransition::new_mode_and_info(
@@ -334,15 +704,73 @@ impl Mode for Normal {
             None
         }
     }
+    fn is_idle(&self) -> bool {
+        self.count_state == cmd_count::State::None && self.register.is_none()
+    }
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
 }
 
+// Counts given to g and G are addresses, which start at the `base` option, and
+// map to file offsets
+fn address_to_offset(buffr_collection: &BuffrCollection, address: usize) -> Result<usize, String> {
+    let options = buffr_collection.options();
+    options.offset_of_address(address).ok_or_else(|| {
+        format!(
+            "0x{:x} is below the base address 0x{:x}",
+            address, options.base
+        )
+    })
+}
+
+fn after_yank(
+    mode: impl Mode,
+    dirty: Option<DirtyBytes>,
+    yanked: Result<(), String>,
+) -> ModeTransition {
+    match (dirty, yanked) {
+        (Some(dirty), Ok(())) => ModeTransition::new_mode_and_dirty(mode, dirty),
+        (Some(dirty), Err(e)) => ModeTransition::new_mode_dirty_and_info(mode, dirty, e),
+        (None, Ok(())) => ModeTransition::new_mode(mode),
+        (None, Err(e)) => ModeTransition::new_mode_and_info(mode, e),
+    }
+}
+
 impl Normal {
     pub fn new() -> Normal {
         Normal {
             count_state: cmd_count::State::None,
+            register: None,
+            paste_cycle: None,
+        }
+    }
+
+    pub fn with_register(count_state: cmd_count::State, register: char) -> Normal {
+        Normal {
+            count_state,
+            register: Some(register),
+            paste_cycle: None,
+        }
+    }
+
+    fn with_selected_register(&self, action: Action) -> Action {
+        let selected = match self.register {
+            Some(register) => register,
+            None => return action,
+        };
+        match action {
+            Action::Delete { .. } => Action::Delete { register: selected },
+            Action::Yank { .. } => Action::Yank { register: selected },
+            Action::Paste { after, .. } => Action::Paste {
+                after,
+                register: selected,
+            },
+            Action::Change { hex, .. } => Action::Change {
+                hex,
+                register: selected,
+            },
+            other => other,
         }
     }
 }
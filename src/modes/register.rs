@@ -0,0 +1,38 @@
+use std::borrow::Cow;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+use crate::modes::{
+    mode::{Mode, ModeTransition},
+    normal::Normal,
+};
+use crate::{cmd_count, BuffrCollection};
+
+/// Waits for the register name following a `"` in normal mode.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SelectRegister {
+    pub count_state: cmd_count::State,
+}
+
+impl Mode for SelectRegister {
+    fn name(&self) -> Cow<'static, str> {
+        format!("REGISTER{}", self.count_state).into()
+    }
+
+    fn transition(&self, evt: &Event, _: &mut BuffrCollection, _: usize) -> Option<ModeTransition> {
+        match evt {
+            Event::Key(KeyEvent {
+                code: KeyCode::Char(ch),
+                modifiers,
+            }) if (*modifiers & !KeyModifiers::SHIFT).is_empty() && ch.is_ascii_graphic() => Some(
+                ModeTransition::new_mode(Normal::with_register(self.count_state, *ch)),
+            ),
+            Event::Key(_) => Some(ModeTransition::new_mode(Normal::new())),
+            _ => None,
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -35,6 +35,12 @@ lazy_static! {
     static ref DEFAULT_MAPS: KeyMap<Action> = default_maps();
 }
 
+pub fn help() -> Vec<(String, String)> {
+    DEFAULT_MAPS.describe(|action| match action {
+        Action::Null => Some("replace with null bytes".into()),
+    })
+}
+
 impl Mode for Replace {
     fn name(&self) -> Cow<'static, str> {
         match (self.hex, self.hex_half) {
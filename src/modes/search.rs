@@ -4,8 +4,10 @@ use lazy_static::lazy_static;
 use regex::bytes::RegexBuilder;
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::cmp;
 use std::collections::HashMap;
 use std::ops::Range;
+use std::rc::Rc;
 
 use crate::keymap::KeyMap;
 use crate::modes::{
@@ -18,11 +20,85 @@ use crate::{CurrentBuffer, BuffrCollection};
 pub enum PatternPiece {
     Literal(u8),
     Wildcard,
+    /// Matches bytes where `byte & mask == value`, as typed with nibble wildcards
+    /// (`4?`, `?f`) or a mask (`80&c0`) in hex input.
+    Masked {
+        value: u8,
+        mask: u8,
+    },
+}
+
+impl PatternPiece {
+    /// The simplest piece matching `byte & mask == value & mask`.
+    pub fn masked(value: u8, mask: u8) -> PatternPiece {
+        match mask {
+            0xff => PatternPiece::Literal(value),
+            0 => PatternPiece::Wildcard,
+            mask => PatternPiece::Masked {
+                value: value & mask,
+                mask,
+            },
+        }
+    }
+
+    pub fn value(self) -> u8 {
+        match self {
+            PatternPiece::Literal(value) | PatternPiece::Masked { value, .. } => value,
+            PatternPiece::Wildcard => 0,
+        }
+    }
+
+    pub fn mask(self) -> u8 {
+        match self {
+            PatternPiece::Literal(_) => 0xff,
+            PatternPiece::Wildcard => 0,
+            PatternPiece::Masked { mask, .. } => mask,
+        }
+    }
+
+    pub fn matches(self, byte: u8) -> bool {
+        byte & self.mask() == self.value()
+    }
+
+    /// How the piece is written in hex input: `4f`, `4?`, `**` or `80&c0`.
+    pub fn to_hex(self) -> String {
+        let nibble = |shift: u8| match (self.mask() >> shift) & 0xf {
+            0xf => format!("{:x}", (self.value() >> shift) & 0xf),
+            _ => "?".to_owned(),
+        };
+        let whole_nibble = |mask: u8| mask == 0 || mask == 0xf;
+        match self {
+            PatternPiece::Wildcard => "**".to_owned(),
+            piece if whole_nibble(piece.mask() >> 4) && whole_nibble(piece.mask() & 0xf) => {
+                format!("{}{}", nibble(4), nibble(0))
+            }
+            piece => format!("{:02x}&{:02x}", piece.value(), piece.mask()),
+        }
+    }
+}
+
+/// A hex byte or mask that's partly typed in the search prompt.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum HexHalf {
+    /// The high nibble of the byte under the cursor, as a piece matching just it.
+    Byte(PatternPiece),
+    /// `&` was typed after the byte before the cursor, then maybe the high nibble
+    /// of its mask.
+    Mask(Option<u8>),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Default)]
 pub struct Pattern {
     pub pieces: Vec<PatternPiece>,
+    /// Whether the pieces spell out a regex over bytes rather than the bytes to match.
+    pub regex: bool,
+}
+
+/// What's wrong with a regex, as the last line of its error without the pattern.
+fn describe_regex_error(err: &regex::Error) -> String {
+    let message = err.to_string();
+    let reason = message.lines().last().unwrap_or_default();
+    format!("invalid regex: {}", reason.trim_start_matches("error: "))
 }
 
 impl Pattern {
@@ -30,8 +106,8 @@ impl Pattern {
         self.pieces.insert(position, PatternPiece::Literal(literal));
         position + 1
     }
-    fn insert_half_literal(&mut self, position: usize, literal: u8) -> usize {
-        self.pieces[position] = PatternPiece::Literal(literal);
+    fn insert_half(&mut self, position: usize, piece: PatternPiece) -> usize {
+        self.pieces[position] = piece;
         position + 1
     }
     fn insert_wildcard(&mut self, position: usize) -> usize {
@@ -61,52 +137,216 @@ impl Pattern {
             .collect::<Option<Vec<_>>>()
     }
 
-    pub fn map_selections_to_matches(&self, current_buffer: &CurrentBuffer) -> Vec<Vec<Range<usize>>> {
-        if let Some(basic_subslice) = self.as_basic_slice() {
-            current_buffer
-                .selection
-                .iter()
-                .map(|x| {
-                    let mut base = x.min();
-                    let mut matched_ranges = vec![];
-                    let byte_substring = ByteSubstring::new(&basic_subslice);
-
-                    let data = current_buffer.data.slice_to_cow(base..=x.max());
-                    let mut slice_base = 0;
-
-                    while let Some(start) = byte_substring.find(&data[slice_base..]) {
-                        let match_abs_start = base + start;
-                        matched_ranges
-                            .push(match_abs_start..match_abs_start + basic_subslice.len());
-                        base = match_abs_start + basic_subslice.len();
-                        slice_base = slice_base + start + basic_subslice.len();
-                    }
-                    matched_ranges
-                })
-                .collect::<Vec<_>>()
-        } else {
-            let expr = self
-                .pieces
-                .iter()
-                .map(|x| match x {
-                    PatternPiece::Wildcard => Cow::from("."),
-                    PatternPiece::Literal(c) => Cow::from(format!("\\x{:02x}", c)),
-                })
-                .collect::<String>();
-            let mut builder = RegexBuilder::new(&expr);
+    /// The regex the pieces spell out. Bytes that can't be typed as text are escaped,
+    /// and wildcards and masks become classes.
+    fn regex_source(&self) -> String {
+        self.pieces
+            .iter()
+            .map(|piece| match piece {
+                PatternPiece::Literal(byte) if byte.is_ascii_graphic() || *byte == b' ' => {
+                    (*byte as char).to_string()
+                }
+                PatternPiece::Literal(byte) => format!("\\x{:02x}", byte),
+                PatternPiece::Wildcard => "(?s:.)".to_owned(),
+                PatternPiece::Masked { .. } => format!(
+                    "[{}]",
+                    (0..=255u8)
+                        .filter(|&byte| piece.matches(byte))
+                        .map(|byte| format!("\\x{:02x}", byte))
+                        .collect::<String>()
+                ),
+            })
+            .collect()
+    }
+
+    /// The pattern as text after its prompt label: `search:` and the pieces in hex,
+    /// or `regex:` and the regex source.
+    pub fn to_text(&self) -> String {
+        if self.regex {
+            return format!("regex:{}", self.regex_source());
+        }
+        let hex = self
+            .pieces
+            .iter()
+            .map(|piece| piece.to_hex())
+            .collect::<Vec<_>>();
+        format!("search:{}", hex.join(" "))
+    }
+
+    fn matcher(&self) -> Result<Matcher, String> {
+        if self.regex {
+            let mut builder = RegexBuilder::new(&self.regex_source());
             builder.unicode(false);
-            let matcher = builder.build().expect("Failed to create pattern");
+            return builder
+                .build()
+                .map(Matcher::Regex)
+                .map_err(|err| describe_regex_error(&err));
+        }
+        Ok(match self.as_basic_slice() {
+            Some(basic_subslice) => Matcher::Substring(basic_subslice),
+            None => Matcher::Masked(MaskedMatcher::new(&self.pieces)),
+        })
+    }
 
-            current_buffer
-                .selection
+    /// The matches in each selection, or what's wrong with the regex.
+    pub fn map_selections_to_matches(
+        &self,
+        current_buffer: &CurrentBuffer,
+    ) -> Result<Vec<Vec<Range<usize>>>, String> {
+        let matcher = self.matcher()?;
+        Ok(current_buffer
+            .selection
+            .iter()
+            .map(|x| {
+                matcher.find_in(
+                    &current_buffer.data.slice_to_cow(x.min()..=x.max()),
+                    x.min(),
+                )
+            })
+            .collect::<Vec<_>>())
+    }
+
+    /// Every match in `data`, in order, or what's wrong with the regex.
+    pub fn find_all(&self, data: &[u8]) -> Result<Vec<Range<usize>>, String> {
+        if self.pieces.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(self.matcher()?.find_in(data, 0))
+    }
+}
+
+enum Matcher {
+    Substring(Vec<u8>),
+    Masked(MaskedMatcher),
+    Regex(regex::bytes::Regex),
+}
+
+/// Matches patterns with wildcards and masks without a regex: the longest run of
+/// literals is found with a substring search, then the bytes around it are checked.
+struct MaskedMatcher {
+    values: Vec<u8>,
+    masks: Vec<u8>,
+    /// Where the longest run of literals starts in the pattern, and the run itself.
+    anchor_offset: usize,
+    anchor: Vec<u8>,
+}
+
+impl MaskedMatcher {
+    fn new(pieces: &[PatternPiece]) -> MaskedMatcher {
+        let (mut anchor_offset, mut anchor_len) = (0, 0);
+        let mut run_start = 0;
+        for (i, piece) in pieces.iter().enumerate() {
+            if let PatternPiece::Literal(_) = piece {
+                if i + 1 - run_start > anchor_len {
+                    anchor_offset = run_start;
+                    anchor_len = i + 1 - run_start;
+                }
+            } else {
+                run_start = i + 1;
+            }
+        }
+        MaskedMatcher {
+            values: pieces.iter().map(|piece| piece.value()).collect(),
+            masks: pieces.iter().map(|piece| piece.mask()).collect(),
+            anchor_offset,
+            anchor: pieces[anchor_offset..anchor_offset + anchor_len]
                 .iter()
-                .map(|x| {
-                    matcher
-                        .find_iter(&current_buffer.data.slice_to_cow(x.min()..=x.max()))
-                        .map(|r| (x.min() + r.start())..(x.min() + r.end()))
-                        .collect::<Vec<_>>()
-                })
-                .collect::<Vec<_>>()
+                .map(|piece| piece.value())
+                .collect(),
+        }
+    }
+
+    fn matches_at(&self, data: &[u8], start: usize) -> bool {
+        data[start..start + self.values.len()]
+            .iter()
+            .zip(self.masks.iter().zip(&self.values))
+            .all(|(byte, (mask, value))| byte & mask == *value)
+    }
+
+    fn find_in(&self, data: &[u8], base: usize) -> Vec<Range<usize>> {
+        let len = self.values.len();
+        let mut matched_ranges = vec![];
+        if data.len() < len {
+            return matched_ranges;
+        }
+        let last_start = data.len() - len;
+        let mut start = 0;
+        if self.anchor.is_empty() {
+            while start <= last_start {
+                if self.matches_at(data, start) {
+                    matched_ranges.push(base + start..base + start + len);
+                    start += len.max(1);
+                } else {
+                    start += 1;
+                }
+            }
+            return matched_ranges;
+        }
+
+        let byte_substring = ByteSubstring::new(&self.anchor);
+        // Anchors are only looked for where a whole match still fits
+        let anchor_end = last_start + self.anchor_offset + self.anchor.len();
+        while start <= last_start {
+            let from = start + self.anchor_offset;
+            let candidate = match byte_substring.find(&data[from..anchor_end]) {
+                Some(found) => start + found,
+                None => break,
+            };
+            if self.matches_at(data, candidate) {
+                matched_ranges.push(base + candidate..base + candidate + len);
+                start = candidate + len;
+            } else {
+                start = candidate + 1;
+            }
+        }
+        matched_ranges
+    }
+}
+
+impl Matcher {
+    /// Non-overlapping matches in `data`, offset by `base`.
+    fn find_in(&self, data: &[u8], base: usize) -> Vec<Range<usize>> {
+        match self {
+            Matcher::Substring(basic_subslice) => {
+                let mut base = base;
+                let mut matched_ranges = vec![];
+                let byte_substring = ByteSubstring::new(basic_subslice);
+                let mut slice_base = 0;
+
+                while let Some(start) = byte_substring.find(&data[slice_base..]) {
+                    let match_abs_start = base + start;
+                    matched_ranges.push(match_abs_start..match_abs_start + basic_subslice.len());
+                    base = match_abs_start + basic_subslice.len();
+                    slice_base = slice_base + start + basic_subslice.len();
+                }
+                matched_ranges
+            }
+            Matcher::Masked(matcher) => matcher.find_in(data, base),
+            Matcher::Regex(regex) => {
+                let mut matched_ranges: Vec<Range<usize>> = vec![];
+                for captures in regex.captures_iter(data) {
+                    // The capture groups are what's selected, if the regex has any. Empty
+                    // matches can't be selected, and nested groups only select the outermost.
+                    let skipped = if captures.len() > 1 { 1 } else { 0 };
+                    let mut groups = captures
+                        .iter()
+                        .skip(skipped)
+                        .flatten()
+                        .map(|group| group.range())
+                        .filter(|group| !group.is_empty())
+                        .collect::<Vec<_>>();
+                    groups.sort_by_key(|group| (group.start, cmp::Reverse(group.end)));
+                    for group in groups {
+                        if matched_ranges
+                            .last()
+                            .map_or(true, |last| last.end <= base + group.start)
+                        {
+                            matched_ranges.push(base + group.start..base + group.end);
+                        }
+                    }
+                }
+                matched_ranges
+            }
         }
     }
 }
@@ -118,14 +358,36 @@ pub trait SearchAcceptor: Mode {
         buffr_collection: &mut BuffrCollection,
         bytes_per_line: usize,
     ) -> ModeTransition;
+
+    /// Whether the search looks for matches after the main cursor rather than
+    /// before it.
+    fn forward(&self) -> bool {
+        true
+    }
 }
 
 pub struct Search {
     pub pattern: Pattern,
     pub cursor: usize,
     pub hex: bool,
-    pub hex_half: Option<u8>,
+    pub hex_half: Option<HexHalf>,
     pub next: RefCell<Option<Box<dyn SearchAcceptor>>>,
+    /// The loaded bytes, copied once when the first match is looked for, as they
+    /// can't change while the pattern is typed.
+    haystack: Option<Rc<Vec<u8>>>,
+    /// The pattern `matches` were found for, so keys that leave it as it was don't
+    /// search again.
+    scanned: Option<Pattern>,
+    /// Matches of the pattern typed so far in the loaded bytes, highlighted while
+    /// it's typed.
+    pub matches: Rc<Vec<Range<usize>>>,
+    /// The first match at or after the main cursor, or the last one before it when
+    /// searching backward, which the view scrolls to.
+    pub current_match: Option<usize>,
+    /// The search history entry shown, from `<up>` and `<down>`.
+    pub history_index: Option<usize>,
+    /// Why the regex typed so far doesn't compile.
+    pub error: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -137,6 +399,9 @@ enum Action {
     CursorLeft,
     CursorRight,
     SwitchInputMode,
+    SwitchRegex,
+    HistoryPrev,
+    HistoryNext,
     Finish,
     Cancel,
 }
@@ -150,7 +415,10 @@ fn default_maps() -> KeyMap<Action> {
             (key KeyCode::Esc => Action::Cancel),
             (key KeyCode::Left => Action::CursorLeft),
             (key KeyCode::Right => Action::CursorRight),
+            (key KeyCode::Up => Action::HistoryPrev),
+            (key KeyCode::Down => Action::HistoryNext),
             (ctrl 'o' => Action::SwitchInputMode ),
+            (ctrl 'r' => Action::SwitchRegex),
             (ctrl 'n' => Action::InsertNull),
             (ctrl 'w' => Action::InsertWilcard)
         ),
@@ -161,6 +429,28 @@ lazy_static! {
     static ref DEFAULT_MAPS: KeyMap<Action> = default_maps();
 }
 
+pub fn help() -> Vec<(String, String)> {
+    DEFAULT_MAPS.describe(|action| {
+        Some(
+            match action {
+                Action::InsertNull => "insert a null byte",
+                Action::InsertWilcard => "insert a wildcard",
+                Action::RemoveLast => "delete before the cursor",
+                Action::RemoveThis => "delete under the cursor",
+                Action::CursorLeft => "move the cursor left",
+                Action::CursorRight => "move the cursor right",
+                Action::SwitchInputMode => "switch between ascii and hex input",
+                Action::SwitchRegex => "switch between a plain pattern and a regex",
+                Action::HistoryPrev => "show the previous pattern searched for",
+                Action::HistoryNext => "show the next pattern searched for",
+                Action::Finish => "accept the pattern",
+                Action::Cancel => "cancel",
+            }
+            .into(),
+        )
+    })
+}
+
 impl Search {
     pub fn new(next: impl SearchAcceptor, hex: bool) -> Search {
         Search {
@@ -169,6 +459,50 @@ impl Search {
             hex_half: None,
             cursor: 0,
             pattern: Pattern::default(),
+            haystack: None,
+            scanned: None,
+            matches: Rc::default(),
+            current_match: None,
+            history_index: None,
+            error: None,
+        }
+    }
+
+    fn forward(&self) -> bool {
+        self.next
+            .borrow()
+            .as_ref()
+            .map_or(true, |next| next.forward())
+    }
+
+    fn with_matches(self, current_buffer: &CurrentBuffer, forward: bool) -> Search {
+        let haystack = self
+            .haystack
+            .unwrap_or_else(|| Rc::new(current_buffer.data.slice_to_cow(..).into_owned()));
+        let (matches, error) = if self.scanned.as_ref() == Some(&self.pattern) {
+            (self.matches, self.error)
+        } else {
+            match self.pattern.find_all(&haystack) {
+                Ok(matches) => (Rc::new(matches), None),
+                Err(err) => (Rc::default(), Some(err)),
+            }
+        };
+        let caret = current_buffer.selection.main_cursor_offset();
+        // Either way, the search wraps around the ends of the loaded bytes
+        let current_match = match matches.partition_point(|m| m.start < caret) {
+            _ if matches.is_empty() => None,
+            i if forward && i == matches.len() => Some(0),
+            i if forward => Some(i),
+            0 => Some(matches.len() - 1),
+            i => Some(i - 1),
+        };
+        Search {
+            scanned: Some(self.pattern.clone()),
+            haystack: Some(haystack),
+            matches,
+            current_match,
+            error,
+            ..self
         }
     }
 }
@@ -184,12 +518,14 @@ impl Mode for Search {
         buffr_collection: &mut BuffrCollection,
         bytes_per_line: usize,
     ) -> Option<ModeTransition> {
+        let forward = self.forward();
         if let Some(action) = DEFAULT_MAPS.event_to_action(evt) {
             let mut cursor = self.cursor;
             let mut pattern = self.pattern.to_owned();
             let mut hex = self.hex;
+            let mut history_index = self.history_index;
 
-            if self.hex_half.is_some() {
+            if let Some(HexHalf::Byte(_)) = self.hex_half {
                 // hex insertion in progress: leave it as-is and skip to the next char
                 cursor += 1;
             }
@@ -216,6 +552,26 @@ impl Mode for Search {
                 Action::SwitchInputMode => {
                     hex = !hex;
                 }
+                Action::SwitchRegex => {
+                    pattern.regex = !pattern.regex;
+                }
+                Action::HistoryPrev | Action::HistoryNext => {
+                    let history = buffr_collection.search_history();
+                    history_index = match (action, history_index) {
+                        (Action::HistoryPrev, None) => Some(0),
+                        (Action::HistoryPrev, Some(i)) => Some(i + 1),
+                        (_, Some(i)) => i.checked_sub(1),
+                        (_, None) => None,
+                    };
+                    history_index =
+                        history_index.map(|i| cmp::min(i, history.len().saturating_sub(1)));
+                    // Past the newest entry, the prompt is empty again
+                    pattern = history_index
+                        .and_then(|i| history.get(i))
+                        .cloned()
+                        .unwrap_or_default();
+                    cursor = pattern.pieces.len();
+                }
                 Action::Cancel => return Some(ModeTransition::new_mode(Normal::new())),
                 Action::Finish => {
                     return Some(self.next.borrow().as_ref().unwrap().apply_search(
@@ -225,13 +581,22 @@ impl Mode for Search {
                     ))
                 }
             }
-            Some(ModeTransition::new_mode(Search {
-                pattern,
-                cursor,
-                hex,
-                hex_half: None, // after any action that doesn't insert a hex half, the hex half should be reset
-                next: RefCell::new(self.next.replace(None)),
-            })) // The old state won't be valid after this
+            Some(ModeTransition::new_mode(
+                Search {
+                    pattern,
+                    cursor,
+                    hex,
+                    hex_half: None, // after any action that doesn't insert a hex half, the hex half should be reset
+                    next: RefCell::new(self.next.replace(None)),
+                    haystack: self.haystack.clone(),
+                    scanned: self.scanned.clone(),
+                    matches: self.matches.clone(),
+                    current_match: None,
+                    history_index,
+                    error: self.error.clone(),
+                }
+                .with_matches(buffr_collection.current(), forward),
+            )) // The old state won't be valid after this
         } else if let Event::Key(KeyEvent {
             code: KeyCode::Char(ch),
             modifiers,
@@ -246,24 +611,64 @@ impl Mode for Search {
             if !self.hex {
                 cursor = pattern.insert_literal(cursor, *ch as u8);
             } else {
-                if !ch.is_ascii_hexdigit() {
-                    return None;
-                }
-                let hex_digit = ch.to_digit(16).unwrap() as u8;
-                if let Some(half) = hex_half {
-                    cursor = pattern.insert_half_literal(cursor, half | hex_digit);
-                    hex_half = None;
-                } else {
-                    pattern.insert_literal(cursor, hex_digit << 4); // Ignore cursor update
-                    hex_half = Some(hex_digit << 4);
+                // `?` leaves a nibble out, `&` starts a mask for the byte before the cursor
+                let nibble = match ch {
+                    '&' if hex_half.is_none() && cursor != 0 => {
+                        hex_half = Some(HexHalf::Mask(None));
+                        None
+                    }
+                    '?' if !matches!(hex_half, Some(HexHalf::Mask(_))) => None,
+                    ch if ch.is_ascii_hexdigit() => Some(ch.to_digit(16).unwrap() as u8),
+                    _ => return None,
+                };
+                match (hex_half, nibble) {
+                    (Some(HexHalf::Mask(None)), Some(digit)) => {
+                        hex_half = Some(HexHalf::Mask(Some(digit << 4)))
+                    }
+                    (Some(HexHalf::Mask(None)), None) => {}
+                    (Some(HexHalf::Mask(Some(high))), digit) => {
+                        let masked = &mut pattern.pieces[cursor - 1];
+                        *masked = PatternPiece::masked(masked.value(), high | digit.unwrap());
+                        hex_half = None;
+                    }
+                    (Some(HexHalf::Byte(half)), digit) => {
+                        let low_mask = if digit.is_some() { 0x0f } else { 0 };
+                        let piece = PatternPiece::masked(
+                            half.value() | digit.unwrap_or(0),
+                            (half.mask() & 0xf0) | low_mask,
+                        );
+                        cursor = pattern.insert_half(cursor, piece);
+                        hex_half = None;
+                    }
+                    (None, digit) => {
+                        // Stands for the whole byte, low nibble 0, until the low nibble is typed
+                        let half = match digit {
+                            Some(digit) => PatternPiece::Literal(digit << 4),
+                            None => PatternPiece::masked(0, 0x0f),
+                        };
+                        pattern.pieces.insert(cursor, half); // Ignore cursor update
+                        hex_half = Some(HexHalf::Byte(half));
+                    }
                 }
             }
-            Some(ModeTransition::new_mode(Search {
+            let search = Search {
                 pattern,
                 cursor,
                 hex_half,
                 hex: self.hex,
                 next: RefCell::new(self.next.replace(None)),
+                haystack: self.haystack.clone(),
+                scanned: self.scanned.clone(),
+                matches: self.matches.clone(),
+                current_match: self.current_match,
+                history_index: self.history_index,
+                error: self.error.clone(),
+            };
+            // A half typed hex byte or mask keeps the matches from before it
+            Some(ModeTransition::new_mode(if hex_half.is_some() {
+                search
+            } else {
+                search.with_matches(buffr_collection.current(), forward)
             })) // The old state won't be valid after this
         } else {
             None
@@ -274,3 +679,121 @@ impl Mode for Search {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modes::find::Find;
+
+    fn literal(text: &[u8]) -> Pattern {
+        Pattern {
+            pieces: text.iter().map(|&b| PatternPiece::Literal(b)).collect(),
+            regex: false,
+        }
+    }
+
+    fn buffer_with_caret(data: &[u8], caret: usize) -> CurrentBuffer {
+        let mut buffer = CurrentBuffer::from_data_and_path(data.to_vec(), None::<&str>);
+        buffer
+            .selection
+            .map_selections(|region| vec![region.jump_to(caret)]);
+        buffer
+    }
+
+    #[test]
+    fn test_find_all() {
+        assert_eq!(literal(b"ab").find_all(b"abxab"), Ok(vec![0..2, 3..5]));
+        assert_eq!(literal(b"aa").find_all(b"aaaaa"), Ok(vec![0..2, 2..4]));
+        assert_eq!(literal(b"").find_all(b"abc"), Ok(vec![]));
+
+        let masked = Pattern {
+            pieces: vec![
+                PatternPiece::Literal(b'a'),
+                PatternPiece::masked(0x30, 0xf0),
+            ],
+            regex: false,
+        };
+        assert_eq!(masked.find_all(b"a1aza9"), Ok(vec![0..2, 4..6]));
+
+        let regex = Pattern {
+            regex: true,
+            ..literal(b"[0-9]+")
+        };
+        assert_eq!(regex.find_all(b"a12b3"), Ok(vec![1..3, 4..5]));
+        assert!(Pattern {
+            regex: true,
+            ..literal(b"(")
+        }
+        .find_all(b"")
+        .is_err());
+    }
+
+    #[test]
+    fn test_to_text() {
+        let pieces = vec![
+            PatternPiece::Literal(b'*'),
+            PatternPiece::Wildcard,
+            PatternPiece::masked(0x40, 0xf0),
+            PatternPiece::masked(0x80, 0xc0),
+        ];
+        let pattern = Pattern {
+            pieces,
+            regex: false,
+        };
+        assert_eq!(pattern.to_text(), "search:2a ** 4? 80&c0");
+        let regex = Pattern {
+            regex: true,
+            ..literal(b"a*\\n")
+        };
+        assert_eq!(regex.to_text(), "regex:a*\\n");
+    }
+
+    #[test]
+    fn test_current_match() {
+        let data = b"ab ab ab";
+        let search = |forward, caret| {
+            let mut search = Search::new(Find { forward }, false);
+            search.pattern = literal(b"ab");
+            let forward = search.forward();
+            search.with_matches(&buffer_with_caret(data, caret), forward)
+        };
+        assert_eq!(search(true, 0).current_match, Some(0));
+        assert_eq!(search(true, 1).current_match, Some(1));
+        assert_eq!(search(true, 3).current_match, Some(1));
+        // Past the last match, the search wraps around to the first
+        assert_eq!(search(true, 7).current_match, Some(0));
+
+        // Searching backward picks the last match before the caret instead
+        assert_eq!(search(false, 3).current_match, Some(0));
+        assert_eq!(search(false, 4).current_match, Some(1));
+        assert_eq!(search(false, 7).current_match, Some(2));
+        // Before the first match, the search wraps around to the last
+        assert_eq!(search(false, 0).current_match, Some(2));
+
+        let mut search = Search::new(Find { forward: true }, false);
+        search.pattern = literal(b"cd");
+        assert_eq!(
+            search
+                .with_matches(&buffer_with_caret(data, 0), true)
+                .current_match,
+            None
+        );
+    }
+
+    #[test]
+    fn test_unchanged_pattern_keeps_its_matches() {
+        let buffer = buffer_with_caret(b"ab ab", 0);
+        let mut search = Search::new(Find { forward: true }, false);
+        search.pattern = literal(b"ab");
+        let search = search.with_matches(&buffer, true);
+        let matches = search.matches.clone();
+
+        let search = search.with_matches(&buffer, true);
+        assert!(Rc::ptr_eq(&matches, &search.matches));
+
+        let mut search = search;
+        search.pattern = literal(b"b");
+        let search = search.with_matches(&buffer, true);
+        assert_eq!(*search.matches, vec![1..2, 4..5]);
+    }
+}
@@ -45,13 +45,34 @@ lazy_static! {
     static ref DEFAULT_MAPS: KeyMap<Action> = default_maps();
 }
 
+fn describe(action: Action) -> Option<Cow<'static, str>> {
+    Some(match action {
+        Action::Width(1) => "bytes".into(),
+        Action::Width(2) => "words".into(),
+        Action::Width(4) => "dwords".into(),
+        Action::Width(8) => "qwords".into(),
+        Action::Width(16) => "owords".into(),
+        Action::Width(width) => format!("{} bytes", width).into(),
+        Action::Null => "null-delimited".into(),
+        Action::Search { hex: false } => "at text pattern".into(),
+        Action::Search { hex: true } => "at hex pattern".into(),
+    })
+}
+
+pub fn help() -> Vec<(String, String)> {
+    DEFAULT_MAPS.describe(describe)
+}
+
 impl SearchAcceptor for Split {
     fn apply_search(&self, pattern: Pattern, buffr_collection: &mut BuffrCollection, _: usize) -> ModeTransition {
         let current_buffer = buffr_collection.current_mut();
         if pattern.pieces.is_empty() {
             return ModeTransition::new_mode(Normal::new());
         }
-        let matched_ranges = pattern.map_selections_to_matches(current_buffer);
+        let matched_ranges = match pattern.map_selections_to_matches(current_buffer) {
+            Ok(matched_ranges) => matched_ranges,
+            Err(err) => return ModeTransition::new_mode_and_info(Normal::new(), err),
+        };
         let matched_len: usize = matched_ranges
             .iter()
             .flatten()
@@ -132,6 +153,7 @@ impl Mode for Split {
                         pieces: std::iter::repeat(PatternPiece::Literal(0u8))
                             .take(count)
                             .collect(),
+                        regex: false,
                     },
                     buffr_collection,
                     bytes_per_line,
@@ -145,6 +167,10 @@ impl Mode for Split {
         }
     }
 
+    fn hints(&self) -> Option<String> {
+        Some(DEFAULT_MAPS.hints(describe))
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
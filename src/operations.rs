@@ -97,6 +97,27 @@ pub fn change(base: &Rope, selection: &Selection, text: impl Into<Rope>) -> Rope
     builder.build()
 }
 
+pub fn overwrite(base: &Rope, selection: &Selection, text: impl Into<Rope>) -> RopeDelta {
+    let written = text.into();
+    let base_len = base.len();
+    let mut builder = DeltaBuilder::new(base_len);
+    let mut regions = selection.iter().peekable();
+    while let Some(region) = regions.next() {
+        // Stop short of the next caret so that the replaced intervals stay disjoint
+        // and write only as much as is replaced, so that the length doesn't change
+        let limit = regions.peek().map_or(base_len, |next| next.caret);
+        let end = std::cmp::max(
+            region.caret,
+            std::cmp::min(limit, region.caret + written.len()),
+        );
+        let iv = Interval::new(region.caret, end);
+        let fitted = Rope::from(written.slice_to_cow(..end - region.caret).into_owned());
+        builder.replace(iv, fitted.into_node());
+    }
+
+    builder.build()
+}
+
 pub fn overwrite_half(base: &Rope, selection: &Selection, top: u8) -> RopeDelta {
     let mut builder = DeltaBuilder::new(base.len());
     for region in selection.iter() {
@@ -126,3 +147,42 @@ pub fn replace(base: &Rope, selection: &Selection, ch: u8) -> RopeDelta {
 
     builder.build()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selection_at(carets: &[usize]) -> Selection {
+        let mut selection = Selection::new();
+        selection.map_selections(|_| {
+            carets
+                .iter()
+                .map(|&caret| SelRegion::new(caret, caret))
+                .collect()
+        });
+        selection
+    }
+
+    fn overwritten(data: &[u8], carets: &[usize], text: &[u8]) -> Vec<u8> {
+        let base = Rope::from(data.to_vec());
+        let delta = overwrite(&base, &selection_at(carets), text.to_vec());
+        base.apply_delta(&delta).slice_to_cow(..).into_owned()
+    }
+
+    #[test]
+    fn test_overwrite_stops_at_next_caret() {
+        assert_eq!(
+            overwritten(&[0; 8], &[0, 1, 2, 3], &[1; 32]),
+            [1, 1, 1, 1, 1, 1, 1, 1]
+        );
+        assert_eq!(overwritten(&[0; 8], &[0, 4], b"ab"), b"ab\0\0ab\0\0");
+        assert_eq!(overwritten(&[0; 4], &[0, 2], b"abc"), b"abab");
+    }
+
+    #[test]
+    fn test_overwrite_at_end() {
+        assert_eq!(overwritten(b"xyz", &[2], b"ab"), b"xya");
+        assert_eq!(overwritten(b"xyz", &[3], b"ab"), b"xyz");
+        assert_eq!(overwritten(b"xyz", &[0, 3], b"ab"), b"abz");
+    }
+}
@@ -0,0 +1,548 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::decoders::{self, Decoder};
+use crate::encoding::Encoding;
+use crate::inspector::{self, Field};
+use crate::marks::Marks;
+use crate::theme::{self, ByteColoring, ByteRule, ByteStyles, ColorDepth, Theme};
+
+/// How bytes yanked into the clipboard register are turned into clipboard text.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ClipboardEncoding {
+    Hex,
+    Raw,
+    Base64,
+}
+
+impl FromStr for ClipboardEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hex" => Ok(ClipboardEncoding::Hex),
+            "raw" => Ok(ClipboardEncoding::Raw),
+            "base64" => Ok(ClipboardEncoding::Base64),
+            _ => Err(format!("expected hex, raw or base64, got {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for ClipboardEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClipboardEncoding::Hex => write!(f, "hex"),
+            ClipboardEncoding::Raw => write!(f, "raw"),
+            ClipboardEncoding::Base64 => write!(f, "base64"),
+        }
+    }
+}
+
+/// Row width used until `bytes-per-line` is changed, and whenever there's no
+/// terminal to fit.
+pub const DEFAULT_BYTES_PER_LINE: usize = 0x10;
+pub const MAX_BYTES_PER_LINE: usize = 0x100;
+
+/// How many bytes each row of the view shows.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BytesPerLine {
+    /// The widest row that fits the terminal.
+    Auto,
+    Fixed(usize),
+}
+
+impl BytesPerLine {
+    /// The widths rows step through: powers of two up to 8, then multiples of 8.
+    pub fn widths() -> impl DoubleEndedIterator<Item = usize> {
+        (1..=MAX_BYTES_PER_LINE).filter(|n| n.is_power_of_two() || n % 8 == 0)
+    }
+
+    pub fn wider(bytes_per_line: usize) -> usize {
+        Self::widths()
+            .find(|&n| n > bytes_per_line)
+            .unwrap_or(bytes_per_line)
+    }
+
+    pub fn narrower(bytes_per_line: usize) -> usize {
+        Self::widths()
+            .rev()
+            .find(|&n| n < bytes_per_line)
+            .unwrap_or(bytes_per_line)
+    }
+}
+
+impl FromStr for BytesPerLine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "auto" {
+            return Ok(BytesPerLine::Auto);
+        }
+        let parsed = match s.strip_prefix("0x") {
+            Some(hex) => usize::from_str_radix(hex, 16),
+            None => s.parse(),
+        };
+        match parsed {
+            Ok(n) if (1..=MAX_BYTES_PER_LINE).contains(&n) => Ok(BytesPerLine::Fixed(n)),
+            _ => Err(format!(
+                "expected auto or a number from 1 to {}, got {}",
+                MAX_BYTES_PER_LINE, s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for BytesPerLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BytesPerLine::Auto => write!(f, "auto"),
+            BytesPerLine::Fixed(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// The order bytes of a group are shown in. Little endian shows each group as the
+/// value it encodes, most significant byte first.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Endian {
+    Big,
+    Little,
+}
+
+impl Endian {
+    pub fn other(self) -> Endian {
+        match self {
+            Endian::Big => Endian::Little,
+            Endian::Little => Endian::Big,
+        }
+    }
+}
+
+impl FromStr for Endian {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "big" | "be" => Ok(Endian::Big),
+            "little" | "le" => Ok(Endian::Little),
+            _ => Err(format!("expected big or little, got {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Endian {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Endian::Big => write!(f, "big"),
+            Endian::Little => write!(f, "little"),
+        }
+    }
+}
+
+/// How offsets are written in the offset column and the status line.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Radix {
+    Hex,
+    Dec,
+    Oct,
+}
+
+impl Radix {
+    pub fn format(self, value: usize) -> String {
+        match self {
+            Radix::Hex => format!("{:x}", value),
+            Radix::Dec => format!("{}", value),
+            Radix::Oct => format!("{:o}", value),
+        }
+    }
+
+    /// Like `format`, with the digits zero padded to `width` where that reads
+    /// naturally, as it does for hex and octal.
+    pub fn format_padded(self, value: usize, width: usize) -> String {
+        match self {
+            Radix::Hex => format!("{:0width$x}", value, width = width),
+            Radix::Dec => format!("{:width$}", value, width = width),
+            Radix::Oct => format!("{:0width$o}", value, width = width),
+        }
+    }
+
+    /// Formats a distance with its sign, `+` included.
+    pub fn format_signed(self, value: isize) -> String {
+        let sign = if value < 0 { '-' } else { '+' };
+        format!("{}{}", sign, self.format(value.unsigned_abs()))
+    }
+}
+
+impl FromStr for Radix {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hex" | "16" => Ok(Radix::Hex),
+            "dec" | "10" => Ok(Radix::Dec),
+            "oct" | "8" => Ok(Radix::Oct),
+            _ => Err(format!("expected hex, dec or oct, got {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Radix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Radix::Hex => write!(f, "hex"),
+            Radix::Dec => write!(f, "dec"),
+            Radix::Oct => write!(f, "oct"),
+        }
+    }
+}
+
+/// How the status line separates its segments.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StatuslineStyle {
+    /// Powerline arrows, which need a patched font.
+    Powerline,
+    /// Plain `|` characters.
+    Ascii,
+}
+
+impl FromStr for StatuslineStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "powerline" => Ok(StatuslineStyle::Powerline),
+            "ascii" => Ok(StatuslineStyle::Ascii),
+            _ => Err(format!("expected powerline or ascii, got {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for StatuslineStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StatuslineStyle::Powerline => write!(f, "powerline"),
+            StatuslineStyle::Ascii => write!(f, "ascii"),
+        }
+    }
+}
+
+/// What the minimap sidebar shows of each stretch of the file, if it's shown.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MinimapStyle {
+    Off,
+    /// Bits of entropy per byte, from padding up to compressed or encrypted data.
+    Entropy,
+    /// The class most of the bytes are in.
+    Class,
+}
+
+impl FromStr for MinimapStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(MinimapStyle::Off),
+            "entropy" => Ok(MinimapStyle::Entropy),
+            "class" => Ok(MinimapStyle::Class),
+            _ => Err(format!("expected off, entropy or class, got {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for MinimapStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MinimapStyle::Off => write!(f, "off"),
+            MinimapStyle::Entropy => write!(f, "entropy"),
+            MinimapStyle::Class => write!(f, "class"),
+        }
+    }
+}
+
+fn parse_address(value: &str) -> Result<usize, String> {
+    let parsed = match value.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    parsed.map_err(|_| {
+        format!(
+            "expected a decimal or 0x prefixed hex address, got {}",
+            value
+        )
+    })
+}
+
+fn parse_mark(value: &str) -> Result<Option<char>, String> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (None, _) => Ok(None),
+        (Some(name), None) if Marks::is_valid_name(name) => Ok(Some(name)),
+        _ => Err(format!("expected a mark letter, got {}", value)),
+    }
+}
+
+fn parse_group(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(group @ (1 | 2 | 4 | 8)) => Ok(group),
+        _ => Err(format!("expected 1, 2, 4 or 8, got {}", value)),
+    }
+}
+
+// `:set` trims its value, so spaces are written as `\s`
+fn parse_separator(value: &str) -> String {
+    value.replace("\\s", " ")
+}
+
+fn format_separator(value: &str) -> String {
+    value.replace(' ', "\\s")
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "on" | "true" | "yes" | "1" => Ok(true),
+        "off" | "false" | "no" | "0" => Ok(false),
+        _ => Err(format!("expected on or off, got {}", value)),
+    }
+}
+
+fn format_bool(value: bool) -> String {
+    if value { "on" } else { "off" }.to_string()
+}
+
+fn parse_command(value: &str) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+/// Editor settings changed at runtime with `:set`.
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub clipboard_encoding: ClipboardEncoding,
+    pub clipboard_copy: Option<String>,
+    pub clipboard_paste: Option<String>,
+    pub osc52: bool,
+    pub bytes_per_line: BytesPerLine,
+    pub group: usize,
+    pub group_separator: String,
+    pub endian: Endian,
+    pub encoding: Encoding,
+    pub inspector: Vec<Field>,
+    pub inspector_endian: Endian,
+    pub decoders: Vec<&'static Decoder>,
+    pub offset_column: bool,
+    pub offset_radix: Radix,
+    /// The address of the first byte, shown instead of its offset.
+    pub base: usize,
+    /// A mark to show offsets relative to, instead of addresses.
+    pub offset_mark: Option<char>,
+    pub theme_name: String,
+    pub colors: ColorDepth,
+    /// The theme as loaded, and as adapted to `colors` for drawing.
+    theme_source: Theme,
+    pub theme: Theme,
+    pub statusline: StatuslineStyle,
+    pub byte_coloring: ByteColoring,
+    pub byte_rules: Vec<ByteRule>,
+    /// Built from the theme, `byte_coloring` and `byte_rules`.
+    pub byte_styles: ByteStyles,
+    pub minimap: MinimapStyle,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        let theme = Theme::default().adapted(ColorDepth::Auto);
+        Options {
+            clipboard_encoding: ClipboardEncoding::Hex,
+            clipboard_copy: None,
+            clipboard_paste: None,
+            osc52: true,
+            bytes_per_line: BytesPerLine::Fixed(DEFAULT_BYTES_PER_LINE),
+            group: 1,
+            group_separator: " ".to_owned(),
+            endian: Endian::Big,
+            encoding: Encoding::Ascii,
+            inspector: Field::ALL.to_vec(),
+            inspector_endian: Endian::Big,
+            decoders: vec![],
+            offset_column: true,
+            offset_radix: Radix::Hex,
+            base: 0,
+            offset_mark: None,
+            theme_name: "default".to_owned(),
+            colors: ColorDepth::Auto,
+            theme_source: Theme::default(),
+            statusline: StatuslineStyle::Powerline,
+            byte_coloring: ByteColoring::Class,
+            byte_rules: vec![],
+            byte_styles: ByteStyles::new(&theme, ByteColoring::Class, &[], ColorDepth::Auto),
+            theme,
+            minimap: MinimapStyle::Off,
+        }
+    }
+}
+
+impl Options {
+    pub const NAMES: &'static [&'static str] = &[
+        "clipboard-encoding",
+        "clipboard-copy",
+        "clipboard-paste",
+        "osc52",
+        "bytes-per-line",
+        "group",
+        "group-separator",
+        "endian",
+        "encoding",
+        "inspector",
+        "inspector-endian",
+        "decoders",
+        "offset-column",
+        "offset-radix",
+        "base",
+        "offset-mark",
+        "theme",
+        "colors",
+        "statusline",
+        "byte-colors",
+        "byte-rules",
+        "minimap",
+    ];
+
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "clipboard-encoding" => self.clipboard_encoding = value.parse()?,
+            "clipboard-copy" => self.clipboard_copy = parse_command(value),
+            "clipboard-paste" => self.clipboard_paste = parse_command(value),
+            "osc52" => self.osc52 = parse_bool(value)?,
+            "bytes-per-line" | "bpl" => self.bytes_per_line = value.parse()?,
+            "group" => self.group = parse_group(value)?,
+            "group-separator" => self.group_separator = parse_separator(value),
+            "endian" => self.endian = value.parse()?,
+            "encoding" => self.encoding = value.parse()?,
+            "inspector" => self.inspector = inspector::parse_fields(value)?,
+            "inspector-endian" => self.inspector_endian = value.parse()?,
+            "decoders" => self.decoders = decoders::parse_decoders(value)?,
+            "offset-column" => self.offset_column = parse_bool(value)?,
+            "offset-radix" => self.offset_radix = value.parse()?,
+            "base" => self.base = parse_address(value)?,
+            "offset-mark" => self.offset_mark = parse_mark(value)?,
+            "theme" => {
+                self.theme_source = Theme::load(value)?;
+                self.theme_name = value.to_owned();
+                self.restyle();
+            }
+            "colors" => {
+                self.colors = value.parse()?;
+                self.restyle();
+            }
+            "statusline" => self.statusline = value.parse()?,
+            "byte-colors" => {
+                self.byte_coloring = value.parse()?;
+                self.restyle();
+            }
+            "byte-rules" => {
+                self.byte_rules = theme::parse_byte_rules(value)?;
+                self.restyle();
+            }
+            "minimap" => self.minimap = value.parse()?,
+            _ => return Err(format!("unknown option {}", name)),
+        }
+        Ok(())
+    }
+
+    fn restyle(&mut self) {
+        self.theme = self.theme_source.adapted(self.colors);
+        self.byte_styles = ByteStyles::new(
+            &self.theme,
+            self.byte_coloring,
+            &self.byte_rules,
+            self.colors,
+        );
+    }
+
+    /// The address shown for a file offset.
+    pub fn address(&self, offset: usize) -> usize {
+        self.base.wrapping_add(offset)
+    }
+
+    /// The file offset of an address, if it isn't below the base.
+    pub fn offset_of_address(&self, address: usize) -> Option<usize> {
+        address.checked_sub(self.base)
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        Some(match name {
+            "clipboard-encoding" => self.clipboard_encoding.to_string(),
+            "clipboard-copy" => self.clipboard_copy.clone().unwrap_or_default(),
+            "clipboard-paste" => self.clipboard_paste.clone().unwrap_or_default(),
+            "osc52" => format_bool(self.osc52),
+            "bytes-per-line" | "bpl" => self.bytes_per_line.to_string(),
+            "group" => self.group.to_string(),
+            "group-separator" => format_separator(&self.group_separator),
+            "endian" => self.endian.to_string(),
+            "encoding" => self.encoding.to_string(),
+            "inspector" => inspector::format_fields(&self.inspector),
+            "inspector-endian" => self.inspector_endian.to_string(),
+            "decoders" => decoders::format_decoders(&self.decoders),
+            "offset-column" => format_bool(self.offset_column),
+            "offset-radix" => self.offset_radix.to_string(),
+            "base" => format!("0x{:x}", self.base),
+            "offset-mark" => self.offset_mark.map(String::from).unwrap_or_default(),
+            "theme" => self.theme_name.clone(),
+            "colors" => self.colors.to_string(),
+            "statusline" => self.statusline.to_string(),
+            "byte-colors" => self.byte_coloring.to_string(),
+            "byte-rules" => theme::format_byte_rules(&self.byte_rules),
+            "minimap" => self.minimap.to_string(),
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes_per_line_steps() {
+        assert_eq!(BytesPerLine::wider(4), 8);
+        assert_eq!(BytesPerLine::wider(16), 24);
+        assert_eq!(BytesPerLine::wider(12), 16);
+        assert_eq!(BytesPerLine::narrower(16), 8);
+        assert_eq!(BytesPerLine::narrower(8), 4);
+        assert_eq!(BytesPerLine::narrower(1), 1);
+        assert_eq!(BytesPerLine::wider(MAX_BYTES_PER_LINE), MAX_BYTES_PER_LINE);
+
+        let mut options = Options::default();
+        options.set("bpl", "0x20").unwrap();
+        assert_eq!(options.bytes_per_line, BytesPerLine::Fixed(32));
+        assert_eq!(options.get("bytes-per-line").unwrap(), "32");
+        options.set("bpl", "auto").unwrap();
+        assert_eq!(options.bytes_per_line, BytesPerLine::Auto);
+        assert!(options.set("bpl", "0").is_err());
+    }
+
+    #[test]
+    fn test_group_separator_spaces() {
+        let mut options = Options::default();
+        assert_eq!(options.get("group-separator").unwrap(), "\\s");
+        options.set("group-separator", "\\s|\\s").unwrap();
+        assert_eq!(options.group_separator, " | ");
+        assert!(options.set("group", "3").is_err());
+    }
+
+    #[test]
+    fn test_base_address() {
+        let mut options = Options::default();
+        options.set("base", "0x08000000").unwrap();
+        assert_eq!(options.address(0x10), 0x08000010);
+        assert_eq!(options.offset_of_address(0x08000010), Some(0x10));
+        assert_eq!(options.offset_of_address(0x10), None);
+        assert_eq!(options.get("base").unwrap(), "0x8000000");
+        assert_eq!(Radix::Oct.format_padded(8, 4), "0010");
+        assert_eq!(Radix::Dec.format_signed(-12), "-12");
+        assert!(options.set("offset-mark", "ab").is_err());
+    }
+}
@@ -0,0 +1,103 @@
+/// How the screen is divided between panes. All panes are split the same way.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SplitDirection {
+    /// Panes stacked above one another.
+    Horizontal,
+    /// Panes side by side.
+    Vertical,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Pane {
+    pub id: usize,
+    /// The buffer shown. For the active pane, the current buffer is the one that
+    /// counts; this is only brought up to date when another pane is focused.
+    pub buffer: usize,
+}
+
+/// The panes the view is divided into, each with its own selection and scroll
+/// position. The active pane uses the current buffer and its selection; the
+/// others keep their selections in `CurrentBuffer::pane_selections`.
+#[derive(Debug, Clone)]
+pub struct Panes {
+    pub direction: SplitDirection,
+    list: Vec<Pane>,
+    active: usize,
+    next_id: usize,
+}
+
+impl Default for Panes {
+    fn default() -> Self {
+        Panes {
+            direction: SplitDirection::Horizontal,
+            list: vec![Pane { id: 0, buffer: 0 }],
+            active: 0,
+            next_id: 1,
+        }
+    }
+}
+
+impl Panes {
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    pub fn active(&self) -> Pane {
+        self.list[self.active]
+    }
+
+    pub fn get(&self, index: usize) -> Option<Pane> {
+        self.list.get(index).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Pane> {
+        self.list.iter()
+    }
+
+    /// Adds a pane after the active one, showing `buffer`, and returns its id.
+    /// The new pane isn't focused yet.
+    pub fn insert(&mut self, buffer: usize) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.list.insert(self.active + 1, Pane { id, buffer });
+        id
+    }
+
+    pub fn set_active(&mut self, index: usize, buffer: usize) {
+        self.active = index;
+        self.list[index].buffer = buffer;
+    }
+
+    pub fn set_buffer(&mut self, index: usize, buffer: usize) {
+        self.list[index].buffer = buffer;
+    }
+
+    /// Removes a pane other than the active one.
+    pub fn remove(&mut self, index: usize) -> Pane {
+        assert_ne!(index, self.active, "the active pane can't be removed");
+        if index < self.active {
+            self.active -= 1;
+        }
+        self.list.remove(index)
+    }
+
+    /// Points the panes showing a closed buffer at `replacement`, an index from
+    /// after the removal, and renumbers those showing later buffers.
+    pub fn remove_buffer(&mut self, buffer: usize, replacement: usize) {
+        for pane in self.list.iter_mut() {
+            if pane.buffer == buffer {
+                pane.buffer = replacement;
+            } else if pane.buffer > buffer {
+                pane.buffer -= 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::{HashMap, VecDeque};
+
+/// How many yanks and deletes the numbered registers `1`..`9` remember.
+pub const YANK_RING_SIZE: usize = 9;
+
+pub type RegisterContents = Vec<Vec<u8>>;
+
+/// Registers shared by every buffer in a `BuffrCollection`.
+///
+/// Letters and symbols name ordinary registers. Uppercase letters append to
+/// their lowercase counterpart. The digits `1`..`9` read from the yank ring,
+/// where `1` is the most recent yank or delete.
+#[derive(Debug, Default, Clone)]
+pub struct Registers {
+    named: HashMap<char, RegisterContents>,
+    ring: VecDeque<RegisterContents>,
+}
+
+impl Registers {
+    pub fn new() -> Registers {
+        Default::default()
+    }
+
+    pub fn get(&self, reg: char) -> Option<&RegisterContents> {
+        match reg.to_digit(10) {
+            Some(0) => None,
+            Some(n) => self.ring.get(n as usize - 1),
+            None => self.named.get(&reg.to_ascii_lowercase()),
+        }
+    }
+
+    pub fn yank(&mut self, reg: char, contents: RegisterContents) {
+        self.push_ring(contents.clone());
+        if !reg.is_ascii_digit() {
+            self.store(reg, contents);
+        }
+    }
+
+    /// Replaces a register's contents without touching the yank ring. Uppercase
+    /// letters append as they do for yanks.
+    pub fn set(&mut self, reg: char, contents: RegisterContents) -> Result<(), String> {
+        Registers::check_writable(reg)?;
+        self.store(reg, contents);
+        Ok(())
+    }
+
+    /// Fails for the digits, which only ever hold the yank ring.
+    pub fn check_writable(reg: char) -> Result<(), String> {
+        if reg.is_ascii_digit() {
+            return Err(format!("register {} only holds yanks and deletes", reg));
+        }
+        Ok(())
+    }
+
+    fn store(&mut self, reg: char, contents: RegisterContents) {
+        if !reg.is_ascii_uppercase() {
+            self.named.insert(reg, contents);
+            return;
+        }
+
+        // Uppercase registers append to each selection's existing entry
+        let existing = self.named.entry(reg.to_ascii_lowercase()).or_default();
+        for (i, selection) in contents.into_iter().enumerate() {
+            match existing.get_mut(i) {
+                Some(entry) => entry.extend(selection),
+                None => existing.push(selection),
+            }
+        }
+    }
+
+    fn push_ring(&mut self, contents: RegisterContents) {
+        self.ring.push_front(contents);
+        self.ring.truncate(YANK_RING_SIZE);
+    }
+
+    pub fn ring_len(&self) -> usize {
+        self.ring.len()
+    }
+
+    pub fn ring_entry(&self, index: usize) -> Option<&RegisterContents> {
+        self.ring.get(index)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.named.is_empty() && self.ring.is_empty()
+    }
+
+    /// All non-empty registers sorted by name, yank ring included.
+    pub fn iter(&self) -> impl Iterator<Item = (char, &RegisterContents)> {
+        let mut named = self.named.iter().map(|(&k, v)| (k, v)).collect::<Vec<_>>();
+        named.sort_unstable_by_key(|(k, _)| *k);
+        let ring = self
+            .ring
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (std::char::from_digit(i as u32 + 1, 10).unwrap(), v));
+        ring.chain(named)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uppercase_register_appends() {
+        let mut registers = Registers::new();
+        registers.yank('a', vec![vec![1], vec![2]]);
+        registers.yank('A', vec![vec![3]]);
+        assert_eq!(registers.get('a'), Some(&vec![vec![1, 3], vec![2]]));
+        assert_eq!(registers.get('A'), registers.get('a'));
+    }
+
+    #[test]
+    fn test_set_register() {
+        let mut registers = Registers::new();
+        registers.set('a', vec![b"ab".to_vec()]).unwrap();
+        registers.set('A', vec![b"c".to_vec()]).unwrap();
+        assert_eq!(registers.get('a'), Some(&vec![b"abc".to_vec()]));
+        assert!(registers.set('1', vec![b"d".to_vec()]).is_err());
+        assert_eq!(registers.get('1'), None);
+    }
+
+    #[test]
+    fn test_yank_ring() {
+        let mut registers = Registers::new();
+        for i in 0..=YANK_RING_SIZE as u8 {
+            registers.yank('"', vec![vec![i]]);
+        }
+        assert_eq!(registers.ring_len(), YANK_RING_SIZE);
+        assert_eq!(registers.get('1'), Some(&vec![vec![YANK_RING_SIZE as u8]]));
+        assert_eq!(registers.get('9'), Some(&vec![vec![1]]));
+        assert_eq!(registers.get('0'), None);
+    }
+}
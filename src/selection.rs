@@ -193,6 +193,16 @@ impl Selection {
             % self.regions.len();
         self.regions[self.main_selection].main = true;
     }
+
+    /// Moves the main selection to `region`, taking in any selections it overlaps.
+    pub fn move_main(&mut self, mut region: SelRegion) {
+        region.main = true;
+        self.regions.remove(self.main_selection);
+        self.regions
+            .retain(|r| r.max() < region.min() || r.min() > region.max());
+        self.main_selection = self.regions.partition_point(|r| r.min() < region.min());
+        self.regions.insert(self.main_selection, region);
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -225,6 +235,17 @@ pub enum Direction {
     Right,
 }
 
+impl Direction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Direction::Up => "up",
+            Direction::Down => "down",
+            Direction::Left => "left",
+            Direction::Right => "right",
+        }
+    }
+}
+
 impl SelRegion {
     pub fn new(caret: usize, tail: usize) -> Self {
         SelRegion {
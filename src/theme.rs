@@ -0,0 +1,792 @@
+use std::env;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crossterm::style::{Attribute, Attributes, Color, ContentStyle};
+
+/// How many colours the terminal shows. Theme colours it can't show are replaced
+/// by the nearest one it can.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ColorDepth {
+    /// Picked from `NO_COLOR`, `COLORTERM` and `TERM`.
+    Auto,
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    /// No colours at all; selections and carets are shown in reverse video.
+    None,
+}
+
+impl ColorDepth {
+    /// The depth `Auto` stands for in this environment.
+    pub fn resolve(self) -> ColorDepth {
+        if self != ColorDepth::Auto {
+            return self;
+        }
+        // https://no-color.org: set to anything but the empty string
+        if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            return ColorDepth::None;
+        }
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return ColorDepth::TrueColor;
+        }
+        match env::var("TERM").unwrap_or_default().as_str() {
+            "dumb" => ColorDepth::None,
+            term if term.contains("256color") => ColorDepth::Ansi256,
+            _ => ColorDepth::Ansi16,
+        }
+    }
+}
+
+impl FromStr for ColorDepth {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorDepth::Auto),
+            "truecolor" | "24bit" => Ok(ColorDepth::TrueColor),
+            "256" => Ok(ColorDepth::Ansi256),
+            "16" => Ok(ColorDepth::Ansi16),
+            "none" | "0" => Ok(ColorDepth::None),
+            _ => Err(format!(
+                "expected auto, truecolor, 256, 16 or none, got {}",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ColorDepth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ColorDepth::Auto => write!(f, "auto"),
+            ColorDepth::TrueColor => write!(f, "truecolor"),
+            ColorDepth::Ansi256 => write!(f, "256"),
+            ColorDepth::Ansi16 => write!(f, "16"),
+            ColorDepth::None => write!(f, "none"),
+        }
+    }
+}
+
+// The sixteen named colours in the order of their ANSI numbers, with the xterm
+// RGB values they're usually shown as
+const ANSI_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::DarkRed, (205, 0, 0)),
+    (Color::DarkGreen, (0, 205, 0)),
+    (Color::DarkYellow, (205, 205, 0)),
+    (Color::DarkBlue, (0, 0, 238)),
+    (Color::DarkMagenta, (205, 0, 205)),
+    (Color::DarkCyan, (0, 205, 205)),
+    (Color::Grey, (229, 229, 229)),
+    (Color::DarkGrey, (127, 127, 127)),
+    (Color::Red, (255, 0, 0)),
+    (Color::Green, (0, 255, 0)),
+    (Color::Yellow, (255, 255, 0)),
+    (Color::Blue, (92, 92, 255)),
+    (Color::Magenta, (255, 0, 255)),
+    (Color::Cyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+const COLOR_NAMES: [&str; 16] = [
+    "black",
+    "dark-red",
+    "dark-green",
+    "dark-yellow",
+    "dark-blue",
+    "dark-magenta",
+    "dark-cyan",
+    "grey",
+    "dark-grey",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+];
+
+// Channel values of the 6x6x6 cube in the 256 colour palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn ansi_to_rgb(value: u8) -> (u8, u8, u8) {
+    match value {
+        0..=15 => ANSI_COLORS[value as usize].1,
+        16..=231 => {
+            let index = value as usize - 16;
+            (
+                CUBE_LEVELS[index / 36],
+                CUBE_LEVELS[index / 6 % 6],
+                CUBE_LEVELS[index % 6],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (value - 232);
+            (level, level, level)
+        }
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let channel = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    channel(a.0, b.0) + channel(a.1, b.1) + channel(a.2, b.2)
+}
+
+fn nearest_cube_level(channel: u8) -> usize {
+    (0..CUBE_LEVELS.len())
+        .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - channel as i32).abs())
+        .unwrap()
+}
+
+fn rgb_to_ansi256(rgb: (u8, u8, u8)) -> u8 {
+    let (r, g, b) = (
+        nearest_cube_level(rgb.0),
+        nearest_cube_level(rgb.1),
+        nearest_cube_level(rgb.2),
+    );
+    let cube = (16 + 36 * r + 6 * g + b) as u8;
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let grey = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+    if distance(ansi_to_rgb(grey), rgb) < distance(ansi_to_rgb(cube), rgb) {
+        grey
+    } else {
+        cube
+    }
+}
+
+fn rgb_to_ansi16(rgb: (u8, u8, u8)) -> Color {
+    ANSI_COLORS
+        .iter()
+        .min_by_key(|(_, ansi)| distance(*ansi, rgb))
+        .unwrap()
+        .0
+}
+
+/// Replaces a colour the terminal can't show with the nearest one it can.
+pub fn downgrade(color: Color, depth: ColorDepth) -> Color {
+    match (depth, color) {
+        (_, Color::Reset) => Color::Reset,
+        (ColorDepth::None, _) => Color::Reset,
+        (ColorDepth::Ansi256, Color::Rgb { r, g, b }) => {
+            Color::AnsiValue(rgb_to_ansi256((r, g, b)))
+        }
+        (ColorDepth::Ansi16, Color::Rgb { r, g, b }) => rgb_to_ansi16((r, g, b)),
+        (ColorDepth::Ansi16, Color::AnsiValue(value)) => rgb_to_ansi16(ansi_to_rgb(value)),
+        _ => color,
+    }
+}
+
+fn parse_color(value: &str) -> Result<Color, String> {
+    if let Some(hex) = value.strip_prefix('#') {
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+        };
+        return match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(r), Some(g), Some(b)) => Ok(Color::Rgb { r, g, b }),
+            _ => Err(format!("expected #rrggbb, got {}", value)),
+        };
+    }
+    if let Ok(value) = value.parse::<u8>() {
+        return Ok(Color::AnsiValue(value));
+    }
+    if value == "default" {
+        return Ok(Color::Reset);
+    }
+    COLOR_NAMES
+        .iter()
+        .position(|&name| name == value)
+        .map(|i| ANSI_COLORS[i].0)
+        .ok_or_else(|| format!("unknown color {}", value))
+}
+
+fn parse_attribute(value: &str) -> Option<Attribute> {
+    Some(match value {
+        "bold" => Attribute::Bold,
+        "dim" => Attribute::Dim,
+        "italic" => Attribute::Italic,
+        "underline" => Attribute::Underlined,
+        "reverse" => Attribute::Reverse,
+        _ => return None,
+    })
+}
+
+/// Parses a style such as `white on blue`, `on #6e6110` or `dark-grey bold`: an
+/// optional foreground, an optional `on` background, then attributes.
+fn parse_style(value: &str) -> Result<ContentStyle, String> {
+    let mut style = ContentStyle::new();
+    let mut words = value.split_whitespace().peekable();
+    if let Some(word) = words.next_if(|&word| word != "on" && parse_attribute(word).is_none()) {
+        style.foreground_color = Some(parse_color(word)?);
+    }
+    if words.next_if_eq(&"on").is_some() {
+        let word = words.next().ok_or("expected a color after on")?;
+        style.background_color = Some(parse_color(word)?);
+    }
+    for word in words {
+        let attribute =
+            parse_attribute(word).ok_or_else(|| format!("unknown attribute {}", word))?;
+        style.attributes.set(attribute);
+    }
+    Ok(style)
+}
+
+// Attributes a style can turn on, and the ones that turn them off again
+const ATTRIBUTE_RESETS: [(Attribute, Attribute); 5] = [
+    (Attribute::Bold, Attribute::NormalIntensity),
+    (Attribute::Dim, Attribute::NormalIntensity),
+    (Attribute::Italic, Attribute::NoItalic),
+    (Attribute::Underlined, Attribute::NoUnderline),
+    (Attribute::Reverse, Attribute::NoReverse),
+];
+
+macro_rules! theme_roles {
+    ($($field:ident: $name:literal => $default:expr,)*) => {
+        /// The styles the view draws with, one per role. Byte class roles only
+        /// use their foreground, as the byte keeps the background of its selection.
+        #[derive(Debug, PartialEq, Eq, Clone)]
+        pub struct Theme {
+            $(pub $field: ContentStyle,)*
+        }
+
+        impl Default for Theme {
+            fn default() -> Self {
+                Theme {
+                    $($field: parse_style($default).unwrap(),)*
+                }
+            }
+        }
+
+        impl Theme {
+            fn role_mut(&mut self, name: &str) -> Option<&mut ContentStyle> {
+                match name {
+                    $($name => Some(&mut self.$field),)*
+                    _ => None,
+                }
+            }
+
+            fn styles_mut(&mut self) -> Vec<&mut ContentStyle> {
+                vec![$(&mut self.$field,)*]
+            }
+        }
+    };
+}
+
+theme_roles! {
+    null: "null" => "150",
+    printable: "printable" => "cyan",
+    whitespace: "whitespace" => "green",
+    other: "other" => "#e834d2",
+    non_ascii: "non-ascii" => "yellow",
+    text: "text" => "white on default",
+    offset: "offset" => "dark-grey",
+    divider: "divider" => "dark-grey",
+    active_divider: "active-divider" => "white",
+    mark: "mark" => "black on dark-magenta",
+    search_match: "search-match" => "black on dark-cyan",
+    selection: "selection" => "black on dark-grey",
+    main_selection: "main-selection" => "black on #6e6110",
+    caret: "caret" => "black on dark-grey",
+    main_caret: "main-caret" => "16 on #6b6c80",
+    empty_caret: "empty-caret" => "on green",
+    info: "info" => "white on blue",
+    prompt_cursor: "prompt-cursor" => "black on white",
+    prompt_escape: "prompt-escape" => "black on dark-grey",
+    prompt_wildcard: "prompt-wildcard" => "dark-red",
+    status_buffer: "status-buffer" => "white on red",
+    status_mode: "status-mode" => "16 on dark-yellow",
+    status_selections: "status-selections" => "16 on white",
+    status_position: "status-position" => "white on blue",
+    help: "help" => "white",
+    inspector_label: "inspector-label" => "dark-magenta on default",
+    inspector_value: "inspector-value" => "150 on default",
+    inspector_one: "inspector-one" => "blue on default",
+    inspector_error: "inspector-error" => "red on default",
+    inspector_unavailable: "inspector-unavailable" => "dark-grey on default",
+    inspector_selected: "inspector-selected" => "black on dark-magenta",
+}
+
+impl Theme {
+    /// Reads a theme: `default`, a path, or a name looked up as
+    /// `tofu/themes/<name>.theme` in the config directory.
+    pub fn load(name: &str) -> Result<Theme, String> {
+        if name == "default" {
+            return Ok(Theme::default());
+        }
+        let path = if name.contains('/') {
+            PathBuf::from(name)
+        } else {
+            crate::config::config_dir()
+                .ok_or("no config directory to look for themes in")?
+                .join("themes")
+                .join(format!("{}.theme", name))
+        };
+        let contents =
+            std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Theme::parse(&contents).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Parses `role = style` lines over the default theme. Blank lines and lines
+    /// starting with `#` are skipped.
+    pub fn parse(contents: &str) -> Result<Theme, String> {
+        let mut theme = Theme::default();
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (role, style) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected role = style", i + 1))?;
+            let slot = theme
+                .role_mut(role.trim())
+                .ok_or_else(|| format!("line {}: unknown role {}", i + 1, role.trim()))?;
+            *slot = parse_style(style).map_err(|e| format!("line {}: {}", i + 1, e))?;
+        }
+        Ok(theme)
+    }
+
+    /// The theme as drawn on a terminal with `depth` colours. Without colours,
+    /// styles with a background are shown reversed, and carets underlined too.
+    pub fn adapted(&self, depth: ColorDepth) -> Theme {
+        let depth = depth.resolve();
+        let mut theme = self.clone();
+        if depth == ColorDepth::None {
+            for style in theme.styles_mut() {
+                if style
+                    .background_color
+                    .is_some_and(|color| color != Color::Reset)
+                {
+                    style.attributes.set(Attribute::Reverse);
+                }
+            }
+            for style in [
+                &mut theme.caret,
+                &mut theme.main_caret,
+                &mut theme.empty_caret,
+            ] {
+                style.attributes.set(Attribute::Underlined);
+            }
+        }
+
+        let mut used = Attributes::default();
+        for style in theme.styles_mut() {
+            style.foreground_color = style.foreground_color.map(|color| downgrade(color, depth));
+            style.background_color = style.background_color.map(|color| downgrade(color, depth));
+            used.extend(style.attributes);
+        }
+        // Styles are switched between without a full reset, so each one has to
+        // turn off what the others turn on
+        for style in theme.styles_mut() {
+            let attributes = style.attributes;
+            // Bold and dim share the attribute that turns them off
+            let intense = attributes.has(Attribute::Bold) || attributes.has(Attribute::Dim);
+            for (on, off) in ATTRIBUTE_RESETS {
+                let keeps_intensity = off == Attribute::NormalIntensity && intense;
+                if used.has(on) && !attributes.has(on) && !keeps_intensity {
+                    style.attributes.set(off);
+                }
+            }
+        }
+        theme
+    }
+
+    /// The colour bytes of a class are drawn in.
+    pub fn class_color(&self, class: ByteClass) -> Color {
+        let style = match class {
+            ByteClass::Null => &self.null,
+            ByteClass::Printable => &self.printable,
+            ByteClass::Whitespace => &self.whitespace,
+            ByteClass::Other => &self.other,
+            ByteClass::NonAscii => &self.non_ascii,
+        };
+        style.foreground_color.unwrap_or(Color::Reset)
+    }
+
+    pub fn byte_color(&self, byte: u8) -> Color {
+        self.class_color(ByteClass::of(byte))
+    }
+}
+
+/// The classes bytes are coloured by.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ByteClass {
+    Null,
+    Printable,
+    Whitespace,
+    Other,
+    NonAscii,
+}
+
+impl ByteClass {
+    pub const ALL: [ByteClass; 5] = [
+        ByteClass::Null,
+        ByteClass::Printable,
+        ByteClass::Whitespace,
+        ByteClass::Other,
+        ByteClass::NonAscii,
+    ];
+
+    /// A character standing for the class where colours can't.
+    pub fn glyph(self) -> &'static str {
+        match self {
+            ByteClass::Null => "0",
+            ByteClass::Printable => "a",
+            ByteClass::Whitespace => "_",
+            ByteClass::Other => ".",
+            ByteClass::NonAscii => "#",
+        }
+    }
+
+    pub fn of(byte: u8) -> ByteClass {
+        if byte == 0x00 {
+            ByteClass::Null
+        } else if byte.is_ascii_graphic() {
+            ByteClass::Printable
+        } else if byte.is_ascii_whitespace() {
+            ByteClass::Whitespace
+        } else if byte.is_ascii() {
+            ByteClass::Other
+        } else {
+            ByteClass::NonAscii
+        }
+    }
+}
+
+/// How bytes are coloured before any byte rules apply.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ByteColoring {
+    /// By class: null, printable, whitespace, other and non-ASCII.
+    Class,
+    /// By value, from dark blue for low bytes to red for high ones.
+    Gradient,
+}
+
+impl FromStr for ByteColoring {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "class" => Ok(ByteColoring::Class),
+            "gradient" => Ok(ByteColoring::Gradient),
+            _ => Err(format!("expected class or gradient, got {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for ByteColoring {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ByteColoring::Class => write!(f, "class"),
+            ByteColoring::Gradient => write!(f, "gradient"),
+        }
+    }
+}
+
+// Byte values and the colours the gradient passes through at them
+const GRADIENT_STOPS: [(u8, (u8, u8, u8)); 5] = [
+    (0x00, (48, 48, 72)),
+    (0x40, (40, 100, 220)),
+    (0x80, (40, 190, 120)),
+    (0xc0, (230, 200, 40)),
+    (0xff, (240, 60, 50)),
+];
+
+/// The colour of `byte` in the `gradient` byte colouring, also used for other
+/// levels from 0 to 255.
+pub fn gradient_color(byte: u8) -> Color {
+    let i = GRADIENT_STOPS
+        .iter()
+        .rposition(|&(at, _)| at <= byte)
+        .unwrap()
+        .min(3);
+    let ((from, a), (to, b)) = (GRADIENT_STOPS[i], GRADIENT_STOPS[i + 1]);
+    let mix = |x: u8, y: u8| {
+        let t = (byte - from) as i32;
+        (x as i32 + (y as i32 - x as i32) * t / (to - from) as i32) as u8
+    };
+    Color::Rgb {
+        r: mix(a.0, b.0),
+        g: mix(a.1, b.1),
+        b: mix(a.2, b.2),
+    }
+}
+
+/// A style for every byte in `start..=end`, drawn over the class or gradient colour.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ByteRule {
+    pub start: u8,
+    pub end: u8,
+    pub style: ContentStyle,
+}
+
+fn parse_byte(value: &str) -> Result<u8, String> {
+    let digits = value.trim();
+    let digits = digits.strip_prefix("0x").unwrap_or(digits);
+    u8::from_str_radix(digits, 16).map_err(|_| format!("expected a hex byte, got {}", value))
+}
+
+/// Parses rules such as `ff:dark-grey,cc:black on yellow,80-9f:magenta bold`. Later
+/// rules win where ranges overlap.
+pub fn parse_byte_rules(value: &str) -> Result<Vec<ByteRule>, String> {
+    value
+        .split(',')
+        .filter(|rule| !rule.trim().is_empty())
+        .map(|rule| {
+            let (range, style) = rule
+                .split_once(':')
+                .ok_or_else(|| format!("expected <byte>[-<byte>]:<style>, got {}", rule))?;
+            let (start, end) = match range.split_once('-') {
+                Some((start, end)) => (parse_byte(start)?, parse_byte(end)?),
+                None => (parse_byte(range)?, parse_byte(range)?),
+            };
+            if start > end {
+                return Err(format!("empty byte range {}", range));
+            }
+            Ok(ByteRule {
+                start,
+                end,
+                style: parse_style(style)?,
+            })
+        })
+        .collect()
+}
+
+fn format_color(color: Color) -> String {
+    match color {
+        Color::Reset => "default".to_owned(),
+        Color::Rgb { r, g, b } => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        Color::AnsiValue(value) => value.to_string(),
+        named => ANSI_COLORS
+            .iter()
+            .position(|&(ansi, _)| ansi == named)
+            .map(|i| COLOR_NAMES[i].to_owned())
+            .unwrap_or_default(),
+    }
+}
+
+fn format_style(style: &ContentStyle) -> String {
+    let mut words = vec![];
+    if let Some(color) = style.foreground_color {
+        words.push(format_color(color));
+    }
+    if let Some(color) = style.background_color {
+        words.push(format!("on {}", format_color(color)));
+    }
+    for name in ["bold", "dim", "italic", "underline", "reverse"] {
+        if style.attributes.has(parse_attribute(name).unwrap()) {
+            words.push(name.to_owned());
+        }
+    }
+    words.join(" ")
+}
+
+pub fn format_byte_rules(rules: &[ByteRule]) -> String {
+    rules
+        .iter()
+        .map(|rule| {
+            let range = if rule.start == rule.end {
+                format!("{:02x}", rule.start)
+            } else {
+                format!("{:02x}-{:02x}", rule.start, rule.end)
+            };
+            format!("{}:{}", range, format_style(&rule.style))
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// The style of each byte value, from the class or gradient colour and the byte
+/// rules, as drawn on the terminal.
+#[derive(Debug, Clone)]
+pub struct ByteStyles {
+    styles: Vec<ContentStyle>,
+    /// Attributes turned on by some rule, to turn off for bytes without them.
+    used: Attributes,
+}
+
+impl ByteStyles {
+    /// `theme` is the theme already adapted to `depth`.
+    pub fn new(
+        theme: &Theme,
+        coloring: ByteColoring,
+        rules: &[ByteRule],
+        depth: ColorDepth,
+    ) -> Self {
+        let depth = depth.resolve();
+        let mut used = Attributes::default();
+        let styles = (0..=255u8)
+            .map(|byte| {
+                let color = match coloring {
+                    ByteColoring::Class => theme.byte_color(byte),
+                    ByteColoring::Gradient => downgrade(gradient_color(byte), depth),
+                };
+                let mut style = ContentStyle::new();
+                style.foreground_color = Some(color);
+                if let Some(rule) = rules
+                    .iter()
+                    .rev()
+                    .find(|rule| (rule.start..=rule.end).contains(&byte))
+                {
+                    let rule_style = rule.style;
+                    if let Some(fg) = rule_style.foreground_color {
+                        style.foreground_color = Some(downgrade(fg, depth));
+                    }
+                    style.background_color =
+                        rule_style.background_color.map(|bg| downgrade(bg, depth));
+                    style.attributes = rule_style.attributes;
+                    if depth == ColorDepth::None
+                        && rule_style
+                            .background_color
+                            .is_some_and(|bg| bg != Color::Reset)
+                    {
+                        style.attributes.set(Attribute::Reverse);
+                    }
+                    used.extend(style.attributes);
+                }
+                style
+            })
+            .collect();
+        ByteStyles { styles, used }
+    }
+
+    /// Whether drawing `byte` changes more than the foreground colour.
+    pub fn is_highlighted(&self, byte: u8) -> bool {
+        let style = &self.styles[byte as usize];
+        style.background_color.is_some() || !style.attributes.is_empty()
+    }
+
+    /// `base` with whatever a rule may have turned on turned off again.
+    pub fn restore(&self, base: &ContentStyle) -> ContentStyle {
+        let mut style = *base;
+        let intense = base.attributes.has(Attribute::Bold) || base.attributes.has(Attribute::Dim);
+        for (on, off) in ATTRIBUTE_RESETS {
+            let keeps_intensity = off == Attribute::NormalIntensity && intense;
+            if self.used.has(on) && !base.attributes.has(on) && !keeps_intensity {
+                style.attributes.set(off);
+            }
+        }
+        style
+    }
+
+    /// `base` with the colour of `byte`. A rule's background only shows where
+    /// `base` has none of its own, such as outside of selections.
+    pub fn apply(&self, byte: u8, base: &ContentStyle) -> ContentStyle {
+        let style = &self.styles[byte as usize];
+        let background = match base.background_color {
+            None | Some(Color::Reset) => style.background_color.or(base.background_color),
+            background => background,
+        };
+        let mut attributes = self.restore(base).attributes;
+        for (on, off) in ATTRIBUTE_RESETS {
+            if style.attributes.has(on) {
+                attributes.unset(off);
+                attributes.set(on);
+            }
+        }
+        ContentStyle {
+            foreground_color: style.foreground_color,
+            background_color: background,
+            attributes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_themes() {
+        let theme = Theme::parse("# comment\nmark = yellow on #102030 bold\n\nnull = 3\n").unwrap();
+        assert_eq!(theme.mark.foreground_color, Some(Color::Yellow));
+        assert_eq!(
+            theme.mark.background_color,
+            Some(Color::Rgb {
+                r: 16,
+                g: 32,
+                b: 48
+            })
+        );
+        assert!(theme.mark.attributes.has(Attribute::Bold));
+        assert_eq!(theme.byte_color(0), Color::AnsiValue(3));
+        assert!(Theme::parse("nope = red").is_err());
+        assert!(Theme::parse("mark = on").is_err());
+
+        let pink = Color::Rgb {
+            r: 232,
+            g: 52,
+            b: 210,
+        };
+        assert_eq!(downgrade(pink, ColorDepth::Ansi256), Color::AnsiValue(170));
+        let grey = Color::Rgb {
+            r: 128,
+            g: 128,
+            b: 128,
+        };
+        assert_eq!(downgrade(grey, ColorDepth::Ansi256), Color::AnsiValue(244));
+        assert_eq!(
+            downgrade(Color::AnsiValue(150), ColorDepth::Ansi16),
+            Color::DarkGrey
+        );
+        assert_eq!(downgrade(Color::Cyan, ColorDepth::Ansi16), Color::Cyan);
+
+        let plain = Theme::default().adapted(ColorDepth::None);
+        assert_eq!(plain.printable.foreground_color, Some(Color::Reset));
+        assert!(plain.main_caret.attributes.has(Attribute::Reverse));
+        assert!(plain.main_caret.attributes.has(Attribute::Underlined));
+        assert!(plain.text.attributes.has(Attribute::NoReverse));
+        assert!(plain.text.attributes.has(Attribute::NoUnderline));
+
+        let rules = parse_byte_rules("80-9f:magenta,cc:black on yellow bold,90:red").unwrap();
+        assert_eq!(
+            format_byte_rules(&rules),
+            "80-9f:magenta,cc:black on yellow bold,90:red"
+        );
+        assert!(parse_byte_rules("9f-80:red").is_err());
+        assert!(parse_byte_rules("ff").is_err());
+        let theme = Theme::default();
+        let styles = ByteStyles::new(&theme, ByteColoring::Class, &rules, ColorDepth::TrueColor);
+        assert_eq!(
+            styles.apply(0x85, &theme.text).foreground_color,
+            Some(Color::Magenta)
+        );
+        assert_eq!(
+            styles.apply(0x90, &theme.text).foreground_color,
+            Some(Color::Red)
+        );
+        let int3 = styles.apply(0xcc, &theme.text);
+        assert_eq!(int3.background_color, Some(Color::Yellow));
+        assert!(int3.attributes.has(Attribute::Bold));
+        assert_eq!(
+            styles.apply(0xcc, &theme.selection).background_color,
+            theme.selection.background_color
+        );
+        assert!(styles
+            .apply(b'a', &theme.text)
+            .attributes
+            .has(Attribute::NormalIntensity));
+
+        let gradient = ByteStyles::new(&theme, ByteColoring::Gradient, &[], ColorDepth::TrueColor);
+        assert_eq!(
+            gradient.apply(0x40, &theme.text).foreground_color,
+            Some(Color::Rgb {
+                r: 40,
+                g: 100,
+                b: 220
+            })
+        );
+        assert_eq!(
+            gradient.apply(0xff, &theme.text).foreground_color,
+            Some(Color::Rgb {
+                r: 240,
+                g: 60,
+                b: 50
+            })
+        );
+    }
+}